@@ -0,0 +1,53 @@
+use cache_lite::{Cache, CacheConfig, MemoryStorage};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+use std::sync::Arc;
+
+fn memory_cache() -> Cache {
+    let mut config = CacheConfig::default();
+    config.path.linux = "/bench".to_string();
+    config.path.windows = "C:/bench".to_string();
+    config.path.unix = "/bench".to_string();
+    config.path.default = "/bench".to_string();
+    config.format.filename = "r{name}.{id}.{time}.cache".to_string();
+    Cache::with_storage(config, Arc::new(MemoryStorage::new())).unwrap()
+}
+
+fn bench_create(c: &mut Criterion) {
+    c.bench_function("create", |b| {
+        b.iter_batched(
+            memory_cache,
+            |mut cache| {
+                for i in 0..100 {
+                    black_box(cache.create(&format!("entry{}", i), None).unwrap());
+                }
+                cache
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("create_custom_config", |b| {
+        b.iter_batched(
+            memory_cache,
+            |mut cache| {
+                for i in 0..100 {
+                    let custom = Some(r#"{"format": {"filename": "{name}.bin"}}"#);
+                    black_box(cache.create(&format!("entry{}", i), custom).unwrap());
+                }
+                cache
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_get(c: &mut Criterion) {
+    let mut cache = memory_cache();
+    for i in 0..1000 {
+        cache.create(&format!("entry{}", i), None).unwrap();
+    }
+    c.bench_function("get", |b| b.iter(|| black_box(cache.get(black_box("entry500")).unwrap())));
+}
+
+criterion_group!(benches, bench_create, bench_get);
+criterion_main!(benches);
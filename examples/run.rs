@@ -1,15 +1,16 @@
 use std::error::Error;
+use std::sync::Arc;
 
 use cache_lite::{
     Cache, CacheConfig, CacheObject, CacheResult
 };
 
-fn save(cache: &mut Cache, strings: &str) -> CacheResult<CacheObject> {
-    Ok(cache.create(strings, None)?)
+fn save(cache: &mut Cache, strings: &str) -> CacheResult<Arc<CacheObject>> {
+    cache.create(strings, None)
 }
 
 fn read(obj: &CacheObject) -> CacheResult<String> {
-    Ok(obj.get_string()?)
+    obj.get_string()
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
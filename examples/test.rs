@@ -1,4 +1,5 @@
 use cache_lite::{Cache, CacheConfig, CacheObject};
+use std::sync::Arc;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = CacheConfig::new(r#"
@@ -15,7 +16,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let mut cache: Cache = Cache::new(config)?;
     
-    let cache1: CacheObject = cache.create("hello_rust_cache", None)?;
+    let cache1: Arc<CacheObject> = cache.create("hello_rust_cache", None)?;
     println!("Cache1 Name: {}", cache1.name());
     println!("Cache1 Path: {}", cache1.path().display());
     println!("Cache1 ID: {}", cache1.id());
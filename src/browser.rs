@@ -0,0 +1,451 @@
+/*
+ * @filename: browser.rs
+ * @description: Browser storage backend persisting to OPFS or IndexedDB
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::storage::{MemoryStorage, Storage};
+use js_sys::{Array, Promise, Reflect, Uint8Array};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemGetFileOptions, FileSystemWritableFileStream,
+    IdbDatabase, IdbFactory, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode, StorageManager,
+};
+
+/// Object store of the IndexedDB fallback
+const OBJECT_STORE: &str = "entries";
+
+/// Next key of `HANDLES`
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Browser handles of opened storages
+    ///
+    /// JavaScript objects cannot leave the thread that created them, so
+    /// `BrowserStorage` (which must be `Send + Sync`) only keeps a key.
+    static HANDLES: RefCell<HashMap<u64, Handle>> = RefCell::new(HashMap::new());
+}
+
+/// Where a `BrowserStorage` persists its entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserBackend {
+    /// A directory of the Origin Private File System
+    Opfs,
+    /// An IndexedDB database, used where OPFS is unavailable
+    IndexedDb,
+    /// Nothing is persisted, see `BrowserStorage::detached()`
+    Detached,
+}
+
+/// Browser handle behind a `BrowserBackend`
+#[derive(Clone)]
+enum Handle {
+    Opfs(FileSystemDirectoryHandle),
+    IndexedDb(IdbDatabase),
+}
+
+/// Entries and the paths changed since the last `sync()`
+#[derive(Debug, Default)]
+struct Inner {
+    entries: MemoryStorage,
+    dirty: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl Inner {
+    fn mark(&self, path: &Path) {
+        self.dirty.lock().unwrap_or_else(|e| e.into_inner()).insert(path.to_path_buf());
+    }
+
+    fn take_dirty(&self) -> BTreeSet<PathBuf> {
+        std::mem::take(&mut *self.dirty.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Storage backend for `wasm32-unknown-unknown` apps running in a browser
+///
+/// Browser storage APIs are asynchronous while `Storage` is not, so
+/// entries are served from memory: `open()` loads every entry of the
+/// Origin Private File System directory (or of the IndexedDB database
+/// where OPFS is missing), and changes are written back by `sync()`.
+/// `Storage::flush()` (and so `Cache::flush()`) schedules a `sync()` on
+/// the browser's event loop. Use it with `Cache::with_storage()`:
+///
+/// ```ignore
+/// let storage = BrowserStorage::open("my-app").await?;
+/// let mut cache = Cache::with_storage(config, Arc::new(storage.clone()))?;
+/// cache.index_all()?;
+/// ```
+#[derive(Clone)]
+pub struct BrowserStorage {
+    inner: Arc<Inner>,
+    backend: BrowserBackend,
+    /// Key of the browser handle in `HANDLES`, 0 when detached
+    handle: u64,
+}
+
+impl fmt::Debug for BrowserStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BrowserStorage")
+            .field("backend", &self.backend)
+            .field("pending", &self.pending())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BrowserStorage {
+    /// Opens the browser storage named `name` and loads its entries
+    ///
+    /// Uses the OPFS directory `name`, falling back to the IndexedDB
+    /// database `name` where OPFS is not available (older browsers,
+    /// private windows). Only works on `wasm32` targets running in a
+    /// browser or worker; elsewhere it fails with `Unsupported`.
+    ///
+    /// # Parameters
+    /// - `name: &str` - Directory or database name
+    ///
+    /// # Returns
+    /// `io::Result<BrowserStorage>` - Storage holding the persisted entries
+    pub async fn open(name: &str) -> io::Result<Self> {
+        if !cfg!(target_arch = "wasm32") {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Browser storage is only available on wasm32 targets",
+            ));
+        }
+        let inner = Arc::new(Inner::default());
+        let (backend, handle) = match open_opfs(name).await {
+            Ok(dir) => (BrowserBackend::Opfs, Handle::Opfs(dir)),
+            Err(_) => (BrowserBackend::IndexedDb, Handle::IndexedDb(open_indexed_db(name).await?)),
+        };
+        for (key, content) in load(&handle).await? {
+            if let Some(path) = decode_key(&key) {
+                inner.entries.write(&path, &content)?;
+            }
+        }
+        let id = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        HANDLES.with(|handles| handles.borrow_mut().insert(id, handle));
+        Ok(BrowserStorage { inner, backend, handle: id })
+    }
+
+    /// Creates a storage that keeps its entries in memory only
+    ///
+    /// Changes are still tracked by `pending()`; `sync()` discards them.
+    ///
+    /// # Returns
+    /// New empty BrowserStorage
+    pub fn detached() -> Self {
+        BrowserStorage {
+            inner: Arc::new(Inner::default()),
+            backend: BrowserBackend::Detached,
+            handle: 0,
+        }
+    }
+
+    /// Returns where the entries are persisted
+    ///
+    /// # Returns
+    /// `BrowserBackend` - OPFS, IndexedDB or detached
+    pub fn backend(&self) -> BrowserBackend {
+        self.backend
+    }
+
+    /// Returns the number of entries changed since the last `sync()`
+    ///
+    /// # Returns
+    /// `usize` - Entries written or removed but not persisted yet
+    pub fn pending(&self) -> usize {
+        self.inner.dirty.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Writes every pending change to the browser storage
+    ///
+    /// Must run on the thread that called `open()`. Changes that could
+    /// not be written stay pending for the next call.
+    ///
+    /// # Returns
+    /// `io::Result<usize>` - Number of entries persisted
+    pub async fn sync(&self) -> io::Result<usize> {
+        sync(Arc::clone(&self.inner), self.handle).await
+    }
+}
+
+/// Persists the dirty entries of `inner` through the handle `id`
+async fn sync(inner: Arc<Inner>, id: u64) -> io::Result<usize> {
+    let dirty = inner.take_dirty();
+    if id == 0 {
+        return Ok(dirty.len());
+    }
+    let Some(handle) = HANDLES.with(|handles| handles.borrow().get(&id).cloned()) else {
+        inner.dirty.lock().unwrap_or_else(|e| e.into_inner()).extend(dirty);
+        return Err(io::Error::other("Browser storage is used outside the thread that opened it"));
+    };
+
+    let mut persisted = 0;
+    let mut remaining = dirty.into_iter();
+    while let Some(path) = remaining.next() {
+        let key = encode_key(&path);
+        let result = match inner.entries.read(&path) {
+            Ok(content) => put(&handle, &key, &content).await,
+            Err(_) => delete(&handle, &key).await,
+        };
+        if let Err(e) = result {
+            let mut dirty = inner.dirty.lock().unwrap_or_else(|e| e.into_inner());
+            dirty.insert(path);
+            dirty.extend(remaining);
+            return Err(e);
+        }
+        persisted += 1;
+    }
+    Ok(persisted)
+}
+
+impl Storage for BrowserStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.entries.read(path)
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        self.inner.entries.write(path, content)?;
+        self.inner.mark(path);
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.inner.entries.remove(path)?;
+        self.inner.mark(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.entries.exists(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.inner.entries.size(path)
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.inner.entries.create_file(path)?;
+        self.inner.mark(path);
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        self.inner.entries.create_new(path)?;
+        self.inner.mark(path);
+        Ok(())
+    }
+
+    /// Schedules a `sync()` on the browser's event loop
+    fn flush(&self) -> io::Result<()> {
+        if self.handle == 0 || !cfg!(target_arch = "wasm32") {
+            return Ok(());
+        }
+        let (inner, id) = (Arc::clone(&self.inner), self.handle);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = sync(inner, id).await {
+                log_warn!("failed to persist browser cache entries: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Browser storage key of an entry path, a single OPFS file name
+fn encode_key(path: &Path) -> String {
+    let mut key = String::new();
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '%' => key.push_str("%25"),
+            '/' => key.push_str("%2F"),
+            '\\' => key.push_str("%5C"),
+            c => key.push(c),
+        }
+    }
+    key
+}
+
+/// Entry path of a key written by `encode_key()`
+fn decode_key(key: &str) -> Option<PathBuf> {
+    let mut path = String::with_capacity(key.len());
+    let mut rest = key;
+    while let Some(start) = rest.find('%') {
+        path.push_str(&rest[..start]);
+        let escaped = rest.get(start..start + 3)?;
+        path.push(match escaped {
+            "%25" => '%',
+            "%2F" => '/',
+            "%5C" => '\\',
+            _ => return None,
+        });
+        rest = &rest[start + 3..];
+    }
+    path.push_str(rest);
+    Some(PathBuf::from(path))
+}
+
+fn js_error(e: JsValue) -> io::Error {
+    io::Error::other(format!("Browser storage error: {:?}", e))
+}
+
+async fn resolve(promise: Promise) -> io::Result<JsValue> {
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+/// Waits for an IndexedDB request to complete
+async fn request(request: &IdbRequest) -> io::Result<JsValue> {
+    let promise = Promise::new(&mut |resolve_fn, reject_fn| {
+        let done = request.clone();
+        let on_success = Closure::once_into_js(move |_event: JsValue| {
+            let _ = resolve_fn.call1(&JsValue::NULL, &done.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let on_error = Closure::once_into_js(move |event: JsValue| {
+            let _ = reject_fn.call1(&JsValue::NULL, &event);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    resolve(promise).await
+}
+
+/// Opens (creating it) the OPFS directory `name`
+async fn open_opfs(name: &str) -> io::Result<FileSystemDirectoryHandle> {
+    let navigator = Reflect::get(&js_sys::global(), &JsValue::from_str("navigator")).map_err(js_error)?;
+    let storage = Reflect::get(&navigator, &JsValue::from_str("storage")).map_err(js_error)?;
+    if storage.is_undefined() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "OPFS is not available"));
+    }
+    let root: FileSystemDirectoryHandle =
+        resolve(storage.unchecked_into::<StorageManager>().get_directory()).await?.unchecked_into();
+    let options = web_sys::FileSystemGetDirectoryOptions::new();
+    options.set_create(true);
+    Ok(resolve(root.get_directory_handle_with_options(name, &options)).await?.unchecked_into())
+}
+
+/// Opens (creating it) the IndexedDB database `name`
+async fn open_indexed_db(name: &str) -> io::Result<IdbDatabase> {
+    let factory = Reflect::get(&js_sys::global(), &JsValue::from_str("indexedDB")).map_err(js_error)?;
+    if factory.is_undefined() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "Neither OPFS nor IndexedDB is available"));
+    }
+    let open: IdbOpenDbRequest = factory.unchecked_into::<IdbFactory>().open(name).map_err(js_error)?;
+    let upgrade = open.clone();
+    let on_upgrade = Closure::once_into_js(move |_event: JsValue| {
+        if let Ok(db) = upgrade.result().map(JsCast::unchecked_into::<IdbDatabase>)
+            && !db.object_store_names().contains(OBJECT_STORE)
+        {
+            let _ = db.create_object_store(OBJECT_STORE);
+        }
+    });
+    open.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(request(&open).await?.unchecked_into())
+}
+
+fn object_store(db: &IdbDatabase, mode: IdbTransactionMode) -> io::Result<IdbObjectStore> {
+    db.transaction_with_str_and_mode(OBJECT_STORE, mode)
+        .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+        .map_err(js_error)
+}
+
+/// Reads every persisted entry
+async fn load(handle: &Handle) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    match handle {
+        Handle::Opfs(dir) => {
+            let keys = dir.keys();
+            loop {
+                let next = resolve(keys.next().map_err(js_error)?).await?;
+                if Reflect::get(&next, &JsValue::from_str("done")).map_err(js_error)?.is_truthy() {
+                    break;
+                }
+                let Some(key) = Reflect::get(&next, &JsValue::from_str("value")).map_err(js_error)?.as_string() else {
+                    continue;
+                };
+                let file: FileSystemFileHandle = resolve(dir.get_file_handle(&key)).await?.unchecked_into();
+                let blob: Blob = resolve(file.get_file()).await?.unchecked_into();
+                let content = Uint8Array::new(&resolve(blob.array_buffer()).await?).to_vec();
+                entries.push((key, content));
+            }
+        }
+        Handle::IndexedDb(db) => {
+            let store = object_store(db, IdbTransactionMode::Readonly)?;
+            let keys: Array = request(&store.get_all_keys().map_err(js_error)?).await?.unchecked_into();
+            let values: Array = request(&store.get_all().map_err(js_error)?).await?.unchecked_into();
+            for (key, value) in keys.iter().zip(values.iter()) {
+                if let Some(key) = key.as_string() {
+                    entries.push((key, value.unchecked_into::<Uint8Array>().to_vec()));
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Replaces the persisted content of `key`
+async fn put(handle: &Handle, key: &str, content: &[u8]) -> io::Result<()> {
+    match handle {
+        Handle::Opfs(dir) => {
+            let options = FileSystemGetFileOptions::new();
+            options.set_create(true);
+            let file: FileSystemFileHandle = resolve(dir.get_file_handle_with_options(key, &options)).await?.unchecked_into();
+            let stream: FileSystemWritableFileStream = resolve(file.create_writable()).await?.unchecked_into();
+            resolve(stream.write_with_u8_array(content).map_err(js_error)?).await?;
+            resolve(stream.close()).await?;
+        }
+        Handle::IndexedDb(db) => {
+            let store = object_store(db, IdbTransactionMode::Readwrite)?;
+            let value = Uint8Array::from(content);
+            request(&store.put_with_key(&value, &JsValue::from_str(key)).map_err(js_error)?).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the persisted content of `key`, if any
+async fn delete(handle: &Handle, key: &str) -> io::Result<()> {
+    match handle {
+        Handle::Opfs(dir) => {
+            // Removing a missing entry rejects with NotFoundError, which is fine
+            let _ = resolve(dir.remove_entry(key)).await;
+        }
+        Handle::IndexedDb(db) => {
+            let store = object_store(db, IdbTransactionMode::Readwrite)?;
+            request(&store.delete(&JsValue::from_str(key)).map_err(js_error)?).await?;
+        }
+    }
+    Ok(())
+}
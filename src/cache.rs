@@ -24,24 +24,394 @@
  * SOFTWARE.
  */
 
-use crate::config::CacheConfig;
+use crate::codec::{Codec, JsonCodec};
+use crate::dependency::{CascadePolicy, EntryGraph};
+use crate::config::{AppInfo, CacheConfig, CacheScope, CollisionPolicy, KeyEncoding, LongPathPolicy, MetadataMode, NameCase, Uniqueness};
 use crate::object::CacheObject;
-use crate::utils::{expand_path, validate_name};
+use crate::doctor::{self, DoctorReport};
+use crate::event::{CacheEvent, CacheObserver, EventBus};
+use crate::index::{IndexEntry, SharedIndex};
+use crate::key::{
+    is_truncated_key, truncate_key, validate_mapped_path, KeyMapper, NameMapping, MAX_KEY_LEN, MAX_PATH_LEN,
+};
+use crate::lock::{FileLock, CLAIM_FILE, CLAIM_STALE_AFTER};
+use crate::manifest::{sha256_hex, Manifest, ManifestEntry, ManifestSigner, MANIFEST_FILE};
+use crate::meta::{sidecar_path, InlineMetaStorage, SIDECAR_EXTENSION};
+use crate::middleware::{Middleware, MiddlewareStack, Operation};
+use crate::quarantine::Quarantine;
+use crate::registry::{PendingObjects, Registry};
+use crate::space::SpaceGuard;
+use crate::template::{suffixed, FilenameTemplate};
+use crate::throttle::{ThrottleState, ThrottledStorage};
+use crate::writeback::WriteBackStorage;
+use crate::storage::{FsStorage, MemoryStorage, Storage, TunedFsStorage};
+use crate::telemetry::OpSpan;
+#[cfg(feature = "notify")]
+use crate::watch::{DirWatcher, Invalidations, OwnChanges};
+use crate::utils::{
+    check_share_reachable, expand_path, file_from_name, name_from_file, parallel_map, resolve_cache_dir,
+    share_file,
+};
+#[cfg(feature = "glob")]
+use crate::utils::parse_name_pattern;
 use crate::{CacheError, CacheResult};
-use chrono::{DateTime, Local};
-use std::collections::HashMap;
-use std::time::SystemTime;
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::fmt;
+use std::ops::Index;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+use unicode_normalization::UnicodeNormalization;
 
-fn time_format(time: SystemTime, format: &str) -> String {
-    let datetime: DateTime<Local> = time.into();
-    datetime.format(format).to_string()
+/// Resolves the cache directory, rejecting an unknown `{cache_dir}`
+fn checked_cache_dir(config: &CacheConfig) -> CacheResult<PathBuf> {
+    let dir = resolve_cache_dir(config)?;
+    if dir.to_string_lossy().contains("{cache_dir}") {
+        return Err(CacheError::InvalidPath(
+            "Platform cache directory is unknown, call set_platform_cache_dir() first".to_string(),
+        ));
+    }
+    Ok(dir)
+}
+
+/// Default cap on the threads deleting files in `clear()` and `gc()`
+const DEFAULT_DELETE_PARALLELISM: usize = 4;
+
+/// Prefix of the directories `publish()` moves file sets into
+const PUBLISH_DIR_PREFIX: &str = ".publish-";
+
+/// Returns the path of an object file relative to the cache directory,
+/// leaving out the publish directory holding it
+fn published_relative(dir: &Path, path: &Path) -> PathBuf {
+    let Ok(relative) = path.strip_prefix(dir) else {
+        return PathBuf::from(path.file_name().unwrap_or_default());
+    };
+    let mut components = relative.components();
+    match components.next() {
+        Some(first) if first.as_os_str().to_string_lossy().starts_with(PUBLISH_DIR_PREFIX) => {
+            components.as_path().to_path_buf()
+        }
+        _ => relative.to_path_buf(),
+    }
+}
+
+/// Copies `source` to `dest` unless `dest` is already up to date, see `Cache::sync_to()`
+///
+/// Backends that cannot open entries as files are read in one piece.
+///
+/// # Returns
+/// `CacheResult<bool>` - Whether the file was copied
+fn mirror_file(storage: &dyn Storage, source: &Path, dest: &Path) -> CacheResult<bool> {
+    let source_modified = std::fs::metadata(source).and_then(|m| m.modified()).ok();
+    if let Ok(meta) = std::fs::metadata(dest) {
+        let unchanged = match source_modified {
+            Some(modified) => meta.len() == storage.size(source)? && meta.modified().ok() == Some(modified),
+            // Backends without timestamps are compared by content
+            None => std::fs::read(dest)? == storage.read(source)?,
+        };
+        if unchanged {
+            return Ok(false);
+        }
+    }
+
+    let mut temp_name = dest.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".sync-tmp");
+    let temp = dest.with_file_name(temp_name);
+    let mut file = std::fs::File::create(&temp)?;
+    let written = match storage.open(source) {
+        Ok(mut reader) => std::io::copy(&mut reader, &mut file).map(|_| ()),
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+            storage.read(source).and_then(|content| std::io::Write::write_all(&mut file, &content))
+        }
+        Err(e) => Err(e),
+    };
+    let written = written.and_then(|()| match source_modified {
+        Some(modified) => file.set_modified(modified),
+        None => Ok(()),
+    });
+    drop(file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(CacheError::Io(e));
+    }
+    std::fs::rename(&temp, dest)?;
+    Ok(true)
+}
+
+/// Creates a new publish directory in `dir`, removing empty earlier ones
+fn create_publish_dir(dir: &Path) -> CacheResult<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let mut last = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(n) = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str()?.strip_prefix(PUBLISH_DIR_PREFIX)?.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        last = last.max(n);
+        // Fails while the directory still holds published files
+        let _ = std::fs::remove_dir(&path);
+    }
+    for n in last + 1.. {
+        let generation = dir.join(format!("{}{}", PUBLISH_DIR_PREFIX, n));
+        match std::fs::create_dir(&generation) {
+            Ok(()) => return Ok(generation),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(CacheError::Io(e)),
+        }
+    }
+    unreachable!("some publish directory number is always free")
+}
+
+/// Runs a lookup through the middleware, telemetry and metrics of `get`
+///
+/// `lookup` and the quarantine see the canonical form of `name`.
+pub(crate) fn traced_get(
+    context: &CacheContext,
+    name: &str,
+    lookup: impl Fn(&str) -> Option<Arc<CacheObject>>,
+) -> CacheResult<Arc<CacheObject>> {
+    let name = &*context.canonical_name(name);
+    let mut span = OpSpan::start("get", name);
+    let start = Instant::now();
+    let result = context.middleware.run(&Operation::Get { name }, || {
+        lookup(name)
+            .filter(|obj| !context.quarantine.contains(name) && !context.space.is_evicted(obj.path()))
+            .ok_or_else(|| CacheError::EntryNotFound { name: name.to_string() })
+    });
+    metric_histogram!("cache_lite_get_latency_seconds", start.elapsed().as_secs_f64(), "op" => "lookup");
+    span.hit(result.is_ok());
+    result
+}
+
+/// State shared between a cache and the objects it hands out
+pub(crate) struct CacheContext {
+    pub(crate) events: EventBus,
+    pub(crate) middleware: MiddlewareStack,
+    pub(crate) codec: RwLock<Arc<dyn Codec>>,
+    pub(crate) storage: Arc<dyn Storage>,
+    pub(crate) graph: EntryGraph,
+    /// `io.read_buffer` of the current config
+    pub(crate) read_buffer: AtomicUsize,
+    /// `io.write_buffer` of the current config
+    pub(crate) write_buffer: AtomicUsize,
+    /// `limits.max_entry_bytes` of the current config, 0 when unlimited
+    pub(crate) max_entry_bytes: AtomicU64,
+    /// `metadata` of the current config
+    pub(crate) metadata: RwLock<MetadataMode>,
+    /// `app` of the current config, stamped into written entries
+    pub(crate) app: Arc<RwLock<Option<AppInfo>>>,
+    /// Header framing layer of `MetadataMode::Inline` caches
+    pub(crate) inline: Option<Arc<InlineMetaStorage>>,
+    /// Objects whose reads keep failing, see `quarantine_after`
+    pub(crate) quarantine: Quarantine,
+    /// `limits.min_free_bytes` checks and evicted files
+    pub(crate) space: SpaceGuard,
+    /// Whether the current config has `path.scope` set to `"system"`
+    pub(crate) shared: AtomicBool,
+    /// `normalize_names` of the current config
+    normalize_names: AtomicBool,
+    /// Whether `name_case` of the current config is `"insensitive"`
+    fold_case: AtomicBool,
+    /// Files of deleted objects removed once their last clone drops
+    deferred_deletes: Mutex<HashSet<PathBuf>>,
+    #[cfg(feature = "notify")]
+    pub(crate) own_changes: OwnChanges,
+    #[cfg(feature = "notify")]
+    pub(crate) invalidations: Invalidations,
+}
+
+impl CacheContext {
+    /// Returns the codec currently configured for the cache
+    pub(crate) fn codec(&self) -> Arc<dyn Codec> {
+        Arc::clone(&self.codec.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Returns the `metadata` mode currently configured for the cache
+    pub(crate) fn metadata(&self) -> MetadataMode {
+        *self.metadata.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the form of `name` objects are registered under
+    pub(crate) fn canonical_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        let name = if self.normalize_names.load(Ordering::Relaxed) && !unicode_normalization::is_nfc(name) {
+            Cow::Owned(name.nfc().collect())
+        } else {
+            Cow::Borrowed(name)
+        };
+        if self.fold_case.load(Ordering::Relaxed) && name.chars().any(char::is_uppercase) {
+            Cow::Owned(name.to_lowercase())
+        } else {
+            name
+        }
+    }
+
+    /// Records that the file at `path` is removed when its last clone drops
+    pub(crate) fn defer_delete(&self, path: &Path) {
+        self.deferred_deletes.lock().unwrap_or_else(|e| e.into_inner()).insert(path.to_path_buf());
+    }
+
+    /// Claims a deferred removal, false if a new entry took over the path
+    pub(crate) fn take_deferred_delete(&self, path: &Path) -> bool {
+        self.deferred_deletes.lock().unwrap_or_else(|e| e.into_inner()).remove(path)
+    }
+}
+
+impl CacheContext {
+    fn new(storage: Arc<dyn Storage>, config: &CacheConfig) -> Self {
+        let middleware = MiddlewareStack::default();
+        middleware.set_retry(config.retry.clone());
+        let app = Arc::new(RwLock::new(config.app.clone()));
+        let inline = (config.metadata == MetadataMode::Inline)
+            .then(|| Arc::new(InlineMetaStorage::new(storage.clone()).with_app(Arc::clone(&app))));
+        let storage = match &inline {
+            Some(inline) => Arc::clone(inline) as Arc<dyn Storage>,
+            None => storage,
+        };
+        CacheContext {
+            app,
+            inline,
+            quarantine: Quarantine::new(config.quarantine_after),
+            space: SpaceGuard::new(config),
+            deferred_deletes: Mutex::default(),
+            shared: AtomicBool::new(config.path.scope == CacheScope::System),
+            normalize_names: AtomicBool::new(config.normalize_names),
+            fold_case: AtomicBool::new(config.name_case == NameCase::Insensitive),
+            read_buffer: AtomicUsize::new(config.io.read_buffer),
+            write_buffer: AtomicUsize::new(config.io.write_buffer),
+            max_entry_bytes: AtomicU64::new(config.limits.max_entry_bytes.unwrap_or(0)),
+            metadata: RwLock::new(config.metadata),
+            events: EventBus::default(),
+            middleware,
+            codec: RwLock::new(Arc::new(JsonCodec)),
+            storage,
+            graph: EntryGraph::default(),
+            #[cfg(feature = "notify")]
+            own_changes: OwnChanges::default(),
+            #[cfg(feature = "notify")]
+            invalidations: Invalidations::default(),
+        }
+    }
+}
+
+impl fmt::Debug for CacheContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheContext")
+            .field("events", &self.events)
+            .field("middleware", &self.middleware)
+            .field("storage", &self.storage)
+            .field("graph", &self.graph)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Object count and total size of a cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cache objects
+    pub objects: usize,
+    /// Total size of the objects in bytes, missing entries count as 0
+    pub bytes: u64,
+    /// Write rate limit state, if `io.max_write_bytes_per_sec` is set
+    /// (always `None` in `CacheManager` totals)
+    pub throttle: Option<ThrottleState>,
+}
+
+/// One page of objects returned by `Cache::page()`
+#[derive(Debug, Clone)]
+pub struct CachePage {
+    /// Objects of this page, ordered by name
+    pub objects: Vec<Arc<CacheObject>>,
+    /// Cursor to pass to `Cache::page()` for the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// What `Cache::merge_from()` does when both caches hold an object name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing object
+    #[default]
+    Skip,
+    /// Replace the content of the existing object
+    Overwrite,
+    /// Import under the first free name of the form `name-1`, `name-2`, ...
+    Rename,
+}
+
+/// Generation floors below which objects are logically invalid
+#[derive(Debug, Default)]
+struct Generations {
+    /// Generation assigned to newly created objects
+    current: u64,
+    /// Cache-wide minimum generation
+    floor: u64,
+    /// Minimum generation per object name prefix
+    namespaces: BTreeMap<String, u64>,
+}
+
+impl Generations {
+    fn is_live(&self, obj: &CacheObject) -> bool {
+        obj.generation() >= self.floor
+            && self
+                .namespaces
+                .iter()
+                .all(|(prefix, floor)| obj.generation() >= *floor || !obj.name().starts_with(prefix.as_str()))
+    }
+}
+
+/// File of `Cache::publish()` and the object it becomes
+struct StagedFile {
+    name: String,
+    source: PathBuf,
+    /// Path relative to the publish directory
+    target: PathBuf,
+    /// Registered object whose file is replaced
+    replaced: Option<Arc<CacheObject>>,
+    id: u32,
+    shared: bool,
 }
 
 /// Main cache manager handling multiple cache objects
 pub struct Cache {
     config: CacheConfig,
-    objects: HashMap<String, CacheObject>,
-    next_id: u32
+    /// `config.format` compiled for `create()`
+    template: FilenameTemplate,
+    /// Expanded cache directory of `config.path`, resolved on first use
+    cache_dir: OnceLock<PathBuf>,
+    /// Registered objects, ordered by name for `page()`
+    objects: Registry,
+    /// `manifest.json` of an opened directory, read on the first `probe()` miss
+    lazy_manifest: OnceLock<HashMap<String, ManifestEntry>>,
+    next_id: AtomicU32,
+    context: Arc<CacheContext>,
+    in_memory: bool,
+    root: Option<PathBuf>,
+    base: Option<PathBuf>,
+    signer: Option<Arc<dyn ManifestSigner>>,
+    /// Replaces `format.filename` in `create()`, see `set_key_mapper()`
+    key_mapper: Option<Arc<dyn KeyMapper>>,
+    generations: Generations,
+    /// Objects replaced after a generation bump, awaiting `gc()`
+    retired: Vec<Arc<CacheObject>>,
+    /// Maximum number of threads deleting files in `clear()` and `gc()`
+    delete_parallelism: usize,
+    /// Registry shared with other processes, see `use_shared_index()`
+    index: Option<SharedIndex>,
+    /// Directory lock held since `claim_exclusive()`
+    claim: Option<FileLock>,
+    /// Cache consulted on misses, see `set_parent()`
+    parent: Option<Arc<Cache>>,
+    /// Whether parent hits are copied into this cache
+    promote_parent_hits: bool,
+    /// Parent hits copied into this cache, registered on the next change
+    promoted: PendingObjects,
+    #[cfg(feature = "notify")]
+    watcher: Option<DirWatcher>
 }
 
 impl Cache {
@@ -53,160 +423,1738 @@ impl Cache {
     /// # Returns
     /// New Cache instance
     pub fn new(config: CacheConfig) -> CacheResult<Self> {
+        if config.path.fail_fast_offline {
+            check_share_reachable(&expand_path(config.path.resolve()))?;
+        }
+        let mut storage: Arc<dyn Storage> = if config.io.tunes_storage() {
+            Arc::new(TunedFsStorage::new(config.io.clone()))
+        } else {
+            Arc::new(FsStorage)
+        };
+        if let Some(rate) = config.io.max_write_bytes_per_sec {
+            storage = Arc::new(ThrottledStorage::new(storage, rate));
+        }
+        if let Some(write_back) = &config.io.write_back {
+            storage = Arc::new(WriteBackStorage::new(storage, write_back.clone()));
+        }
+        Self::with_storage(config, storage)
+    }
+
+    /// Creates a new Cache storing its objects through a custom backend
+    ///
+    /// # Parameters
+    /// - `config: CacheConfig` - Cache configuration
+    /// - `storage: Arc<dyn Storage>` - Backend performing the I/O
+    ///
+    /// # Returns
+    /// New Cache instance
+    pub fn with_storage(config: CacheConfig, storage: Arc<dyn Storage>) -> CacheResult<Self> {
+        let context = Arc::new(CacheContext::new(storage, &config));
         Ok(Cache {
+            template: FilenameTemplate::new(&config.format.filename, &config.format.time),
+            cache_dir: OnceLock::new(),
+            objects: Registry::tracking(context.space.registered()),
+            context,
             config,
-            objects: HashMap::new(),
-            next_id: 1
+            lazy_manifest: OnceLock::new(),
+            next_id: AtomicU32::new(1),
+            in_memory: false,
+            root: None,
+            base: None,
+            signer: None,
+            key_mapper: None,
+            generations: Generations::default(),
+            retired: Vec::new(),
+            delete_parallelism: DEFAULT_DELETE_PARALLELISM,
+            index: None,
+            claim: None,
+            parent: None,
+            promote_parent_hits: false,
+            promoted: PendingObjects::default(),
+            #[cfg(feature = "notify")]
+            watcher: None
         })
     }
 
+    /// Opens an existing directory as the cache root
+    ///
+    /// The platform path configuration is bypassed: objects are created
+    /// directly in `path`, which is used verbatim without placeholder or
+    /// environment variable expansion. File naming still follows the
+    /// default `format` settings.
+    ///
+    /// Nothing is scanned upfront: with a `{name}`-only file name format
+    /// (see `set_config()`), `get()` resolves objects already on disk
+    /// lazily by probing their path, and `index_all()` registers every
+    /// existing object at once.
+    ///
+    /// # Parameters
+    /// - `path: impl AsRef<Path>` - Existing cache directory
+    ///
+    /// # Returns
+    /// `CacheResult<Self>` - Cache rooted at `path`, or `NotFound` /
+    /// `InvalidPath` if it is not an existing directory
+    pub fn open(path: impl AsRef<Path>) -> CacheResult<Self> {
+        let path = path.as_ref();
+        match std::fs::metadata(path) {
+            Ok(meta) if meta.is_dir() => {}
+            Ok(_) => {
+                return Err(CacheError::InvalidPath(format!(
+                    "{} is not a directory",
+                    path.display()
+                )));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CacheError::NotFound(format!(
+                    "Cache directory {} does not exist",
+                    path.display()
+                )));
+            }
+            Err(e) => return Err(CacheError::Io(e)),
+        }
+
+        let mut cache = Self::with_storage(CacheConfig::default(), Arc::new(FsStorage))?;
+        cache.root = Some(path.to_path_buf());
+        Ok(cache)
+    }
+
+    /// Returns the directory given to `Cache::open()`
+    ///
+    /// # Returns
+    /// `Option<&Path>` - Cache root, or `None` if paths come from the config
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Layers the cache over a read-only base directory
+    ///
+    /// Objects found in `dir` under the same file name are read from there
+    /// until they are written, writes always go to this cache. `get()` of a
+    /// name this cache does not know also finds it in `dir`. Base files are
+    /// never modified; in-place writes (`get_file()`, `write_at()`, ...)
+    /// copy the base content into this cache first. File names must therefore not depend on
+    /// `{time}` or `{id}`.
+    ///
+    /// # Parameters
+    /// - `dir: impl Into<PathBuf>` - Shared base cache directory
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or `InvalidConfig` if file names are not
+    /// derived from the object name alone
+    pub fn set_base(&mut self, dir: impl Into<PathBuf>) -> CacheResult<()> {
+        let filename = &self.config.format.filename;
+        if filename.contains("{time}") || filename.contains("{id}") {
+            return Err(CacheError::InvalidConfig(format!(
+                "format.filename '{}' must only use {{name}} to look up objects in a base layer",
+                filename
+            )));
+        }
+        self.base = Some(dir.into());
+        Ok(())
+    }
+
+    /// Backs the cache with a parent cache consulted on misses
+    ///
+    /// `get()` of a name this cache does not know returns a read-only view
+    /// of the parent's object, e.g. a machine-wide cache behind a
+    /// per-project one; writing or deleting it fails with
+    /// `PermissionDenied`. With `promote`, the content is copied into this
+    /// cache first and the copy is returned, so later reads stay local.
+    /// Where the filesystem allows it the copy is a reflink or a hard
+    /// link, broken on the first write to either side, so large shared
+    /// artifacts are not duplicated. Promoted objects get an ID like
+    /// created ones and show up in `len()`, `iter()` and
+    /// `dump_manifest()` right away. The parent is never written to by
+    /// this cache.
+    ///
+    /// # Parameters
+    /// - `parent: Arc<Cache>` - Cache to fall back to
+    /// - `promote: bool` - Whether to copy parent hits into this cache
+    pub fn set_parent(&mut self, parent: Arc<Cache>, promote: bool) {
+        self.parent = Some(parent);
+        self.promote_parent_hits = promote;
+    }
+
+    /// Returns the parent cache set with `set_parent()`
+    ///
+    /// # Returns
+    /// `Option<&Arc<Cache>>` - Parent cache, if any
+    pub fn parent(&self) -> Option<&Arc<Cache>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns the read-only base directory set with `set_base()`
+    ///
+    /// # Returns
+    /// `Option<&Path>` - Base layer, if any
+    pub fn base(&self) -> Option<&Path> {
+        self.base.as_deref()
+    }
+
+    /// Creates a Cache that keeps every object in memory
+    ///
+    /// No directory is created and no file is written; object paths are
+    /// just the object names and no time formatting takes place. The rest
+    /// of the API behaves as with a filesystem cache.
+    ///
+    /// # Returns
+    /// New in-memory Cache instance
+    pub fn in_memory() -> Self {
+        let config = CacheConfig::default();
+        let context = Arc::new(CacheContext::new(Arc::new(MemoryStorage::new()), &config));
+        Cache {
+            template: FilenameTemplate::new(&config.format.filename, &config.format.time),
+            cache_dir: OnceLock::new(),
+            objects: Registry::tracking(context.space.registered()),
+            context,
+            config,
+            lazy_manifest: OnceLock::new(),
+            next_id: AtomicU32::new(1),
+            in_memory: true,
+            root: None,
+            base: None,
+            signer: None,
+            key_mapper: None,
+            generations: Generations::default(),
+            retired: Vec::new(),
+            delete_parallelism: DEFAULT_DELETE_PARALLELISM,
+            index: None,
+            claim: None,
+            parent: None,
+            promote_parent_hits: false,
+            promoted: PendingObjects::default(),
+            #[cfg(feature = "notify")]
+            watcher: None
+        }
+    }
+
+    /// Checks whether this cache keeps its objects in memory only
+    ///
+    /// # Returns
+    /// `bool` - True for caches created with `Cache::in_memory()`
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
     /// Creates a new cache object with optional custom configuration
     ///
+    /// An existing object with the same name is replaced: its file is
+    /// deleted, or truncated and reused when the new object maps to the
+    /// same path. Use `create_new()` to fail instead.
+    ///
     /// # Parameters
     /// - `name: &str` - Cache object identifier
     /// - `custom_config: Option<&str>` - Optional JSON configuration override
     ///
     /// # Returns
     /// New CacheObject instance
-    pub fn create(&mut self, name: &str, custom_config: Option<&str>) -> CacheResult<CacheObject> {
-        validate_name(name)?;
+    pub fn create(&mut self, name: &str, custom_config: Option<&str>) -> CacheResult<Arc<CacheObject>> {
+        self.create_with(name, custom_config, false)
+    }
 
-        if self.objects.contains_key(name) {
-            return Err(CacheError::AlreadyExists(format!(
-                "Cache object '{}' already exists",
-                name
-            )));
+    /// Creates a new cache object, failing if the name is taken
+    ///
+    /// # Parameters
+    /// - `name: &str` - Cache object identifier
+    /// - `custom_config: Option<&str>` - Optional JSON configuration override
+    ///
+    /// # Returns
+    /// `CacheResult<Arc<CacheObject>>` - New object, or `AlreadyExists` if
+    /// a live object is registered under `name`
+    pub fn create_new(&mut self, name: &str, custom_config: Option<&str>) -> CacheResult<Arc<CacheObject>> {
+        self.create_with(name, custom_config, true)
+    }
+
+    fn create_with(&mut self, name: &str, custom_config: Option<&str>, exclusive: bool) -> CacheResult<Arc<CacheObject>> {
+        let context = Arc::clone(&self.context);
+        let name = &*context.canonical_name(name);
+        let mut span = OpSpan::start("create", name);
+        let result = context
+            .middleware
+            .run(&Operation::Create { name }, || self.create_inner(name, custom_config, exclusive));
+        match &result {
+            Ok(obj) => self.context.events.emit_with(|| CacheEvent::Created {
+                name: obj.name().to_string(),
+                id: obj.id(),
+            }),
+            Err(e) => self.context.events.emit_with(|| CacheEvent::error(name, 0, "create", e)),
         }
+        span.record(result)
+    }
 
-        let id = self.next_id;
-        self.next_id += 1;
+    fn create_inner(&mut self, name: &str, custom_config: Option<&str>, exclusive: bool) -> CacheResult<Arc<CacheObject>> {
+        self.check_name(name)?;
+        self.adopt_promoted();
 
-        let mut merged_config = self.config.clone();
+        let mut replaced = None;
+        let mut invalidated = None;
+        if let Some(existing) = self.objects.get(name) {
+            if !self.generations.is_live(existing) {
+                if let Some(old) = self.objects.remove(name) {
+                    old.detach();
+                    invalidated = Some(old.path().to_path_buf());
+                    self.retired.push(old);
+                }
+            } else if exclusive {
+                return Err(CacheError::AlreadyExists(format!(
+                    "Cache object '{}' already exists",
+                    name
+                )));
+            } else if existing.is_sealed() {
+                return Err(CacheError::PermissionDenied(format!(
+                    "Cache object '{}' is sealed, remove it before creating it again",
+                    name
+                )));
+            } else {
+                replaced = Some(Arc::clone(existing));
+            }
+        }
 
-        if let Some(config_str) = custom_config {
-            match serde_json::from_str::<CacheConfig>(config_str) {
-                Ok(custom) => {
-                    if !custom.path.windows.is_empty() {
-                        merged_config.path.windows = custom.path.windows.clone();
-                    }
-                    if !custom.path.linux.is_empty() {
-                        merged_config.path.linux = custom.path.linux.clone();
+        let (id, full_path, shared) = match &self.index {
+            Some(index) => {
+                // The ID and the name are reserved for every process at once
+                let (id, full_path, shared) = index.update(|state| {
+                    // Another process may have created the name since the last sync
+                    let taken = state.entries.get(name).is_some_and(|entry| Some(&entry.path) != invalidated.as_ref());
+                    if exclusive && taken {
+                        return Err(CacheError::AlreadyExists(format!(
+                            "Cache object '{}' already exists in the shared index",
+                            name
+                        )));
                     }
+                    let id = state.next_id;
+                    let (full_path, shared) = self.object_path(name, id, custom_config, replaced.as_ref())?;
+                    state.next_id += 1;
+                    let entry = IndexEntry { path: full_path.clone(), id };
+                    state.entries.insert(name.to_string(), entry);
+                    Ok((id, full_path, shared))
+                })?;
+                self.next_id.fetch_max(id + 1, Ordering::Relaxed);
+                (id, full_path, shared)
+            }
+            None => {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                let (full_path, shared) = if self.in_memory {
+                    (PathBuf::from(name), false)
+                } else {
+                    self.object_path(name, id, custom_config, replaced.as_ref())?
+                };
+                (id, full_path, shared)
+            }
+        };
 
-                    if !custom.format.filename.is_empty() {
-                        merged_config.format.filename = custom.format.filename.clone();
-                    }
-                    if !custom.format.time.is_empty() {
-                        merged_config.format.time = custom.format.time.clone();
-                    }
+        let mut cache_object = CacheObject::new(name.to_string(), full_path.clone(), id)
+            .with_shared(shared)
+            .with_generation(self.generations.current)
+            .with_context(Arc::clone(&self.context))
+            .tracking_reads();
+
+        let base_path = match (&self.base, full_path.file_name()) {
+            (Some(base), Some(file_name)) => Some(base.join(file_name)),
+            _ => None,
+        };
+        if let Some(base_path) = base_path
+            && base_path.is_file()
+        {
+            // An empty local entry would hide the base layer
+            cache_object = cache_object.with_base(base_path);
+        } else if self.config.protect_existing_files
+            && replaced.as_ref().is_none_or(|old| old.path() != full_path)
+        {
+            self.context.storage.create_new(&full_path).map_err(|e| {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    CacheError::AlreadyExists(format!(
+                        "Refusing to overwrite {}, which was not created by this cache",
+                        full_path.display()
+                    ))
+                } else {
+                    CacheError::PermissionDenied(e.to_string())
                 }
-                Err(e) => return Err(CacheError::ConfigParse(e.to_string())),
+            })?;
+        } else {
+            self.context
+                .storage
+                .create_file(&full_path)
+                .map_err(|e| CacheError::PermissionDenied(e.to_string()))?;
+        }
+        if shared && self.context.storage.exists(&full_path) {
+            self.context
+                .storage
+                .set_shared(&full_path)
+                .map_err(|e| CacheError::PermissionDenied(e.to_string()))?;
+        }
+
+        // A deleted object still held elsewhere must not remove the new file
+        self.context.take_deferred_delete(&full_path);
+        if self.context.storage.exists(&full_path) {
+            cache_object.reset_metadata()?;
+            let key_in_path = full_path.to_string_lossy().contains(&*self.file_key(name));
+            if (self.config.format.key_encoding.is_some() || !key_in_path) && self.config.metadata != MetadataMode::None {
+                // Hashed and truncated file names cannot be decoded, keep the name itself
+                cache_object.update_metadata(|meta| meta.key = Some(name.to_string()))?;
+            }
+        }
+
+        // The new file is in place, drop the one of the replaced object
+        if let Some(old) = replaced {
+            old.detach();
+            #[cfg(feature = "notify")]
+            if let Some(watcher) = &self.watcher {
+                watcher.untrack(old.path());
+            }
+            if old.path() != full_path {
+                old.delete_held(Arc::strong_count(&old) > 1)?;
+            }
+        }
+
+        #[cfg(feature = "notify")]
+        if let Some(watcher) = &self.watcher {
+            self.context.own_changes.note(&full_path);
+            watcher.track(&full_path, name, id);
+        }
+
+        let cache_object = Arc::new(cache_object);
+        self.objects.insert(name.to_string(), Arc::clone(&cache_object));
+        self.context.quarantine.release(name);
+
+        Ok(cache_object)
+    }
+
+    /// Validates a new object name, see `name_policy` and `format.key_encoding`
+    fn check_name(&self, name: &str) -> CacheResult<()> {
+        if self.key_mapper.is_some() {
+            if name.is_empty() {
+                return Err(CacheError::InvalidName("Cache name cannot be empty".to_string()));
             }
+            return Ok(());
         }
+        self.config.check_key_storage()?;
+        NameMapping::new(&self.config).validate(name)
+    }
+
+    /// Returns what `{name}` expands to in the file name of `name`
+    fn file_key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        NameMapping::new(&self.config).file_key(name)
+    }
+
+    /// Recovers the name of an unregistered object from the `{name}` part of its file
+    ///
+    /// Hashed and truncated names are read from the entry metadata.
+    /// Returns `None` for invalid names and names that are registered
+    /// already.
+    fn recover_name(&self, key: String, path: &Path) -> Option<String> {
+        let stored_key = |key: &str| {
+            CacheObject::new(key.to_string(), path.to_path_buf(), 0)
+                .with_context(Arc::clone(&self.context))
+                .metadata()
+                .ok()?
+                .key
+        };
+        let name = match NameMapping::new(&self.config) {
+            NameMapping::Encode(KeyEncoding::Hash) => stored_key(&key),
+            mapping if is_truncated_key(&key) => stored_key(&key).or_else(|| mapping.decode(key)),
+            mapping => mapping.decode(key),
+        }?;
+        let name = self.context.canonical_name(&name).into_owned();
+        (!self.objects.contains_key(&name)).then_some(name)
+    }
 
-        let cache_path = if cfg!(windows) {
-            expand_path(&merged_config.path.windows)
+    /// Chooses the file of a new object, applying `format.uniqueness` or `format.on_collision`
+    fn object_path(
+        &self,
+        name: &str,
+        id: u32,
+        custom_config: Option<&str>,
+        replaced: Option<&Arc<CacheObject>>,
+    ) -> CacheResult<(PathBuf, bool)> {
+        let (full_path, shared) = self.prepare_path(name, id, custom_config)?;
+        if let Some(uniqueness) = self.config.format.uniqueness {
+            Ok((self.unique_path(uniqueness, name, id, &full_path)?, shared))
+        } else if custom_config.is_none() && self.key_mapper.is_none() && self.template.has_id() {
+            Ok((full_path, shared))
         } else {
-            expand_path(&merged_config.path.linux)
+            Ok((self.resolve_collision(name, full_path, replaced)?, shared))
+        }
+    }
+
+    /// Applies `format.on_collision` if another object already uses `path`
+    ///
+    /// Retired objects may keep sharing their path, `gc()` skips files
+    /// still used by a registered object.
+    fn resolve_collision(&self, name: &str, path: PathBuf, replaced: Option<&Arc<CacheObject>>) -> CacheResult<PathBuf> {
+        let replaced = replaced.map(|old| old.name());
+        let in_use = |candidate: &Path| self.objects.path_owner(candidate, replaced).map(str::to_string);
+        let Some(owner) = in_use(&path) else {
+            return Ok(path);
+        };
+        if self.config.format.on_collision == CollisionPolicy::Error {
+            return Err(CacheError::AlreadyExists(format!(
+                "File {} of cache object '{}' is already used by '{}'",
+                path.display(),
+                name,
+                owner
+            )));
+        }
+
+        for n in 1.. {
+            let candidate = suffixed(&path, &format!("~{}", n));
+            if in_use(&candidate).is_none() {
+                return Ok(candidate);
+            }
+        }
+        unreachable!("some suffix is always free")
+    }
+
+    /// Appends the `format.uniqueness` token to the file stem of `path`
+    fn unique_path(&self, uniqueness: Uniqueness, name: &str, id: u32, path: &Path) -> CacheResult<PathBuf> {
+        Ok(match uniqueness {
+            #[cfg(feature = "uuid")]
+            Uniqueness::Uuid => suffixed(path, &format!("-{}", uuid::Uuid::new_v4().simple())),
+            #[cfg(not(feature = "uuid"))]
+            Uniqueness::Uuid => {
+                return Err(CacheError::InvalidConfig(
+                    "format.uniqueness 'uuid' needs the `uuid` feature".to_string(),
+                ));
+            }
+            Uniqueness::NameHash => suffixed(path, &format!("-{}", &sha256_hex(name.as_bytes())[..32])),
+            Uniqueness::Counter => {
+                let in_use = |candidate: &Path| {
+                    self.context.storage.exists(candidate) || self.objects.path_owner(candidate, None).is_some()
+                };
+                (u64::from(id)..)
+                    .map(|n| suffixed(path, &format!("-{}", n)))
+                    .find(|candidate| !in_use(candidate))
+                    .expect("some counter value is always free")
+            }
+        })
+    }
+
+    /// Builds the file path of a new object and creates its directory
+    ///
+    /// Also returns whether the object belongs to a system-scoped cache.
+    fn prepare_path(&self, name: &str, id: u32, custom_config: Option<&str>) -> CacheResult<(PathBuf, bool)> {
+        let Some(config_str) = custom_config else {
+            let shared = self.root.is_none() && self.config.path.scope == CacheScope::System;
+            let mut full_path = match &self.root {
+                Some(root) => root.clone(),
+                None => self.cache_dir()?,
+            };
+            let file_name = self.file_name(&self.template, &full_path, name, id)?;
+            full_path.push(file_name);
+            return self.finish_path(full_path, shared);
         };
 
-        let filename = merged_config
-            .format
-            .filename
-            .replace("{name}", name)
-            .replace("{id}", &id.to_string())
-            .replace(
-                "{time}",
-                &time_format(SystemTime::now(), &merged_config.format.time),
-            );
+        // Only a per-object override needs its own copy of the config
+        let custom = serde_json::from_str::<CacheConfig>(config_str)
+            .map_err(|e| CacheError::ConfigParse(e.to_string()))?;
+        let mut merged_config = self.config.clone();
+        merged_config.path.merge(&custom.path);
+        if !custom.format.filename.is_empty() {
+            merged_config.format.filename = custom.format.filename;
+        }
+        if !custom.format.time.is_empty() {
+            merged_config.format.time = custom.format.time;
+        }
 
-        let full_path = std::path::PathBuf::from(&cache_path).join(&filename);
+        let shared = self.root.is_none() && merged_config.path.scope == CacheScope::System;
+        let mut full_path = match &self.root {
+            Some(root) => root.clone(),
+            None => checked_cache_dir(&merged_config)?,
+        };
+        let template = FilenameTemplate::new(&merged_config.format.filename, &merged_config.format.time);
+        let file_name = self.file_name(&template, &full_path, name, id)?;
+        full_path.push(file_name);
+        self.finish_path(full_path, shared)
+    }
 
-        #[cfg(windows)]
-        let full_path = std::path::PathBuf::from(full_path.to_string_lossy().replace('/', "\\"));
+    /// Expands a file name format for `name` in `dir`, applying `format.on_long_path`
+    ///
+    /// Room is left for the token of `format.uniqueness` and the
+    /// extension of metadata sidecars. A key mapper replaces the format.
+    fn file_name(&self, template: &FilenameTemplate, dir: &Path, name: &str, id: u32) -> CacheResult<PathBuf> {
+        if let Some(mapper) = &self.key_mapper {
+            let path = mapper.map(name, id);
+            validate_mapped_path(name, &path)?;
+            if dir.as_os_str().len() + 1 + path.as_os_str().len() > MAX_PATH_LEN {
+                return Err(CacheError::InvalidPath(format!(
+                    "Path {} mapped from '{}' is over the platform limit in {}",
+                    path.display(),
+                    name,
+                    dir.display()
+                )));
+            }
+            return Ok(path);
+        }
 
-        // Create directory if it doesn't exist
-        if let Some(parent) = full_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                CacheError::InvalidPath(format!("Failed to create cache directory: {}", e))
-            })?;
+        let mut reserved = match self.config.format.uniqueness {
+            Some(Uniqueness::Counter) => 11,
+            Some(Uniqueness::Uuid | Uniqueness::NameHash) => 33,
+            None => 0,
+        };
+        if self.config.metadata == MetadataMode::Sidecar {
+            reserved += SIDECAR_EXTENSION.len() + 1;
+        }
+        let excess = |file_name: &str| {
+            let longest_component = file_name.split('/').map(str::len).max().unwrap_or(0);
+            let path_len = dir.as_os_str().len() + 1 + file_name.len();
+            (longest_component + reserved)
+                .saturating_sub(MAX_KEY_LEN)
+                .max((path_len + reserved).saturating_sub(MAX_PATH_LEN))
+        };
+
+        let key = self.file_key(name);
+        let file_name = template.format(&key, id);
+        let too_long = excess(&file_name);
+        if too_long == 0 {
+            return Ok(PathBuf::from(file_name));
+        }
+        let error = || {
+            CacheError::InvalidPath(format!(
+                "File name of cache object '{}' in {} is {} bytes over the platform limit",
+                name,
+                dir.display(),
+                too_long
+            ))
+        };
+        if self.config.format.on_long_path == LongPathPolicy::Error {
+            return Err(error());
+        }
+        let truncated = key
+            .len()
+            .checked_sub(too_long)
+            .and_then(|max_len| truncate_key(&key, name, max_len))
+            .ok_or_else(error)?;
+        let file_name = template.format(&truncated, id);
+        // Nothing to shorten if the format itself is too long
+        if excess(&file_name) > 0 {
+            return Err(error());
         }
+        Ok(PathBuf::from(file_name))
+    }
+
+    /// Returns the expanded cache directory of the config, resolving it once
+    ///
+    /// Environment variables are read on the first `create()` after
+    /// `Cache::new()` or `set_config()`.
+    fn cache_dir(&self) -> CacheResult<PathBuf> {
+        if let Some(dir) = self.cache_dir.get() {
+            return Ok(dir.clone());
+        }
+        let dir = checked_cache_dir(&self.config)?;
+        Ok(self.cache_dir.get_or_init(|| dir).clone())
+    }
 
-        let cache_object = CacheObject::new(name.to_string(), full_path.clone(), id);
+    /// Creates the parent directory of a formatted object path
+    fn finish_path(&self, full_path: PathBuf, shared: bool) -> CacheResult<(PathBuf, bool)> {
+        #[cfg(windows)]
+        let full_path = PathBuf::from(full_path.to_string_lossy().replace('/', "\\"));
 
-        #[cfg(unix)]
+        // Create directory if it doesn't exist
+        let storage = &self.context.storage;
+        if let Some(parent) = full_path.parent()
+            && !storage.exists(parent)
         {
-            use std::os::unix::fs::PermissionsExt;
-            let perms = std::fs::Permissions::from_mode(0o600); // rw-------
-            if let Ok(file) = std::fs::File::create(&full_path) {
-                file.set_permissions(perms)
+            log_debug!("creating cache directory {}", parent.display());
+            storage.create_dir_all(parent).map_err(|e| {
+                log_warn!("failed to create cache directory {}: {}", parent.display(), e);
+                CacheError::InvalidPath(format!("Failed to create cache directory: {}", e))
+            })?;
+            if shared {
+                storage
+                    .set_shared(parent)
                     .map_err(|e| CacheError::PermissionDenied(e.to_string()))?;
             }
         }
 
-        self.objects.insert(name.to_string(), cache_object.clone());
-
-        Ok(cache_object)
+        Ok((full_path, shared))
     }
 
-    /// Retrieves an existing cache object by name
+    /// Creates an object holding `content`, failing if the name is taken
+    ///
+    /// If the write fails, the new object is removed again.
     ///
     /// # Parameters
     /// - `name: &str` - Cache object identifier
+    /// - `content: &[u8]` - Content to store
     ///
     /// # Returns
-    /// `CacheResult<CacheObject>` - Retrieved cache object or error
-    pub fn get(&self, name: &str) -> CacheResult<CacheObject> {
-        self.objects
-            .get(name)
-            .cloned()
-            .ok_or_else(|| CacheError::NotFound(format!("Cache object '{}' not found", name)))
+    /// `CacheResult<Arc<CacheObject>>` - New object, or `AlreadyExists`
+    pub fn insert(&mut self, name: &str, content: &[u8]) -> CacheResult<Arc<CacheObject>> {
+        let obj = self.create_new(name, None)?;
+        self.write_new(obj, content)
     }
 
-    /// Returns the number of cache objects
+    /// Writes `content` to an object, creating it if needed
     ///
-    /// # Returns
-    /// `usize` - Count of cache objects
-    pub fn len(&self) -> usize {
-        self.objects.len()
-    }
-
-    /// Check if the cache list is empty
+    /// # Parameters
+    /// - `name: &str` - Cache object identifier
+    /// - `content: &[u8]` - Content to store
     ///
     /// # Returns
-    /// `bool` - True if the cache list is empty, false otherwise
-    pub fn is_empty(&self) -> bool {
-        self.objects.is_empty()
+    /// `CacheResult<Arc<CacheObject>>` - Updated or new object
+    pub fn upsert(&mut self, name: &str, content: &[u8]) -> CacheResult<Arc<CacheObject>> {
+        match self.lookup(name) {
+            Some(obj) => obj.write_bytes(content).map(|_| obj),
+            None => {
+                let obj = self.create_new(name, None)?;
+                self.write_new(obj, content)
+            }
+        }
     }
 
-    /// Removes a cache object by name
+    /// Overwrites the content of an existing object
     ///
     /// # Parameters
     /// - `name: &str` - Cache object identifier
+    /// - `content: &[u8]` - Content to store
     ///
     /// # Returns
-    /// `CacheResult<()>` - Success or error
-    pub fn remove(&mut self, name: &str) -> CacheResult<()> {
-        if let Some(cache_obj) = self.objects.remove(name) {
-            cache_obj.delete()?;
+    /// `CacheResult<Arc<CacheObject>>` - Updated object, or `EntryNotFound`
+    pub fn replace(&self, name: &str, content: &[u8]) -> CacheResult<Arc<CacheObject>> {
+        let obj = self.get(name)?;
+        obj.write_bytes(content)?;
+        Ok(obj)
+    }
+
+    /// Writes the content of a just created object, removing it on failure
+    fn write_new(&mut self, obj: Arc<CacheObject>, content: &[u8]) -> CacheResult<Arc<CacheObject>> {
+        if let Err(e) = obj.write_bytes(content) {
+            let _ = self.remove(obj.name());
+            return Err(e);
         }
-        Ok(())
+        Ok(obj)
     }
 
-    /// Clears all cache objects
+    /// Retrieves an existing cache object by name
+    ///
+    /// # Parameters
+    /// - `name: &Q` - Cache object identifier, any `Borrow<str>` (`str`, `String`, `CacheObject`)
     ///
     /// # Returns
-    /// `CacheResult<()>` - Success or error
-    pub fn clear(&mut self) -> CacheResult<()> {
-        let mut errors = Vec::new();
+    /// `CacheResult<Arc<CacheObject>>` - Retrieved cache object or error
+    pub fn get<Q: Borrow<str> + ?Sized>(&self, name: &Q) -> CacheResult<Arc<CacheObject>> {
+        let name = name.borrow();
+        traced_get(&self.context, name, |name| self.lookup(name))
+    }
+
+    /// Retrieves an existing cache object by name, if there is one
+    ///
+    /// Like `get()`, but for code that treats a missing object as normal.
+    ///
+    /// # Parameters
+    /// - `name: &Q` - Cache object identifier, any `Borrow<str>`
+    ///
+    /// # Returns
+    /// `Option<Arc<CacheObject>>` - Retrieved cache object or `None`
+    pub fn try_get<Q: Borrow<str> + ?Sized>(&self, name: &Q) -> Option<Arc<CacheObject>> {
+        self.get(name).ok()
+    }
+
+    /// Retrieves several cache objects in one call
+    ///
+    /// A convenience over calling `get()` for each name, with the same
+    /// lookups and events; names are not resolved in a single pass.
+    ///
+    /// # Parameters
+    /// - `names: &[&str]` - Cache object identifiers
+    ///
+    /// # Returns
+    /// `Vec<CacheResult<Arc<CacheObject>>>` - One result per name, in the same order
+    pub fn get_many(&self, names: &[&str]) -> Vec<CacheResult<Arc<CacheObject>>> {
+        names.iter().map(|name| self.get(*name)).collect()
+    }
+
+    /// Reads the content of several cache objects concurrently
+    ///
+    /// Looks the objects up like `get_many()`, then reads them all at once
+    /// on Tokio's blocking thread pool (see `CacheObject::get_bytes_async()`).
+    ///
+    /// # Parameters
+    /// - `names: &[&str]` - Cache object identifiers
+    ///
+    /// # Returns
+    /// `Vec<CacheResult<Vec<u8>>>` - One content or error per name, in the same order
+    #[cfg(feature = "async")]
+    pub async fn get_many_bytes_async(&self, names: &[&str]) -> Vec<CacheResult<Vec<u8>>> {
+        let reads: Vec<_> = self
+            .get_many(names)
+            .into_iter()
+            .map(|obj| obj.map(|obj| tokio::task::spawn_blocking(move || obj.get_bytes())))
+            .collect();
+        let mut contents = Vec::with_capacity(reads.len());
+        for read in reads {
+            contents.push(match read {
+                Ok(task) => task
+                    .await
+                    .map_err(|e| CacheError::Task { operation: "read", source: Box::new(e) })
+                    .and_then(|content| content),
+                Err(e) => Err(e),
+            });
+        }
+        contents
+    }
+
+    /// Finds a live object by name, probing the disk for opened directories
+    pub(crate) fn lookup(&self, name: &str) -> Option<Arc<CacheObject>> {
+        let name = &*self.context.canonical_name(name);
+        self.objects
+            .get(name)
+            .filter(|obj| self.generations.is_live(obj))
+            .cloned()
+            .or_else(|| self.probe(name))
+            .or_else(|| self.lookup_base(name))
+            .or_else(|| self.lookup_parent(name))
+    }
+
+    /// Resolves a name this cache does not know from the base layer
+    ///
+    /// The object reads the base file until it is written, like the ones
+    /// created with `set_base()` in place. It is not registered and has ID 0.
+    fn lookup_base(&self, name: &str) -> Option<Arc<CacheObject>> {
+        let base = self.base.as_ref()?;
+        if self.objects.contains_key(name) || self.in_memory {
+            return None;
+        }
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => self.cache_dir().ok()?,
+        };
+        let file_name = self.probe_file_name(&dir, name)?;
+        let base_path = base.join(&file_name);
+        if !base_path.is_file() {
+            return None;
+        }
+        let obj = CacheObject::new(name.to_string(), dir.join(file_name), 0)
+            .with_generation(self.generations.current)
+            .with_context(Arc::clone(&self.context))
+            .with_base(base_path);
+        Some(Arc::new(obj))
+    }
+
+    /// Looks a missed name up in the parent cache, promoting the hit if enabled
+    fn lookup_parent(&self, name: &str) -> Option<Arc<CacheObject>> {
+        let parent = self.parent.as_ref()?;
+        if let Some(promoted) = self.promoted.get(name) {
+            return Some(Arc::clone(promoted));
+        }
+        let hit = parent.get(name).ok()?;
+        let view = || Arc::new(hit.read_only_view());
+        if !self.promote_parent_hits {
+            return Some(view());
+        }
+        match self.promoted.get_or_add(name, || self.promote(name, &hit)) {
+            Ok(promoted) => Some(promoted),
+            Err(e) => {
+                log_warn!("failed to promote '{}' from the parent cache: {}", name, e);
+                Some(view())
+            }
+        }
+    }
+
+    /// Copies a parent object into this cache under a new ID
+    fn promote(&self, name: &str, hit: &CacheObject) -> CacheResult<Arc<CacheObject>> {
+        let storage = &self.context.storage;
+        // Claims the path first, as `create_inner()` does
+        let protect = |path: &Path| {
+            if !self.config.protect_existing_files {
+                return Ok(());
+            }
+            storage.create_new(path).map_err(|e| {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    CacheError::AlreadyExists(format!(
+                        "Refusing to overwrite {}, which was not created by this cache",
+                        path.display()
+                    ))
+                } else {
+                    CacheError::PermissionDenied(e.to_string())
+                }
+            })
+        };
+        let (id, path) = match &self.index {
+            // The ID and the name are reserved for every process at once
+            Some(index) => index.update(|state| {
+                let id = state.next_id.max(self.next_id.load(Ordering::Relaxed));
+                let (path, _) = self.object_path(name, id, None, None)?;
+                protect(&path)?;
+                state.next_id = id + 1;
+                state.entries.insert(name.to_string(), IndexEntry { path: path.clone(), id });
+                self.next_id.fetch_max(id + 1, Ordering::Relaxed);
+                Ok((id, path))
+            })?,
+            None => {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                let path = if self.in_memory {
+                    PathBuf::from(name)
+                } else {
+                    self.object_path(name, id, None, None)?.0
+                };
+                protect(&path)?;
+                (id, path)
+            }
+        };
+        let obj = Arc::new(
+            CacheObject::new(name.to_string(), path, id)
+                .with_generation(self.generations.current)
+                .with_context(Arc::clone(&self.context))
+                .tracking_reads(),
+        );
+        // Linked files share the blocks of the parent until written
+        let linked = storage.is_plain_file()
+            && hit.storage().is_plain_file()
+            && hit.exists()
+            && storage.remove(obj.path()).map_or_else(|e| e.kind() == std::io::ErrorKind::NotFound, |()| true)
+            && share_file(hit.path(), obj.path()).is_ok();
+        if linked {
+            obj.written(hit.size()?)?;
+        } else {
+            obj.write_bytes(&hit.get_bytes()?)?;
+        }
+        Ok(obj)
+    }
+
+    /// Moves the objects promoted from the parent into the registry
+    fn adopt_promoted(&mut self) -> usize {
+        let mut adopted = 0;
+        for obj in self.promoted.take() {
+            if let Some(existing) = self.objects.get(obj.name()) {
+                if self.generations.is_live(existing) {
+                    continue;
+                }
+                if let Some(old) = self.objects.remove(obj.name()) {
+                    old.detach();
+                    self.retired.push(old);
+                }
+            }
+            self.objects.insert(obj.name().to_string(), obj);
+            adopted += 1;
+        }
+        adopted
+    }
+
+    /// Returns the registered objects and those promoted since, live or not
+    fn registered(&self) -> impl Iterator<Item = &Arc<CacheObject>> {
+        let pending = self.promoted.iter().filter(|obj| {
+            self.objects.get(obj.name()).is_none_or(|existing| !self.generations.is_live(existing))
+        });
+        self.objects.values().chain(pending)
+    }
+
+    /// Returns the live objects shared with the registry
+    #[cfg(feature = "concurrent")]
+    pub(crate) fn live_objects(&self) -> impl Iterator<Item = &Arc<CacheObject>> {
+        self.objects.values().filter(|obj| self.generations.is_live(obj))
+    }
+
+    /// Returns the state shared with handed out objects
+    #[cfg(feature = "concurrent")]
+    pub(crate) fn context(&self) -> &Arc<CacheContext> {
+        &self.context
+    }
+
+    /// Resolves an object of an opened directory that is not indexed yet
+    ///
+    /// With a `{name}`-only file name format the path is derived from the
+    /// name and the object has ID 0. Otherwise, or if that file is missing,
+    /// the directory's `manifest.json` is read once and the recorded path
+    /// and ID are used. Lazily resolved objects are not registered.
+    fn probe(&self, name: &str) -> Option<Arc<CacheObject>> {
+        let root = self.root.as_ref()?;
+        if self.objects.contains_key(name) {
+            return None;
+        }
+        let derived = self.probe_file_name(root, name).map(|file_name| root.join(file_name));
+        match derived {
+            Some(path) if path.is_file() => {
+                Some(Arc::new(CacheObject::new(name.to_string(), path, 0).with_context(Arc::clone(&self.context))))
+            }
+            _ => self.probe_manifest(root, name),
+        }
+    }
+
+    /// Resolves a name from the `manifest.json` of an opened directory
+    ///
+    /// Entries whose file is gone or no longer has the recorded size are
+    /// ignored; `index_all()` verifies checksums as well.
+    fn probe_manifest(&self, root: &Path, name: &str) -> Option<Arc<CacheObject>> {
+        let entries = self.lazy_manifest.get_or_init(|| {
+            let manifest = root.join(MANIFEST_FILE);
+            if !manifest.is_file() {
+                return HashMap::new();
+            }
+            match std::fs::read_to_string(&manifest).map_err(CacheError::from).and_then(|json| self.parse_manifest(&json)) {
+                Ok(manifest) => {
+                    let mut entries = HashMap::new();
+                    for entry in manifest.entries {
+                        entries.entry(self.context.canonical_name(&entry.name).into_owned()).or_insert(entry);
+                    }
+                    entries
+                }
+                Err(e) => {
+                    log_warn!("ignoring {} for lazy lookups: {}", manifest.display(), e);
+                    HashMap::new()
+                }
+            }
+        });
+        let entry = entries.get(name)?;
+        self.check_name(name).ok()?;
+        let path = self.manifest_path(name, &entry.path).ok()?;
+        let obj = CacheObject::new(name.to_string(), path, entry.id)
+            .with_created_at(entry.created_at())
+            .with_sources(entry.sources.clone())
+            .with_context(Arc::clone(&self.context));
+        let size = obj.size().ok()?;
+        entry.size.is_none_or(|recorded| recorded == size).then(|| Arc::new(obj))
+    }
+
+    /// Derives the relative path of an object in `dir` from its name alone
+    ///
+    /// Long names are shortened like `create()` does with
+    /// `LongPathPolicy::Truncate`.
+    fn probe_file_name(&self, dir: &Path, name: &str) -> Option<PathBuf> {
+        let file_name = match &self.key_mapper {
+            Some(mapper) => Some(mapper.map(name, 0)).filter(|path| validate_mapped_path(name, path).is_ok())?,
+            None if file_from_name(&self.config.format.filename, name).is_some() => {
+                self.file_name(&self.template, dir, name, 0).ok()?
+            }
+            None => return None,
+        };
+        self.check_name(name).ok()?;
+        Some(file_name)
+    }
+
+    /// Registers every object found in the cache directory
+    ///
+    /// Imports `manifest.json` if the directory has one, then registers
+    /// the remaining files whose object name can be recovered from the
+    /// file name format (`{name}` without `{time}` or `{id}`). Needed
+    /// before `len()` or `iter()` reflect a directory given to `open()`.
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects registered
+    pub fn index_all(&mut self) -> CacheResult<usize> {
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => resolve_cache_dir(&self.config)?,
+        };
+
+        let mut indexed = self.adopt_promoted();
+        let manifest = dir.join(MANIFEST_FILE);
+        if manifest.is_file() {
+            indexed += self.import_manifest(&std::fs::read_to_string(manifest)?)?;
+        }
+
+        let known: std::collections::HashSet<PathBuf> =
+            self.objects.values().map(|obj| obj.path().to_path_buf()).collect();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if !path.is_file() || known.contains(&path) {
+                continue;
+            }
+            if path.file_name().is_some_and(|file_name| SharedIndex::is_index_file(file_name) || file_name == CLAIM_FILE) {
+                continue;
+            }
+            if self.config.metadata == MetadataMode::Sidecar
+                && path.extension().is_some_and(|ext| ext == SIDECAR_EXTENSION)
+            {
+                continue;
+            }
+            let Some(name) = path
+                .file_name()
+                .and_then(|file_name| name_from_file(&self.config.format.filename, file_name))
+                .and_then(|key| self.recover_name(key, &path))
+            else {
+                continue;
+            };
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            let obj = CacheObject::new(name.clone(), path, id)
+                .with_generation(self.generations.current)
+                .with_context(Arc::clone(&self.context));
+            self.objects.insert(name, Arc::new(obj));
+            indexed += 1;
+        }
+        Ok(indexed)
+    }
+
+    /// Shares the registry with other processes using the cache directory
+    ///
+    /// The registry is kept in `index.json` (see `INDEX_FILE`) next to the
+    /// cached files. `create()`, `remove()` and `clear()` update it under a
+    /// file lock, so processes never hand out the same object ID, and
+    /// `sync_index()` picks up the changes other processes made. Objects
+    /// registered before the call are added to the index. Other registry
+    /// changes, like `migrate_format()` or `import_manifest()`, stay local
+    /// and are kept by `sync_index()`. `create_new()` and `insert()` fail
+    /// with `AlreadyExists` for names another process created.
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects registered from the index,
+    /// or `InvalidConfig` for an in-memory cache
+    pub fn use_shared_index(&mut self) -> CacheResult<usize> {
+        if self.in_memory {
+            return Err(CacheError::InvalidConfig(
+                "An in-memory cache has no directory to share an index in".to_string(),
+            ));
+        }
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => self.cache_dir()?,
+        };
+        let index = SharedIndex::open(&dir)?;
+        if !self.objects.is_empty() {
+            index.update(|state| {
+                for (name, obj) in self.objects.iter() {
+                    state.entries.entry(name.clone()).or_insert_with(|| IndexEntry {
+                        path: obj.path().to_path_buf(),
+                        id: obj.id(),
+                    });
+                }
+                state.next_id = state.next_id.max(self.next_id.load(Ordering::Relaxed));
+                Ok(())
+            })?;
+        }
+        self.index = Some(index);
+        self.sync_index()
+    }
+
+    /// Makes this cache the only one managing its directory
+    ///
+    /// Takes a lock on `cache.lock` in the cache directory, held until the
+    /// cache is dropped or `release_exclusive()` is called. Meant for
+    /// applications that must not run two instances with different
+    /// policies (eviction, generations, ...) on the same directory. The
+    /// claim refreshes its heartbeat in the background; a claim left by a
+    /// process that died or hung for `CLAIM_STALE_AFTER` is taken over.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, `AlreadyExists` naming the owner PID
+    /// if another live instance claimed the directory, or
+    /// `InvalidConfig` for an in-memory cache
+    pub fn claim_exclusive(&mut self) -> CacheResult<()> {
+        if self.claim.is_some() {
+            return Ok(());
+        }
+        if self.in_memory {
+            return Err(CacheError::InvalidConfig(
+                "An in-memory cache has no directory to claim".to_string(),
+            ));
+        }
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => self.cache_dir()?,
+        };
+        std::fs::create_dir_all(&dir)?;
+        match FileLock::try_acquire(&dir.join(CLAIM_FILE), Some(CLAIM_STALE_AFTER))? {
+            Ok(lock) => {
+                self.claim = Some(lock.keep_alive(CLAIM_STALE_AFTER));
+                Ok(())
+            }
+            Err(Some(owner)) => Err(CacheError::AlreadyExists(format!(
+                "Cache directory {} is claimed by process {} on {}",
+                dir.display(),
+                owner.pid,
+                owner.host
+            ))),
+            Err(None) => Err(CacheError::AlreadyExists(format!(
+                "Cache directory {} is claimed by another process",
+                dir.display()
+            ))),
+        }
+    }
+
+    /// Releases the claim taken with `claim_exclusive()`
+    ///
+    /// # Returns
+    /// `bool` - True if the directory was claimed
+    pub fn release_exclusive(&mut self) -> bool {
+        self.claim.take().is_some()
+    }
+
+    /// Applies the changes other processes made to the shared index
+    ///
+    /// Objects created elsewhere are registered, objects removed elsewhere
+    /// are unregistered without touching their files. Does nothing unless
+    /// `use_shared_index()` was called.
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects registered or unregistered
+    pub fn sync_index(&mut self) -> CacheResult<usize> {
+        let Some(index) = &mut self.index else {
+            return Ok(0);
+        };
+        let Some(state) = index.changes()? else {
+            return Ok(0);
+        };
+        self.next_id.fetch_max(state.next_id, Ordering::Relaxed);
+
+        // Only objects the index listed were removed elsewhere, others stay local
+        let mut changed = 0;
+        self.objects.retain(|name, obj| {
+            let indexed = state.entries.get(name).is_some_and(|entry| entry.path == obj.path());
+            let dropped = !indexed && index.was_listed(name, obj.path());
+            if dropped {
+                obj.detach();
+                changed += 1;
+            }
+            !dropped
+        });
+        index.set_listed(&state.entries);
+        for (name, entry) in state.entries {
+            if self.objects.contains_key(&name) {
+                continue;
+            }
+            let obj = CacheObject::new(name.clone(), entry.path, entry.id)
+                .with_generation(self.generations.current)
+                .with_context(Arc::clone(&self.context));
+            self.objects.insert(name, Arc::new(obj));
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Drops objects from the shared index, unless they were replaced there
+    fn unindex<'a>(&self, objects: impl IntoIterator<Item = (&'a str, &'a CacheObject)>) -> CacheResult<()> {
+        let Some(index) = &self.index else {
+            return Ok(());
+        };
+        index.update(|state| {
+            for (name, obj) in objects {
+                if state.entries.get(name).is_some_and(|entry| entry.path == obj.path()) {
+                    state.entries.remove(name);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Renames the files of every object from one file name format to another
+    ///
+    /// Registered objects whose file name matches `old_template` and
+    /// unregistered files in the cache directory from which `old_template`
+    /// recovers an object name are moved to the name `new_template` gives
+    /// them, together with their metadata sidecars. Nothing is renamed if
+    /// two objects would end up with the same file, a target exists
+    /// already or a sealed object would move, and files already moved go
+    /// back if a rename fails. A
+    /// `manifest.json` in the cache directory is rewritten, and
+    /// `format.filename` becomes `new_template`. Handles to moved objects
+    /// fail with `NotFound` afterwards, fetch them again with `get()`.
+    ///
+    /// # Parameters
+    /// - `old_template: &str` - Format the files were created with
+    /// - `new_template: &str` - Format to rename them to
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects renamed, `AlreadyExists`
+    /// if the new names collide, or `PermissionDenied` for a sealed object
+    pub fn migrate_format(&mut self, old_template: &str, new_template: &str) -> CacheResult<usize> {
+        if self.in_memory {
+            return Ok(0);
+        }
+        let old = FilenameTemplate::new(old_template, &self.config.format.time);
+        let new = FilenameTemplate::new(new_template, &self.config.format.time);
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => resolve_cache_dir(&self.config)?,
+        };
+
+        let mut moves: Vec<(Arc<CacheObject>, PathBuf)> = Vec::new();
+        for obj in self.objects.values() {
+            let (Some(parent), Some(file_name)) = (obj.path().parent(), obj.path().file_name()) else {
+                continue;
+            };
+            let key = self.file_key(obj.name());
+            if old.parse(&file_name.to_string_lossy(), Some(&key)).is_some() {
+                moves.push((Arc::clone(obj), parent.join(new.format(&key, obj.id()))));
+            }
+        }
+        let mut next_id = self.next_id.load(Ordering::Relaxed);
+        if dir.is_dir() {
+            let known: HashSet<PathBuf> = self.objects.values().map(|obj| obj.path().to_path_buf()).collect();
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if !path.is_file() || known.contains(&path) {
+                    continue;
+                }
+                let Some(name) = path
+                    .file_name()
+                    .and_then(|file_name| old.parse(&file_name.to_string_lossy(), None).flatten())
+                    .and_then(|key| self.recover_name(key, &path))
+                else {
+                    continue;
+                };
+                let id = next_id;
+                next_id += 1;
+                let obj = CacheObject::new(name.clone(), path, id)
+                    .with_generation(self.generations.current)
+                    .with_context(Arc::clone(&self.context));
+                moves.push((Arc::new(obj), dir.join(new.format(&self.file_key(&name), id))));
+            }
+        }
+
+        let mut targets = HashSet::new();
+        for (obj, target) in &moves {
+            if obj.path() == target {
+                continue;
+            }
+            // Sealed files keep the path they were published under
+            obj.check_sealed()?;
+            if !targets.insert(target.as_path()) || self.context.storage.exists(target) {
+                return Err(CacheError::AlreadyExists(format!(
+                    "Cannot migrate cache object '{}', {} is already taken",
+                    obj.name(),
+                    target.display()
+                )));
+            }
+        }
+
+        // Move every file before touching the registry, undoing the moves if one fails
+        let mut renamed: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut failure = None;
+        'files: for (obj, target) in moves.iter().filter(|(obj, target)| obj.path() != target) {
+            // Objects without a file yet only get the new path
+            for (from, to) in [
+                (obj.path().to_path_buf(), target.clone()),
+                (sidecar_path(obj.path()), sidecar_path(target)),
+            ] {
+                if !self.context.storage.exists(&from) {
+                    continue;
+                }
+                if let Err(e) = self.context.storage.rename(&from, &to) {
+                    failure = Some(e);
+                    break 'files;
+                }
+                renamed.push((from, to));
+            }
+        }
+        if let Some(e) = failure {
+            for (from, to) in renamed.into_iter().rev() {
+                let _ = self.context.storage.rename(&to, &from);
+            }
+            return Err(CacheError::Io(e));
+        }
+
+        self.next_id.store(next_id, Ordering::Relaxed);
+        let mut migrated = 0;
+        for (obj, target) in moves {
+            if obj.path() != target {
+                obj.relocated(&target);
+                migrated += 1;
+            }
+            #[cfg(feature = "notify")]
+            if let Some(watcher) = &self.watcher {
+                watcher.untrack(obj.path());
+                watcher.track(&target, obj.name(), obj.id());
+            }
+            let moved = CacheObject::new(obj.name().to_string(), target, obj.id())
+                .with_generation(obj.generation())
+                .with_created_at(obj.created_at())
+                .with_sources(obj.sources())
+                .with_context(Arc::clone(&self.context));
+            if let Some(old) = self.objects.insert(obj.name().to_string(), Arc::new(moved)) {
+                old.detach();
+            }
+        }
+
+        let mut config = self.config.clone();
+        config.format.filename = new_template.to_string();
+        self.set_config(config);
+
+        let manifest = dir.join(MANIFEST_FILE);
+        if manifest.is_file() {
+            std::fs::write(manifest, self.dump_manifest()?)?;
+        }
+        Ok(migrated)
+    }
+
+    /// Moves a prepared set of files into the cache and registers them together
+    ///
+    /// Every regular file in `staging_dir` becomes the object named after
+    /// its file name, replacing registered objects of the same name. The
+    /// files are moved with renames into a new `.publish-<n>` directory of
+    /// the cache, so `staging_dir` must be on the same filesystem as the
+    /// cache directory. Nothing refers to that directory until every file
+    /// is in place; the registry, the shared index (see
+    /// `use_shared_index()`) and a `manifest.json` are then switched to the
+    /// new files at once, so readers see either the old or the new set.
+    /// If any move fails, the files go back to `staging_dir`. The files of
+    /// replaced objects are removed once their last handle is dropped.
+    ///
+    /// # Parameters
+    /// - `staging_dir: impl AsRef<Path>` - Directory holding the new files
+    ///
+    /// # Returns
+    /// `CacheResult<Vec<Arc<CacheObject>>>` - Published objects sorted by name,
+    /// or `PermissionDenied` if a replaced object is sealed
+    pub fn publish(&mut self, staging_dir: impl AsRef<Path>) -> CacheResult<Vec<Arc<CacheObject>>> {
+        if self.in_memory {
+            return Err(CacheError::InvalidConfig(
+                "publish() moves files into the cache directory, in-memory caches have none".to_string(),
+            ));
+        }
+        let mut staged = Vec::new();
+        for entry in std::fs::read_dir(staging_dir.as_ref())? {
+            let source = entry?.path();
+            if !source.is_file() {
+                continue;
+            }
+            let file_name = source.file_name().unwrap_or_default().to_string_lossy();
+            let name = self.context.canonical_name(&file_name).into_owned();
+            self.check_name(&name)?;
+            staged.push((name, source));
+        }
+        staged.sort();
+        if let Some(pair) = staged.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(CacheError::AlreadyExists(format!(
+                "Staged files {} and {} are both named '{}'",
+                pair[0].1.display(),
+                pair[1].1.display(),
+                pair[0].0
+            )));
+        }
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => self.cache_dir()?,
+        };
+
+        // Choose every target before touching the cache directory
+        let mut next_id = self.next_id.load(Ordering::Relaxed);
+        let mut plan: Vec<StagedFile> = Vec::new();
+        for (name, source) in staged {
+            let replaced = self.objects.get(&name).filter(|obj| self.generations.is_live(obj)).cloned();
+            let (id, path, shared) = match &replaced {
+                Some(obj) => {
+                    obj.check_sealed()?;
+                    (obj.id(), obj.path().to_path_buf(), false)
+                }
+                None => {
+                    next_id += 1;
+                    let (path, shared) = self.object_path(&name, next_id - 1, None, None)?;
+                    (next_id - 1, path, shared)
+                }
+            };
+            let target = published_relative(&dir, &path);
+            if let Some(other) = plan.iter().find(|staged| staged.target == target) {
+                return Err(CacheError::AlreadyExists(format!(
+                    "Cache objects '{}' and '{}' would both be published to {}",
+                    other.name,
+                    name,
+                    target.display()
+                )));
+            }
+            plan.push(StagedFile { name, source, target, replaced, id, shared });
+        }
+
+        let generation = create_publish_dir(&dir)?;
+        let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut failure = None;
+        for StagedFile { source, target, .. } in &plan {
+            let target = generation.join(target);
+            let result = target
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::rename(source, &target));
+            if let Err(e) = result {
+                failure = Some(CacheError::Io(e));
+                break;
+            }
+            moved.push((source.clone(), target));
+        }
+        // Switch the shared index with a single rename
+        if failure.is_none()
+            && let Some(index) = &self.index
+        {
+            let switched = index.update(|state| {
+                for (staged, (_, path)) in plan.iter().zip(&moved) {
+                    state.entries.insert(staged.name.clone(), IndexEntry { path: path.clone(), id: staged.id });
+                }
+                state.next_id = state.next_id.max(next_id);
+                Ok(())
+            });
+            failure = switched.err();
+        }
+        if let Some(e) = failure {
+            for (source, target) in moved.into_iter().rev() {
+                let _ = std::fs::rename(target, source);
+            }
+            let _ = std::fs::remove_dir_all(&generation);
+            return Err(e);
+        }
+
+        self.next_id.store(next_id, Ordering::Relaxed);
+        let mut published = Vec::with_capacity(plan.len());
+        let mut retired = Vec::new();
+        for (StagedFile { name, replaced, id, shared, .. }, (_, target)) in plan.into_iter().zip(moved) {
+            if shared {
+                self.context
+                    .storage
+                    .set_shared(&target)
+                    .map_err(|e| CacheError::PermissionDenied(e.to_string()))?;
+            }
+            let mut obj = CacheObject::new(name.clone(), target.clone(), id)
+                .with_generation(self.generations.current)
+                .with_context(Arc::clone(&self.context))
+                .tracking_reads();
+            if let Some(old) = &replaced {
+                obj = obj.with_created_at(old.created_at()).with_sources(old.sources());
+            }
+            obj.reset_metadata()?;
+            #[cfg(feature = "notify")]
+            if let Some(watcher) = &self.watcher {
+                if let Some(old) = &replaced {
+                    watcher.untrack(old.path());
+                }
+                watcher.track(&target, &name, id);
+            }
+            let obj = Arc::new(obj);
+            if let Some(old) = self.objects.insert(name, Arc::clone(&obj)) {
+                old.detach();
+            }
+            match replaced {
+                Some(old) => retired.push(old),
+                None => self.context.events.emit_with(|| CacheEvent::Created {
+                    name: obj.name().to_string(),
+                    id,
+                }),
+            }
+            published.push(obj);
+        }
+
+        let manifest = dir.join(MANIFEST_FILE);
+        if manifest.is_file() {
+            std::fs::write(manifest, self.dump_manifest()?)?;
+        }
+        for obj in &published {
+            obj.written(obj.size()?)?;
+        }
+        for old in retired {
+            // Handles still reading the replaced file keep it until they are dropped
+            old.remove_file(Arc::strong_count(&old) > 1)?;
+        }
+        Ok(published)
+    }
+
+    /// Returns the names of the objects quarantined after repeated read failures
+    ///
+    /// See the `quarantine_after` config option. Quarantined objects stay
+    /// registered but `get()` and reads report them as not found.
+    ///
+    /// # Returns
+    /// `Vec<String>` - Quarantined object names, sorted
+    pub fn quarantined(&self) -> Vec<String> {
+        self.context.quarantine.names()
+    }
+
+    /// Lifts the quarantine of an object, e.g. after repairing its file
+    ///
+    /// Writing the object has the same effect.
+    ///
+    /// # Parameters
+    /// - `name: &str` - Cache object identifier
+    ///
+    /// # Returns
+    /// `bool` - True if the object was quarantined
+    pub fn release_quarantine(&self, name: &str) -> bool {
+        self.context.quarantine.release(&self.context.canonical_name(name))
+    }
+
+    /// Returns the number of cache objects
+    ///
+    /// # Returns
+    /// `usize` - Count of cache objects
+    pub fn len(&self) -> usize {
+        if self.generations.current == 0 && self.promoted.is_empty() {
+            return self.objects.len();
+        }
+        self.iter().count()
+    }
+
+    /// Check if the cache list is empty
+    ///
+    /// # Returns
+    /// `bool` - True if the cache list is empty, false otherwise
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the object count and total size of the cache
+    ///
+    /// # Returns
+    /// `CacheStats` - Current statistics
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            objects: self.registered().count(),
+            bytes: self.registered().filter_map(|obj| obj.size().ok()).sum(),
+            throttle: self.context.storage.throttle(),
+        }
+    }
+
+    /// Removes a cache object by name
+    ///
+    /// # Parameters
+    /// - `name: &Q` - Cache object identifier, any `Borrow<str>`
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn remove<Q: Borrow<str> + ?Sized>(&mut self, name: &Q) -> CacheResult<()> {
+        let context = Arc::clone(&self.context);
+        let name = &*context.canonical_name(name.borrow());
+        self.adopt_promoted();
+        let mut span = OpSpan::start("remove", name);
+        span.hit(self.objects.contains_key(name));
+        if let Some(cache_obj) = self.objects.remove(name) {
+            cache_obj.detach();
+            self.unindex([(name, &*cache_obj)])?;
+            #[cfg(feature = "notify")]
+            if let Some(watcher) = &self.watcher {
+                watcher.untrack(cache_obj.path());
+            }
+            // Callers still holding the object keep reading the file until they drop it
+            span.record(cache_obj.delete_held(Arc::strong_count(&cache_obj) > 1))?;
+
+            let dependents = self.context.graph.removed(name);
+            if self.context.graph.policy() == CascadePolicy::Delete {
+                for dependent in dependents {
+                    self.remove(&dependent)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every object last written by an older version of this application
+    ///
+    /// Compares the `written_by` stamp of each registered object (see
+    /// `CacheConfig::app`) with `version`. Objects stamped by another
+    /// application, without a stamp or with a version that is not
+    /// semantic are kept; call `index_all()` first to include objects
+    /// not registered yet.
+    ///
+    /// # Parameters
+    /// - `version: &str` - Oldest compatible version, e.g. `"2.0.0"`
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects removed, or `InvalidConfig`
+    /// if `version` or the configured `app` is missing or malformed
+    #[cfg(feature = "semver")]
+    pub fn purge_written_by_older_than(&mut self, version: &str) -> CacheResult<usize> {
+        let parse = |version: &str| semver::Version::parse(version.trim_start_matches('v'));
+        let minimum = parse(version)
+            .map_err(|e| CacheError::InvalidConfig(format!("Invalid version '{}': {}", version, e)))?;
+        let Some(app) = &self.config.app else {
+            return Err(CacheError::InvalidConfig("`app` is not configured".to_string()));
+        };
+
+        let mut outdated = Vec::new();
+        for obj in self.iter() {
+            if let Some(written_by) = obj.metadata()?.written_by
+                && written_by.name == app.name
+                && parse(&written_by.version).is_ok_and(|written| written < minimum)
+            {
+                outdated.push(obj.name().to_string());
+            }
+        }
+        for name in &outdated {
+            self.remove(name)?;
+        }
+        Ok(outdated.len())
+    }
+
+    /// Removes every object whose name starts with `prefix`
+    ///
+    /// # Parameters
+    /// - `prefix: &str` - Name prefix, e.g. `"user_42_"`
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects removed
+    pub fn remove_prefix(&mut self, prefix: &str) -> CacheResult<usize> {
+        let context = Arc::clone(&self.context);
+        let prefix = &*context.canonical_name(prefix);
+        self.remove_where(|name| name.starts_with(prefix))
+    }
+
+    /// Removes every object whose name matches a glob pattern
+    ///
+    /// # Parameters
+    /// - `pattern: &str` - Pattern with `*`, `?` and `[...]` wildcards, e.g. `"thumb_*"`
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects removed, or `InvalidConfig`
+    /// for a malformed pattern
+    #[cfg(feature = "glob")]
+    pub fn remove_matching(&mut self, pattern: &str) -> CacheResult<usize> {
+        let pattern = parse_name_pattern(&self.context.canonical_name(pattern))?;
+        self.remove_where(|name| pattern.matches(name))
+    }
+
+    /// Returns the objects whose name matches a glob pattern
+    ///
+    /// # Parameters
+    /// - `pattern: &str` - Pattern with `*`, `?` and `[...]` wildcards
+    ///
+    /// # Returns
+    /// `CacheResult<impl Iterator<Item = &CacheObject>>` - Matching objects,
+    /// or `InvalidConfig` for a malformed pattern
+    #[cfg(feature = "glob")]
+    pub fn iter_matching(&self, pattern: &str) -> CacheResult<impl Iterator<Item = &CacheObject>> {
+        let pattern = parse_name_pattern(&self.context.canonical_name(pattern))?;
+        Ok(self.iter().filter(move |obj| pattern.matches(obj.name())))
+    }
+
+    /// Returns the objects whose name matches a regular expression
+    ///
+    /// # Parameters
+    /// - `pattern: &regex::Regex` - Expression searched in each name; anchor
+    ///   it with `^...$` to match whole names
+    ///
+    /// # Returns
+    /// `impl Iterator<Item = &CacheObject>` - Matching objects
+    #[cfg(feature = "regex")]
+    pub fn find_regex<'a>(&'a self, pattern: &'a regex::Regex) -> impl Iterator<Item = &'a CacheObject> {
+        self.iter().filter(move |obj| pattern.is_match(obj.name()))
+    }
+
+    fn remove_where(&mut self, predicate: impl Fn(&str) -> bool) -> CacheResult<usize> {
+        let names: Vec<String> = self
+            .iter()
+            .filter(|obj| predicate(obj.name()))
+            .map(|obj| obj.name().to_string())
+            .collect();
+        for name in &names {
+            self.remove(name)?;
+        }
+        Ok(names.len())
+    }
 
-        for (name, cache_obj) in &self.objects {
-            if let Err(e) = cache_obj.delete() {
-                errors.push(format!("Failed to delete cache object '{}': {}", name, e));
+    /// Clears all cache objects
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn clear(&mut self) -> CacheResult<()> {
+        self.adopt_promoted();
+        let objects: Vec<(&String, &Arc<CacheObject>)> = self.objects.iter().collect();
+        #[cfg(feature = "notify")]
+        if let Some(watcher) = &self.watcher {
+            for (_, cache_obj) in &objects {
+                watcher.untrack(cache_obj.path());
             }
         }
 
+        let errors: Vec<String> = parallel_map(&objects, self.delete_parallelism, |(name, cache_obj)| {
+            cache_obj.delete_held(Arc::strong_count(cache_obj) > 1).err().map(|e| {
+                log_warn!("failed to delete cache object '{}' during clear: {}", name, e);
+                format!("Failed to delete cache object '{}': {}", name, e)
+            })
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+        self.unindex(self.objects.iter().map(|(name, obj)| (name.as_str(), &**obj)))?;
+        for cache_obj in self.objects.values() {
+            cache_obj.detach();
+        }
         self.objects.clear();
+        self.context.graph.clear();
 
         if !errors.is_empty() {
             return Err(CacheError::Generic(format!(
@@ -218,11 +2166,47 @@ impl Cache {
         Ok(())
     }
 
+    /// Caps the threads used to delete files in `clear()` and `gc()`
+    ///
+    /// Defaults to 4; use 1 to delete sequentially, e.g. on spinning disks.
+    ///
+    /// # Parameters
+    /// - `parallelism: usize` - Maximum number of deleting threads (at least 1)
+    pub fn set_delete_parallelism(&mut self, parallelism: usize) {
+        self.delete_parallelism = parallelism.max(1);
+    }
+
     /// Updates the cache configuration
     ///
+    /// Storage settings (`io`) and switching `metadata` to or from
+    /// `"inline"` only apply to caches created with the configuration.
+    ///
     /// # Parameters
     /// - `config: CacheConfig` - New configuration
-    pub fn set_config(&mut self, config: CacheConfig) {
+    pub fn set_config(&mut self, mut config: CacheConfig) {
+        self.template = FilenameTemplate::new(&config.format.filename, &config.format.time);
+        self.cache_dir = OnceLock::new();
+        self.context.middleware.set_retry(config.retry.clone());
+        self.context.read_buffer.store(config.io.read_buffer, Ordering::Relaxed);
+        self.context.write_buffer.store(config.io.write_buffer, Ordering::Relaxed);
+        self.context
+            .max_entry_bytes
+            .store(config.limits.max_entry_bytes.unwrap_or(0), Ordering::Relaxed);
+        if (config.metadata == MetadataMode::Inline) != self.context.inline.is_some() {
+            // Header framing is part of the storage stack built on creation
+            config.metadata = self.config.metadata;
+        }
+        *self.context.metadata.write().unwrap_or_else(|e| e.into_inner()) = config.metadata;
+        *self.context.app.write().unwrap_or_else(|e| e.into_inner()) = config.app.clone();
+        self.context.quarantine.set_threshold(config.quarantine_after);
+        self.context
+            .shared
+            .store(self.root.is_none() && config.path.scope == CacheScope::System, Ordering::Relaxed);
+        self.context.normalize_names.store(config.normalize_names, Ordering::Relaxed);
+        self.context
+            .fold_case
+            .store(config.name_case == NameCase::Insensitive, Ordering::Relaxed);
+        self.context.space.configure(&config);
         self.config = config;
     }
 
@@ -234,11 +2218,692 @@ impl Cache {
         self.config.clone()
     }
 
+    /// Runs a set of diagnostics against the current configuration
+    ///
+    /// Validates the configuration, probes the cache directory for
+    /// writability, checks its permissions and the free space left on
+    /// the volume. Nothing is created or modified except a short-lived
+    /// probe file.
+    ///
+    /// # Returns
+    /// `DoctorReport` - Outcome of every check
+    pub fn doctor(&self) -> DoctorReport {
+        doctor::run(&self.config, self.root.as_deref())
+    }
+
+    /// Registers an observer receiving every event of this cache
+    ///
+    /// Observers also receive events from cache objects handed out by
+    /// this cache, including ones created before the observer was added.
+    ///
+    /// # Parameters
+    /// - `observer: Arc<dyn CacheObserver>` - Observer to register
+    pub fn add_observer(&self, observer: Arc<dyn CacheObserver>) {
+        self.context.events.add_observer(observer);
+    }
+
+    /// Subscribes to the events of this cache through a channel
+    ///
+    /// Events are sent without blocking the cache operation, so a
+    /// separate thread can consume them at its own pace. Dropping the
+    /// receiver unsubscribes it.
+    ///
+    /// # Returns
+    /// `Receiver<CacheEvent>` - Receiving end of the event channel
+    pub fn subscribe(&self) -> Receiver<CacheEvent> {
+        self.context.events.subscribe()
+    }
+
+    /// Subscribes to the events of this cache as an async stream
+    ///
+    /// Like `subscribe()`, but the returned stream can be awaited from
+    /// any async runtime. Dropping the stream unsubscribes it.
+    ///
+    /// # Returns
+    /// `impl Stream<Item = CacheEvent>` - Stream of cache events
+    #[cfg(feature = "async")]
+    pub fn event_stream(&self) -> impl tokio_stream::Stream<Item = CacheEvent> + Send + Unpin + 'static {
+        tokio_stream::wrappers::UnboundedReceiverStream::new(self.context.events.subscribe_async())
+    }
+
+    /// Writes buffered entries to disk
+    ///
+    /// Only does something in write-back mode (`io.write_back`), where
+    /// writes are otherwise flushed in batches by a background thread.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or the first flush error
+    pub fn flush(&self) -> CacheResult<()> {
+        self.context.storage.flush().map_err(CacheError::Io)
+    }
+
+    /// Writes buffered entries to disk without blocking the runtime
+    ///
+    /// Async variant of `flush()` running on Tokio's blocking pool, for
+    /// explicit sync points in write-back mode.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or the first flush error
+    #[cfg(feature = "async")]
+    pub async fn flush_all(&self) -> CacheResult<()> {
+        let storage = Arc::clone(&self.context.storage);
+        tokio::task::spawn_blocking(move || storage.flush())
+            .await
+            .map_err(|e| CacheError::Task { operation: "flush", source: Box::new(e) })?
+            .map_err(CacheError::Io)
+    }
+
+    /// Adds a middleware wrapped around every cache operation
+    ///
+    /// Middlewares run in the order they were added, the first one
+    /// being the outermost. They also wrap reads, writes and deletes of
+    /// cache objects handed out by this cache.
+    ///
+    /// # Parameters
+    /// - `middleware: Arc<dyn Middleware>` - Middleware to add
+    pub fn add_middleware(&self, middleware: Arc<dyn Middleware>) {
+        self.context.middleware.push(middleware);
+    }
+
+    /// Sets the codec used by `write_value()` and `get_value()`
+    ///
+    /// Applies to every cache object handed out by this cache, including
+    /// existing ones. Defaults to `JsonCodec`.
+    ///
+    /// # Parameters
+    /// - `codec: Arc<dyn Codec>` - Codec to use
+    pub fn set_codec(&self, codec: Arc<dyn Codec>) {
+        *self.context.codec.write().unwrap_or_else(|e| e.into_inner()) = codec;
+    }
+
+    /// Copies every object into a new, independent cache
+    ///
+    /// Objects are recreated in their original creation order under
+    /// `dest_config` and their content is copied; objects invalidated by
+    /// `bump_generation()` are left out. The codec is carried over,
+    /// observers and middleware are not. Later changes to either
+    /// cache do not affect the other.
+    ///
+    /// # Parameters
+    /// - `dest_config: CacheConfig` - Configuration of the new cache
+    ///
+    /// # Returns
+    /// `CacheResult<Cache>` - The forked cache or the first error
+    pub fn fork(&self, dest_config: CacheConfig) -> CacheResult<Cache> {
+        let mut fork = Cache::new(dest_config)?;
+        fork.set_codec(self.context.codec());
+
+        let mut objects: Vec<&CacheObject> = self.iter().collect();
+        objects.sort_by_key(|obj| obj.id());
+        for obj in objects {
+            let copy = fork.create(obj.name(), None)?;
+            if obj.exists() {
+                copy.write_bytes(&obj.get_bytes()?)?;
+            }
+        }
+
+        Ok(fork)
+    }
+
+    /// Imports the objects of another cache
+    ///
+    /// Objects are imported in their creation order; name collisions are
+    /// resolved with `policy`.
+    ///
+    /// # Parameters
+    /// - `other: &Cache` - Cache to import from, left unchanged
+    /// - `policy: ConflictPolicy` - Handling of names present in both caches
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects created or overwritten
+    pub fn merge_from(&mut self, other: &Cache, policy: ConflictPolicy) -> CacheResult<usize> {
+        let mut objects: Vec<&CacheObject> = other.objects.values().map(Arc::as_ref).collect();
+        objects.sort_by_key(|obj| obj.id());
+
+        let mut imported = 0;
+        for obj in objects {
+            let target = match self.objects.get(obj.name()) {
+                None => self.create(obj.name(), None)?,
+                Some(_) if policy == ConflictPolicy::Skip => continue,
+                Some(existing) if policy == ConflictPolicy::Overwrite => existing.clone(),
+                Some(_) => {
+                    let name = (1..)
+                        .map(|n| format!("{}-{}", obj.name(), n))
+                        .find(|name| !self.objects.contains_key(name))
+                        .expect("unbounded range always yields a free name");
+                    self.create(&name, None)?
+                }
+            };
+            if obj.exists() {
+                target.write_bytes(&obj.get_bytes()?)?;
+            }
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+
+    /// Mirrors the cache files into another directory
+    ///
+    /// Only files whose copy in `path` is missing or differs in size or
+    /// modification time are copied; copies keep the source modification
+    /// time so the next call skips them. Metadata sidecars are mirrored
+    /// along with their entries. Each file is streamed to a temporary
+    /// name and renamed, so readers of `path` never see partial files.
+    /// Files of removed objects are left in place.
+    ///
+    /// # Parameters
+    /// - `path: impl AsRef<Path>` - Destination directory, created if missing
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of files copied, sidecars included
+    pub fn sync_to(&self, path: impl AsRef<Path>) -> CacheResult<usize> {
+        let dest_dir = path.as_ref();
+        std::fs::create_dir_all(dest_dir)?;
+
+        let storage = &*self.context.storage;
+        let mut copied = 0;
+        for obj in self.objects.values() {
+            let sidecar = sidecar_path(obj.path());
+            for source in [obj.path(), &sidecar] {
+                let Some(file_name) = source.file_name() else {
+                    continue;
+                };
+                if storage.exists(source) && mirror_file(storage, source, &dest_dir.join(file_name))? {
+                    copied += 1;
+                }
+            }
+        }
+
+        Ok(copied)
+    }
+
+    /// Serializes the registry to JSON
+    ///
+    /// Every object is listed with its name, path, id, creation time and,
+    /// if its content exists, its size and hex SHA-256 checksum. The
+    /// manifest is signed if a signer was set.
+    ///
+    /// # Returns
+    /// `CacheResult<String>` - Pretty-printed manifest or read error
+    pub fn dump_manifest(&self) -> CacheResult<String> {
+        Manifest::from_objects(self.registered().map(Arc::as_ref))?.into_json(self.signer.as_deref())
+    }
+
+    /// Sets the mapper choosing the file of each new object
+    ///
+    /// Replaces `format.filename` for objects created afterwards, for
+    /// layouts a template cannot express. Returned paths are checked to
+    /// be relative and inside the cache directory, collisions are handled
+    /// by `format.on_collision`. `index_all()` cannot recover names from
+    /// mapped paths; list the entries in `manifest.json` instead.
+    ///
+    /// # Parameters
+    /// - `mapper: Arc<dyn KeyMapper>` - Mapper from object names to relative paths
+    pub fn set_key_mapper(&mut self, mapper: Arc<dyn KeyMapper>) {
+        self.key_mapper = Some(mapper);
+    }
+
+    /// Sets the signer used for exported and imported manifests
+    ///
+    /// Once set, `dump_manifest()` and `push()` sign the manifest, while
+    /// `import_manifest()` and `pull()` reject manifests that are unsigned
+    /// or whose signature does not verify.
+    ///
+    /// # Parameters
+    /// - `signer: Arc<dyn ManifestSigner>` - Signer, e.g. `HmacSha256Signer`
+    pub fn set_manifest_signer(&mut self, signer: Arc<dyn ManifestSigner>) {
+        self.signer = Some(signer);
+        self.lazy_manifest = OnceLock::new();
+    }
+
+    /// Parses a manifest, verifying it with the configured signer
+    pub(crate) fn parse_manifest(&self, json: &str) -> CacheResult<Manifest> {
+        Manifest::parse(json, self.signer.as_deref())
+    }
+
+    /// Registers the objects listed in a manifest
+    ///
+    /// Objects keep the recorded paths, resolved against the cache
+    /// directory; no file is created or copied. Names already present in
+    /// this cache are skipped. The whole manifest is rejected, leaving the
+    /// registry untouched, if a path leaves the cache directory or some
+    /// content does not match its recorded checksum.
+    ///
+    /// # Parameters
+    /// - `json: &str` - Manifest produced by `dump_manifest()`
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects registered, `InvalidPath` or `Corrupted`
+    pub fn import_manifest(&mut self, json: &str) -> CacheResult<usize> {
+        let manifest = self.parse_manifest(json)?;
+
+        // Every entry is checked before the registry changes
+        let mut next_id = self.next_id.load(Ordering::Relaxed);
+        let mut seen = std::collections::HashSet::new();
+        let mut imported = Vec::new();
+        for entry in manifest.entries {
+            let name = self.context.canonical_name(&entry.name).into_owned();
+            self.check_name(&name)?;
+            let live = self.objects.get(&name).is_some_and(|obj| self.generations.is_live(obj));
+            if live || !seen.insert(name.clone()) {
+                continue;
+            }
+            let path = self.manifest_path(&name, &entry.path)?;
+            let obj = CacheObject::new(name, path, entry.id)
+                .with_created_at(entry.created_at())
+                .with_sources(entry.sources.clone())
+                .with_generation(self.generations.current)
+                .with_context(Arc::clone(&self.context));
+            if obj.exists() {
+                entry.verify(&obj.get_bytes()?)?;
+            }
+            let after = entry.id.checked_add(1).ok_or_else(|| {
+                CacheError::Corrupted(format!("Cache object '{}' has out of range id {}", entry.name, entry.id))
+            })?;
+            next_id = next_id.max(after);
+            imported.push(obj);
+        }
+
+        self.next_id.store(next_id, Ordering::Relaxed);
+        let count = imported.len();
+        for obj in imported {
+            #[cfg(feature = "notify")]
+            if let Some(watcher) = &self.watcher {
+                watcher.track(obj.path(), obj.name(), obj.id());
+            }
+            // Invalidated objects of the same name are left to `gc()`
+            if let Some(old) = self.objects.insert(obj.name().to_string(), Arc::new(obj)) {
+                old.detach();
+                self.retired.push(old);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Resolves a manifest path against the cache directory
+    ///
+    /// Absolute paths are accepted only inside the cache directory, relative
+    /// ones must not climb out of it with `..`.
+    fn manifest_path(&self, name: &str, path: &Path) -> CacheResult<PathBuf> {
+        let dir = match (&self.root, self.in_memory) {
+            (_, true) => None,
+            (Some(root), false) => Some(root.clone()),
+            (None, false) => Some(self.cache_dir()?),
+        };
+        let relative = dir
+            .as_deref()
+            .and_then(|dir| path.strip_prefix(dir).ok())
+            .unwrap_or(path);
+        let inside = relative.components().all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir))
+            && relative.components().any(|c| matches!(c, std::path::Component::Normal(_)));
+        if !inside {
+            return Err(CacheError::InvalidPath(format!(
+                "Manifest path {} of cache object '{}' is outside the cache directory",
+                path.display(),
+                name
+            )));
+        }
+        Ok(match dir {
+            Some(dir) => dir.join(relative),
+            None => relative.to_path_buf(),
+        })
+    }
+
+    /// Watches the cache directory for changes made by other processes
+    ///
+    /// External modifications and deletions of known objects are reported
+    /// right away as `CacheEvent::Changed` and `CacheEvent::Removed`.
+    /// The registry itself is updated by `refresh()`. Only the cache
+    /// directory itself is watched, not per-object custom paths.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error starting the watcher
+    #[cfg(feature = "notify")]
+    pub fn watch(&mut self) -> CacheResult<()> {
+        let dir = match &self.root {
+            Some(root) => root.clone(),
+            None => resolve_cache_dir(&self.config)?,
+        };
+        std::fs::create_dir_all(&dir)?;
+
+        let watcher = DirWatcher::start(&dir, Arc::clone(&self.context))?;
+        for obj in self.objects.values() {
+            watcher.track(obj.path(), obj.name(), obj.id());
+        }
+        self.watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Registers a callback for objects changed by other processes
+    ///
+    /// The callback runs on the watcher thread with the
+    /// `CacheEvent::Changed` or `CacheEvent::Removed` event of every
+    /// object whose name matches `name_pattern` (`*`, `?` and `[...]`
+    /// wildcards). Changes made through this cache never trigger it.
+    /// Takes effect once `watch()` has been called.
+    ///
+    /// # Parameters
+    /// - `name_pattern: &str` - Glob pattern matched against object names
+    /// - `callback: F` - Called with the change event
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or `InvalidConfig` for a malformed pattern
+    #[cfg(feature = "notify")]
+    pub fn on_invalidated<F>(&self, name_pattern: &str, callback: F) -> CacheResult<()>
+    where
+        F: Fn(&CacheEvent) + Send + Sync + 'static,
+    {
+        let pattern = parse_name_pattern(&self.context.canonical_name(name_pattern))?;
+        self.context.invalidations.add(pattern, Arc::new(callback));
+        Ok(())
+    }
+
+    /// Applies the external changes seen by `watch()` to the registry
+    ///
+    /// Objects whose file was deleted are dropped. New files are
+    /// registered when the object name can be recovered from the file
+    /// name, i.e. when `format.filename` uses `{name}` but neither
+    /// `{time}` nor `{id}`.
+    ///
+    /// # Returns
+    /// `usize` - Number of objects added or dropped
+    #[cfg(feature = "notify")]
+    pub fn refresh(&mut self) -> usize {
+        let Some(watcher) = &self.watcher else {
+            return 0;
+        };
+
+        let mut changes = 0;
+        for path in watcher.take_pending() {
+            match watcher.lookup(&path) {
+                Some((name, _)) if !path.exists() => {
+                    watcher.untrack(&path);
+                    if let Some(old) = self.objects.remove(&name) {
+                        old.detach();
+                    }
+                    changes += 1;
+                }
+                Some(_) => {}
+                None if path.is_file() => {
+                    let Some(name) = path
+                        .file_name()
+                        .and_then(|file_name| name_from_file(&self.config.format.filename, file_name))
+                        .and_then(|key| self.recover_name(key, &path))
+                    else {
+                        continue;
+                    };
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let obj = CacheObject::new(name.clone(), path.clone(), id)
+                        .with_generation(self.generations.current)
+                        .with_context(Arc::clone(&self.context));
+                    watcher.track(&path, &name, id);
+                    self.objects.insert(name.clone(), Arc::new(obj));
+                    self.context.events.emit_with(|| CacheEvent::Created { name, id });
+                    changes += 1;
+                }
+                None => {}
+            }
+        }
+        changes
+    }
+
+    /// Declares that one object was derived from another
+    ///
+    /// Writing `source` afterwards makes `derived` and everything derived
+    /// from it report `is_stale()` until they are rewritten. Removing
+    /// `source` invalidates or removes them according to the cascade policy.
+    ///
+    /// # Parameters
+    /// - `derived: &str` - Name of the derived object
+    /// - `source: &str` - Name of the object it was computed from
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, `EntryNotFound` for unknown objects or
+    /// `InvalidConfig` if the edge would create a cycle
+    pub fn add_dependency(&self, derived: &str, source: &str) -> CacheResult<()> {
+        let (derived, source) = (self.context.canonical_name(derived), self.context.canonical_name(source));
+        for name in [&*derived, &*source] {
+            if !self.objects.contains_key(name) {
+                return Err(CacheError::EntryNotFound { name: name.to_string() });
+            }
+        }
+        self.context.graph.add(&derived, &source)
+    }
+
+    /// Returns the objects directly derived from an object
+    ///
+    /// # Parameters
+    /// - `name: &str` - Source object name
+    ///
+    /// # Returns
+    /// `Vec<String>` - Names of the derived objects, sorted
+    pub fn dependents(&self, name: &str) -> Vec<String> {
+        self.context.graph.dependents(&self.context.canonical_name(name))
+    }
+
+    /// Sets what removing an object does to the objects derived from it
+    ///
+    /// # Parameters
+    /// - `policy: CascadePolicy` - `Invalidate` (default) or `Delete`
+    pub fn set_cascade_policy(&self, policy: CascadePolicy) {
+        self.context.graph.set_policy(policy);
+    }
+
     /// Returns iterator over all cache objects
     ///
     /// # Returns
     /// `impl Iterator<Item = &CacheObject>` - Iterator over cache objects
     pub fn iter(&self) -> impl Iterator<Item = &CacheObject> {
-        self.objects.values()
+        self.registered()
+            .map(Arc::as_ref)
+            .filter(|obj| self.generations.is_live(obj))
+    }
+
+    /// Returns one page of objects ordered by name
+    ///
+    /// Only `page_size` objects are held at a time, so large caches can be
+    /// enumerated page by page. Objects created or removed between calls
+    /// are seen or skipped according to their name, never twice.
+    ///
+    /// # Parameters
+    /// - `cursor: Option<&str>` - `next_cursor` of the previous page, `None` to start
+    /// - `page_size: usize` - Maximum number of objects per page (at least 1)
+    ///
+    /// # Returns
+    /// `CachePage` - Objects of the page and the cursor of the next one
+    pub fn page(&self, cursor: Option<&str>, page_size: usize) -> CachePage {
+        use std::ops::Bound;
+
+        let page_size = page_size.max(1);
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+        let mut live = self
+            .objects
+            .range::<str, _>((start, Bound::Unbounded))
+            .map(|(_, obj)| obj)
+            .filter(|obj| self.generations.is_live(obj));
+        let objects: Vec<Arc<CacheObject>> = live.by_ref().take(page_size).cloned().collect();
+        let more = live.next().is_some();
+        let next_cursor = more
+            .then(|| objects.last().map(|obj| obj.name().to_string()))
+            .flatten();
+        CachePage { objects, next_cursor }
+    }
+
+    /// Returns an iterator over pages of objects ordered by name
+    ///
+    /// # Parameters
+    /// - `page_size: usize` - Maximum number of objects per page (at least 1)
+    ///
+    /// # Returns
+    /// `impl Iterator<Item = Vec<Arc<CacheObject>>>` - Non-empty pages
+    pub fn iter_pages(&self, page_size: usize) -> impl Iterator<Item = Vec<Arc<CacheObject>>> + '_ {
+        let mut cursor: Option<Option<String>> = Some(None);
+        std::iter::from_fn(move || {
+            let page = self.page(cursor.take()?.as_deref(), page_size);
+            cursor = page.next_cursor.map(Some);
+            (!page.objects.is_empty()).then_some(page.objects)
+        })
+    }
+
+    /// Invalidates every object at once
+    ///
+    /// Existing objects are hidden from `get()`, `len()` and `iter()`
+    /// immediately and their names can be created again; their files are
+    /// only deleted by `gc()`.
+    ///
+    /// # Returns
+    /// `u64` - The new generation
+    pub fn bump_generation(&mut self) -> u64 {
+        self.generations.current += 1;
+        self.generations.floor = self.generations.current;
+        self.detach_invalidated();
+        self.generations.current
+    }
+
+    /// Invalidates every object whose name starts with `prefix`
+    ///
+    /// Behaves like `bump_generation()` for that namespace only.
+    ///
+    /// # Parameters
+    /// - `prefix: &str` - Namespace prefix, e.g. `"thumbs."`
+    ///
+    /// # Returns
+    /// `u64` - The new generation
+    pub fn bump_namespace_generation(&mut self, prefix: &str) -> u64 {
+        self.generations.current += 1;
+        self.generations
+            .namespaces
+            .insert(self.context.canonical_name(prefix).into_owned(), self.generations.current);
+        self.detach_invalidated();
+        self.generations.current
+    }
+
+    /// Marks objects hidden by a generation bump as no longer registered
+    fn detach_invalidated(&self) {
+        for obj in self.objects.values().filter(|obj| !self.generations.is_live(obj)) {
+            obj.detach();
+        }
+    }
+
+    /// Returns the generation assigned to newly created objects
+    ///
+    /// # Returns
+    /// `u64` - Current generation, 0 until the first bump
+    pub fn generation(&self) -> u64 {
+        self.generations.current
+    }
+
+    /// Removes entries that were written but never read
+    ///
+    /// Meant for speculative caches: objects created at least `age` ago
+    /// whose content was never read (see `CacheObject::was_read()`) are
+    /// removed like with `remove()`. Reads by other processes are seen
+    /// through the `read_at` marker of `MetadataMode::Sidecar`; objects
+    /// this process did not create and has no such marker for are kept.
+    ///
+    /// # Parameters
+    /// - `age: Duration` - Minimum time since creation
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects removed
+    pub fn gc_unread_older_than(&mut self, age: Duration) -> CacheResult<usize> {
+        let mut unread = Vec::new();
+        for obj in self.iter() {
+            if obj.created_at().elapsed().is_ok_and(|elapsed| elapsed >= age) && obj.read_state()? == Some(false) {
+                unread.push(obj.name().to_string());
+            }
+        }
+        for name in &unread {
+            self.remove(name)?;
+        }
+        Ok(unread.len())
+    }
+
+    /// Deletes the files of objects invalidated by a generation bump
+    ///
+    /// Files still used by a live object (same path) are kept. Objects
+    /// whose files were evicted to keep disk space free are unregistered.
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects reclaimed
+    pub fn gc(&mut self) -> CacheResult<usize> {
+        let stale: Vec<String> = self
+            .objects
+            .values()
+            .filter(|obj| !self.generations.is_live(obj) || self.context.space.is_evicted(obj.path()))
+            .map(|obj| obj.name().to_string())
+            .collect();
+        let mut reclaimed = std::mem::take(&mut self.retired);
+        reclaimed.extend(stale.iter().filter_map(|name| self.objects.remove(name)));
+        for obj in &reclaimed {
+            obj.detach();
+        }
+
+        let live_paths: HashSet<&Path> = self.objects.values().map(|live| live.path()).collect();
+        let orphaned: Vec<&Arc<CacheObject>> = reclaimed
+            .iter()
+            .filter(|obj| !live_paths.contains(obj.path()))
+            .collect();
+        parallel_map(&orphaned, self.delete_parallelism, |obj| obj.delete_held(Arc::strong_count(obj) > 1))
+            .into_iter()
+            .collect::<CacheResult<()>>()?;
+        Ok(reclaimed.len())
+    }
+}
+
+/// Returns a registered object, like `HashMap` indexing
+///
+/// # Panics
+/// If no live object with that name is registered. Objects of an opened
+/// directory that `get()` resolves lazily are not indexed yet; use
+/// `get()` or `try_get()` for them.
+///
+/// ```rust
+/// let mut cache = cache_lite::Cache::in_memory();
+/// cache.insert("greeting", b"hello").unwrap();
+/// assert_eq!(cache["greeting"].get_string().unwrap(), "hello");
+/// assert!(cache.try_get("missing").is_none());
+/// ```
+impl<Q: Borrow<str> + ?Sized> Index<&Q> for Cache {
+    type Output = Arc<CacheObject>;
+
+    fn index(&self, name: &Q) -> &Arc<CacheObject> {
+        let name = name.borrow();
+        self.objects
+            .get(&*self.context.canonical_name(name))
+            .filter(|obj| self.generations.is_live(obj))
+            .unwrap_or_else(|| panic!("no cache object named '{}'", name))
+    }
+}
+
+/// Upserts every `(name, content)` pair, see `Cache::upsert()`
+///
+/// Failed entries are skipped; their errors reach observers as
+/// `CacheEvent::Error`. Use `upsert()` directly to stop at the first one.
+impl Extend<(String, Vec<u8>)> for Cache {
+    fn extend<I: IntoIterator<Item = (String, Vec<u8>)>>(&mut self, iter: I) {
+        for (name, content) in iter {
+            let _ = self.upsert(&name, &content);
+        }
+    }
+}
+
+/// Collects `(name, content)` pairs into a new `Cache::in_memory()`
+///
+/// ```rust
+/// let cache: cache_lite::Cache = [("a".to_string(), b"1".to_vec()), ("b".to_string(), b"2".to_vec())]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(cache.get("b").unwrap().get_string().unwrap(), "2");
+/// ```
+impl FromIterator<(String, Vec<u8>)> for Cache {
+    fn from_iter<I: IntoIterator<Item = (String, Vec<u8>)>>(iter: I) -> Self {
+        let mut cache = Cache::in_memory();
+        cache.extend(iter);
+        cache
     }
 }
@@ -0,0 +1,110 @@
+/*
+ * @filename: codec.rs
+ * @description: Pluggable value codecs for cache-lite library
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{CacheError, CacheResult};
+
+/// Callback receiving the deserializer produced by a codec
+pub type DecodeVisitor<'a> =
+    dyn for<'de> FnMut(&mut dyn erased_serde::Deserializer<'de>) -> Result<(), erased_serde::Error> + 'a;
+
+/// Converts values to and from the bytes stored in a cache file
+///
+/// The trait is object safe so a codec can be configured per cache with
+/// `Cache::set_codec()` or passed per call to `write_value_with()` and
+/// `get_value_with()`. Any serde data format can be plugged in through
+/// `erased_serde`.
+///
+/// # Example
+///
+/// ```
+/// use cache_lite::{CacheError, CacheResult, Codec, DecodeVisitor};
+///
+/// struct CompactJson;
+///
+/// impl Codec for CompactJson {
+///     fn encode(&self, value: &dyn erased_serde::Serialize) -> CacheResult<Vec<u8>> {
+///         serde_json::to_vec(value).map_err(|e| CacheError::Serialization(e.to_string()))
+///     }
+///
+///     fn decode(&self, bytes: &[u8], visit: &mut DecodeVisitor<'_>) -> CacheResult<()> {
+///         let mut de = serde_json::Deserializer::from_slice(bytes);
+///         visit(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+///             .map_err(|e| CacheError::Serialization(e.to_string()))
+///     }
+/// }
+/// ```
+pub trait Codec: Send + Sync {
+    /// Serializes a value into bytes
+    ///
+    /// # Parameters
+    /// - `value: &dyn erased_serde::Serialize` - Value to encode
+    ///
+    /// # Returns
+    /// `CacheResult<Vec<u8>>` - Encoded bytes or `Serialization` error
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> CacheResult<Vec<u8>>;
+
+    /// Hands a deserializer reading `bytes` to `visit`
+    ///
+    /// # Parameters
+    /// - `bytes: &[u8]` - Encoded bytes
+    /// - `visit: &mut DecodeVisitor` - Callback building the value
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or `Serialization` error
+    fn decode(&self, bytes: &[u8], visit: &mut DecodeVisitor<'_>) -> CacheResult<()>;
+}
+
+/// JSON codec backed by `serde_json`, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> CacheResult<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|e| CacheError::Serialization(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8], visit: &mut DecodeVisitor<'_>) -> CacheResult<()> {
+        let mut de = serde_json::Deserializer::from_slice(bytes);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+            .map_err(|e| CacheError::Serialization(e.to_string()))?;
+        de.end().map_err(|e| CacheError::Serialization(e.to_string()))
+    }
+}
+
+/// Encodes a value with the given codec
+pub(crate) fn encode<T: serde::Serialize + ?Sized>(codec: &dyn Codec, value: &T) -> CacheResult<Vec<u8>> {
+    codec.encode(&value)
+}
+
+/// Decodes a value with the given codec
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(codec: &dyn Codec, bytes: &[u8]) -> CacheResult<T> {
+    let mut value = None;
+    codec.decode(bytes, &mut |de| {
+        value = Some(erased_serde::deserialize::<T>(de)?);
+        Ok(())
+    })?;
+    value.ok_or_else(|| CacheError::Serialization("Codec did not produce a value".to_string()))
+}
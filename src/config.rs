@@ -25,6 +25,7 @@
  */
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use crate::{CacheError, CacheResult};
 
 /// Main configuration structure for cache behavior
@@ -36,55 +37,529 @@ use crate::{CacheError, CacheResult};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]  
 pub struct CacheConfig {
+    /// Layout version, see `CacheConfig::upgrade()`
+    pub version: u32,
     pub path: CachePathConfig,
     pub format: CacheFormatConfig,
+    /// Maximum total cache size in bytes (0 means no limit)
     pub max_size: u64,
-    pub max_files: usize
+    /// Maximum number of cache files (0 means no limit)
+    pub max_files: usize,
+    /// Low-level I/O behavior of the filesystem backend
+    pub io: CacheIoConfig,
+    /// Retries of operations failing with transient I/O errors
+    pub retry: CacheRetryConfig,
+    /// Hard limits on single entries
+    pub limits: CacheLimitsConfig,
+    /// Refuse to create objects over files this cache did not create
+    /// (e.g. unrelated files whose name matches the file name format)
+    pub protect_existing_files: bool,
+    /// Where entry metadata (`CacheObject::metadata()`) is stored
+    pub metadata: MetadataMode,
+    /// Application recorded in the metadata of every written entry
+    pub app: Option<AppInfo>,
+    /// Consecutive failed reads after which an object is quarantined and
+    /// looked up as not found (0 disables quarantining)
+    pub quarantine_after: u32,
+    /// Handling of names `create()` cannot use as file names verbatim
+    pub name_policy: NamePolicy,
+    /// Normalize names to Unicode NFC, so a name typed as decomposed
+    /// characters (as macOS does) finds the entry created with composed
+    /// ones; disable for byte-exact names
+    pub normalize_names: bool,
+    /// Whether names differing only by case are distinct objects
+    pub name_case: NameCase,
+    /// Named partial configs merged over this one by `with_profile()`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, serde_json::Value>
+}
+
+/// Environment variable naming the profile `CacheConfig::new()` applies
+pub const PROFILE_ENV: &str = "CACHE_LITE_PROFILE";
+
+/// Current configuration layout version
+///
+/// - `1`: the layout before versioning, where `path.linux` also applied
+///   to macOS and the BSDs
+/// - `2`: per-platform fields (`unix`, `default`, `platforms`, ...)
+pub const CONFIG_VERSION: u32 = 2;
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            version: CONFIG_VERSION,
+            path: CachePathConfig::default(),
+            format: CacheFormatConfig::default(),
+            max_size: 0,
+            max_files: 0,
+            io: CacheIoConfig::default(),
+            retry: CacheRetryConfig::default(),
+            limits: CacheLimitsConfig::default(),
+            protect_existing_files: false,
+            metadata: MetadataMode::None,
+            app: None,
+            quarantine_after: 0,
+            name_policy: NamePolicy::Strict,
+            normalize_names: true,
+            name_case: NameCase::Sensitive,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Name and semantic version of the application writing entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppInfo {
+    /// Application name, only entries of the same name are compared
+    pub name: String,
+    /// Version as `major.minor.patch`, compared by `Cache::purge_written_by_older_than()`
+    pub version: String,
+}
+
+/// Handling of object names that are not valid file names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamePolicy {
+    /// Reject them with `CacheError::InvalidName`
+    #[default]
+    Strict,
+    /// Percent-encode the offending characters (and `%`) in the file
+    /// name, so `a/b` is stored as `a%2Fb` and other names are unchanged
+    Sanitize,
+}
+
+/// Case sensitivity of object names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameCase {
+    /// `Foo` and `foo` are distinct objects. On case-insensitive file
+    /// systems (Windows, macOS) their files collide, which is handled by
+    /// `format.on_collision` like any other shared path
+    #[default]
+    Sensitive,
+    /// Names are lowercased, so `Foo` and `foo` are the same object
+    Insensitive,
+}
+
+/// Storage of entry metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataMode {
+    /// Nothing is stored, `metadata()` only reports the creation time
+    #[default]
+    None,
+    /// A `<file>.meta` JSON file next to each entry, updated on every
+    /// write, so the entry itself holds exactly the written bytes
+    Sidecar,
+    /// A header framed at the start of each entry (see
+    /// `InlineMetaStorage`), keeping entries single files. Fixed when the
+    /// cache is created: `set_config()` cannot switch to or from it
+    Inline,
+}
+
+/// Hard limits enforced on every write
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheLimitsConfig {
+    /// Largest entry in bytes; bigger writes fail with `SizeLimitExceeded`
+    /// unless forced (`CacheObject::force_write_bytes()`). Unlimited by default.
+    pub max_entry_bytes: Option<u64>,
+    /// Free space in bytes that writes must leave on the cache volume;
+    /// below it `on_low_space` applies. Not checked by default.
+    pub min_free_bytes: Option<u64>,
+    /// What writes do when they would go below `min_free_bytes`
+    pub on_low_space: LowSpacePolicy,
+}
+
+/// Behavior of writes that would leave less than `CacheLimitsConfig::min_free_bytes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LowSpacePolicy {
+    /// Fail with `CacheError::DiskFull` before writing anything
+    #[default]
+    Fail,
+    /// Delete the least recently modified entries until enough space is
+    /// free, failing with `DiskFull` if that is not enough
+    Evict,
+}
+
+/// Retry policy for transient I/O failures, see `CacheError::is_retryable()`
+///
+/// Failed attempts are retried after `backoff_ms`, doubling on every
+/// attempt up to `max_backoff_ms`. Middlewares see a single call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheRetryConfig {
+    /// Total attempts per operation, 1 (the default) disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry in milliseconds, 10 by default
+    pub backoff_ms: u64,
+    /// Upper bound of the delay in milliseconds, 1000 by default
+    pub max_backoff_ms: u64
+}
+
+impl Default for CacheRetryConfig {
+    fn default() -> Self {
+        CacheRetryConfig {
+            max_attempts: 1,
+            backoff_ms: 10,
+            max_backoff_ms: 1000,
+        }
+    }
+}
+
+/// I/O tuning of the filesystem backend
+///
+/// `direct` and `advise` are read when the cache is created, the buffer
+/// sizes also follow `Cache::set_config()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheIoConfig {
+    /// Read and write entries with direct I/O (`O_DIRECT` on Linux,
+    /// `FILE_FLAG_NO_BUFFERING` on Windows), bypassing the OS page cache.
+    /// Falls back to buffered I/O where the filesystem refuses it.
+    pub direct: bool,
+    /// Page cache hint passed to the kernel (`posix_fadvise`, Linux only)
+    pub advise: IoAdvice,
+    /// Buffer size in bytes for streamed reads (`copy_to_writer()`),
+    /// 64 KiB by default
+    pub read_buffer: usize,
+    /// Buffer size in bytes for streamed writes (`copy_from_reader()`,
+    /// `begin_write()`), 256 KiB by default
+    pub write_buffer: usize,
+    /// Keeps written entries in memory and flushes them in batches,
+    /// see `WriteBackStorage`. Disabled by default.
+    pub write_back: Option<WriteBackConfig>,
+    /// Limit on the bytes written to disk per second, see
+    /// `ThrottledStorage`. Unlimited by default.
+    pub max_write_bytes_per_sec: Option<u64>
+}
+
+/// Flush schedule and queue limit of write-back mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteBackConfig {
+    /// Milliseconds between two batched flushes, 1000 by default
+    pub interval_ms: u64,
+    /// Dirty bytes triggering a flush before the timer fires, 64 MiB by default
+    pub max_dirty_bytes: usize,
+    /// Bytes held in memory (dirty or being flushed) beyond which writes
+    /// apply `on_full`, unbounded by default
+    pub max_queue_bytes: Option<usize>,
+    /// What writes do while the queue is full
+    pub on_full: QueueFullPolicy
+}
+
+impl Default for WriteBackConfig {
+    fn default() -> Self {
+        WriteBackConfig {
+            interval_ms: 1000,
+            max_dirty_bytes: 64 * 1024 * 1024,
+            max_queue_bytes: None,
+            on_full: QueueFullPolicy::Block,
+        }
+    }
+}
+
+/// Behavior of writes exceeding `WriteBackConfig::max_queue_bytes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueFullPolicy {
+    /// Wait until a flush makes room; async writes (`write_bytes_async()`)
+    /// wait on Tokio's blocking pool without stalling the runtime. Fails
+    /// with the flush error once a flush fails to make room.
+    #[default]
+    Block,
+    /// Fail immediately with an `io::ErrorKind::WouldBlock` error
+    Fail,
+}
+
+impl Default for CacheIoConfig {
+    fn default() -> Self {
+        CacheIoConfig {
+            direct: false,
+            advise: IoAdvice::Normal,
+            read_buffer: 64 * 1024,
+            write_buffer: 256 * 1024,
+            write_back: None,
+            max_write_bytes_per_sec: None,
+        }
+    }
+}
+
+impl CacheIoConfig {
+    /// Checks whether the filesystem backend needs tuning beyond buffer sizes
+    pub(crate) fn tunes_storage(&self) -> bool {
+        self.direct || self.advise != IoAdvice::Normal
+    }
+}
+
+/// Page cache hint for cache files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoAdvice {
+    /// No hint, the kernel's default read-ahead and caching
+    #[default]
+    Normal,
+    /// Entries are read front to back, read ahead aggressively
+    Sequential,
+    /// Drop entries from the page cache after reads and large (1 MiB+)
+    /// writes, so they do not evict more valuable pages
+    DontNeed,
 }
 
 /// Platform-specific path configuration
+///
+/// Paths may use the `{cache_dir}` placeholder, which expands to the
+/// directory given to `set_platform_cache_dir()` or else to the
+/// platform's conventional per-user cache directory, as well as `${VAR}`
+/// and `%VAR%` environment variable references on every platform.
+///
+/// Entries of the `platforms` map take precedence over the fields below:
+/// the current OS (`std::env::consts::OS`, e.g. `"macos"`, `"freebsd"`),
+/// then its family (`"unix"`/`"windows"`), then `"default"`. This lets one
+/// file describe every platform without blanking out the built-in paths:
+///
+/// ```json
+/// { "platforms": { "windows": "%localappdata%/App", "macos": "~/Library/Caches/App", "default": "~/.cache/app" } }
+/// ```
+///
+/// Otherwise the first non-empty field in the platform's resolution order
+/// is used:
+///
+/// | Platform                  | Order                            |
+/// |---------------------------|----------------------------------|
+/// | Windows                   | `windows`, `default`             |
+/// | Linux                     | `linux`, `unix`, `default`       |
+/// | Android                   | `android`, `default`             |
+/// | iOS                       | `ios`, `default`                 |
+/// | Other Unix (macOS, BSDs)  | `unix`, `default`, `linux`       |
+///
+/// `linux` stays last for other Unix systems because older configs relied
+/// on it being used there.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]  
 pub struct CachePathConfig {
     pub windows: String,
     pub linux: String,
+    /// Used on Android, where the app cache directory must be injected
+    pub android: String,
+    /// Used on iOS, defaults to the app's `Library/Caches`
+    pub ios: String,
+    /// Used on Unix systems without a more specific field (macOS, BSDs)
+    pub unix: String,
+    /// Used when no platform-specific field applies
+    pub default: String,
+    /// Per-user, machine-wide or explicit cache location
+    pub scope: CacheScope,
+    /// What to do when a `${VAR}` or `%VAR%` reference is undefined
+    pub undefined_vars: UndefinedVarPolicy,
+    /// Paths keyed by OS name, OS family or `"default"`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub platforms: BTreeMap<String, String>,
+    /// On Linux, expand `{cache_dir}` to `/tmp` instead of
+    /// `$XDG_CACHE_HOME` (or `~/.cache`), restoring the pre-XDG default
+    /// location `/tmp/Rust/Cache`
+    pub legacy_tmp_dir: bool,
+    /// Fail `Cache::new()` when the cache path is on an unreachable
+    /// network share (UNC path) instead of failing on first write
+    pub fail_fast_offline: bool,
 }
 
 impl Default for CachePathConfig {
     fn default() -> Self {
         CachePathConfig {
             windows: "%temp%/Rust/Cache".to_string(),
-            linux: "/tmp/Rust/Cache".to_string(),
+            linux: "{cache_dir}/Rust/Cache".to_string(),
+            android: "{cache_dir}/Rust/Cache".to_string(),
+            ios: "{cache_dir}/Rust/Cache".to_string(),
+            unix: String::new(),
+            default: String::new(),
+            scope: CacheScope::User,
+            undefined_vars: UndefinedVarPolicy::Error,
+            platforms: BTreeMap::new(),
+            legacy_tmp_dir: false,
+            fail_fast_offline: false,
         }
     }
 }
 
+impl CachePathConfig {
+    /// Returns the fields consulted on the current platform, in order
+    ///
+    /// # Returns
+    /// `Vec<&str>` - Candidate path templates, possibly empty
+    pub fn resolution_order(&self) -> Vec<&str> {
+        let mut order: Vec<&str> = [std::env::consts::OS, std::env::consts::FAMILY, "default"]
+            .iter()
+            .filter_map(|key| self.platforms.get(*key).map(String::as_str))
+            .collect();
+
+        let fields: [&str; _] = if cfg!(windows) {
+            [&self.windows, &self.default, ""]
+        } else if cfg!(target_os = "android") {
+            [&self.android, &self.default, ""]
+        } else if cfg!(target_os = "ios") {
+            [&self.ios, &self.default, ""]
+        } else if cfg!(target_os = "linux") {
+            [&self.linux, &self.unix, &self.default]
+        } else {
+            [&self.unix, &self.default, &self.linux]
+        };
+        order.extend(fields);
+        order
+    }
+
+    /// Returns the unexpanded path configured for the current platform
+    ///
+    /// # Returns
+    /// `&str` - First non-empty path in the resolution order, or `""`
+    pub fn resolve(&self) -> &str {
+        self.resolution_order()
+            .into_iter()
+            .find(|path| !path.is_empty())
+            .unwrap_or("")
+    }
+
+    /// Overrides fields with the non-empty fields of `other`
+    ///
+    /// # Parameters
+    /// - `other: &CachePathConfig` - Overriding configuration
+    pub fn merge(&mut self, other: &CachePathConfig) {
+        let fields = [
+            (&mut self.windows, &other.windows),
+            (&mut self.linux, &other.linux),
+            (&mut self.android, &other.android),
+            (&mut self.ios, &other.ios),
+            (&mut self.unix, &other.unix),
+            (&mut self.default, &other.default),
+        ];
+        for (field, value) in fields {
+            if !value.is_empty() {
+                field.clone_from(value);
+            }
+        }
+        for (platform, path) in &other.platforms {
+            self.platforms.insert(platform.clone(), path.clone());
+        }
+        if other.scope != CacheScope::default() {
+            self.scope = other.scope;
+        }
+        if other.undefined_vars != UndefinedVarPolicy::default() {
+            self.undefined_vars = other.undefined_vars;
+        }
+        self.legacy_tmp_dir |= other.legacy_tmp_dir;
+        self.fail_fast_offline |= other.fail_fast_offline;
+    }
+}
+
+/// Whose cache directory the `{cache_dir}` placeholder refers to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheScope {
+    /// Per-user cache directory (`~/.cache`, `%LOCALAPPDATA%`, ...)
+    #[default]
+    User,
+    /// Machine-wide cache directory shared by every user and service
+    /// (`/var/cache`, `/Library/Caches`, `%ProgramData%`); directories and
+    /// files are created group-writable
+    System,
+    /// Only explicit paths, `{cache_dir}` is rejected
+    Custom,
+}
+
+/// Handling of undefined environment variables in cache paths
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UndefinedVarPolicy {
+    /// Fail with `InvalidPath` naming the variable
+    #[default]
+    Error,
+    /// Keep the reference (e.g. `${VAR}`) literally in the path
+    Passthrough,
+}
+
 /// File naming format configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]  
 pub struct CacheFormatConfig {
     pub filename: String,
-    pub time: String
+    pub time: String,
+    /// What to do when a new object's file name is already used by
+    /// another object (possible when `filename` has no `{id}`)
+    pub on_collision: CollisionPolicy,
+    /// Token appended to every file stem so paths are unique whatever
+    /// `filename` expands to
+    pub uniqueness: Option<Uniqueness>,
+    /// Encoding of `{name}` in file names; with one set, any non-empty
+    /// UTF-8 name (URLs, paths, ...) is accepted as object name
+    pub key_encoding: Option<KeyEncoding>,
+    /// What to do when a formatted path exceeds the file name or path
+    /// length limit of the platform
+    pub on_long_path: LongPathPolicy,
 }
 
 impl Default for CacheFormatConfig {
     fn default() -> Self {
         CacheFormatConfig {
             filename: "r{name}.{time}.cache".to_string(),
-            time: "%Y+%m+%d-%H+%M+%S".to_string()
+            time: "%Y+%m+%d-%H+%M+%S".to_string(),
+            on_collision: CollisionPolicy::Suffix,
+            uniqueness: None,
+            key_encoding: None,
+            on_long_path: LongPathPolicy::Error,
         }
     }
 }
 
-impl Default for CacheConfig {
-    fn default() -> Self {
-        CacheConfig {
-            path: CachePathConfig::default(),
-            format: CacheFormatConfig::default(),
-            max_size: 0,  // 0 means no limit
-            max_files: 0, // 0 means no limit
-        }
-    }
+/// Unique token `format.uniqueness` appends to file stems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Uniqueness {
+    /// Random UUID v4 (`r{name}-5f0c...e2.cache`)
+    Uuid,
+    /// Object id, counting further while the path exists on disk
+    Counter,
+    /// SHA-256 of the object name, stable across runs and processes
+    NameHash,
+}
+
+/// Encoding `format.key_encoding` applies to object names in file names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyEncoding {
+    /// Every byte but ASCII letters, digits, `-` and `_` as `%XX`,
+    /// readable and reversible (`https%3A%2F%2Fexample%2Ecom`)
+    Percent,
+    /// Hex SHA-256 of the name, fixed length whatever the name; the name
+    /// is kept in the entry metadata so `index_all()` can recover it,
+    /// which requires `metadata` other than `none`
+    Hash,
+}
+
+/// Handling of formatted paths that are too long for the platform
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LongPathPolicy {
+    /// Fail `create()` with `CacheError::InvalidPath`
+    #[default]
+    Error,
+    /// Shorten `{name}` and append `~` and 16 hex digits of the name's
+    /// SHA-256, keeping file names distinct; the full name is kept in the
+    /// entry metadata so `index_all()` can recover it, which requires
+    /// `metadata` other than `none`
+    Truncate,
+}
+
+/// Handling of objects whose formatted file names collide
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionPolicy {
+    /// Append `~1`, `~2`, ... to the file stem until the path is free
+    #[default]
+    Suffix,
+    /// Fail with `CacheError::AlreadyExists`
+    Error,
 }
 
 impl CacheConfig {
@@ -96,15 +571,136 @@ impl CacheConfig {
     /// # Returns
     /// New CacheConfig instance or error if parsing fails
     pub fn new(json_config: &str) -> CacheResult<Self> {
+        Self::new_with(json_config, |name| std::env::var(name).ok())
+    }
+
+    /// Parses a config like `new()`, reading `CACHE_LITE_PROFILE` through `lookup`
+    pub(crate) fn new_with(json_config: &str, lookup: impl Fn(&str) -> Option<String>) -> CacheResult<Self> {
         let json_config = json_config
             .trim()
             .replace('\\', "/") 
             .replace(r#"\""#, r#"""#); 
         
-        serde_json::from_str(&json_config)
-            .map_err(|e| CacheError::ConfigParse(format!("Failed to parse config: {}\nInput: {}", e, json_config)))
+        let mut value: serde_json::Value = serde_json::from_str(&json_config)
+            .map_err(|e| CacheError::ConfigParse(format!("Failed to parse config: {}\nInput: {}", e, json_config)))?;
+        upgrade_value(&mut value)?;
+        let config: Self = serde_json::from_value(value)
+            .map_err(|e| CacheError::ConfigParse(format!("Failed to parse config: {}\nInput: {}", e, json_config)))?;
+        match lookup(PROFILE_ENV) {
+            Some(profile) if !config.profiles.is_empty() => config.with_profile(&profile),
+            _ => Ok(config),
+        }
+    }
+
+    /// Returns the configuration with a profile of `profiles` applied
+    ///
+    /// The profile is a partial config merged field by field over this
+    /// one, e.g. `{ "profiles": { "prod": { "max_size": 1073741824 } } }`.
+    /// `new()` applies the profile named by `CACHE_LITE_PROFILE` to configs
+    /// defining profiles.
+    ///
+    /// # Parameters
+    /// - `profile: &str` - Profile name, e.g. `"prod"`
+    ///
+    /// # Returns
+    /// `CacheResult<Self>` - Merged configuration, or `InvalidConfig` for
+    /// an unknown profile
+    pub fn with_profile(&self, profile: &str) -> CacheResult<Self> {
+        let Some(overrides) = self.profiles.get(profile) else {
+            return Err(CacheError::InvalidConfig(format!(
+                "unknown config profile '{}', expected one of: {}",
+                profile,
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            )));
+        };
+        let mut value = serde_json::to_value(self).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        merge_json(&mut value, overrides);
+        serde_json::from_value(value)
+            .map_err(|e| CacheError::ConfigParse(format!("Invalid config profile '{}': {}", profile, e)))
+    }
+
+    /// Converts a configuration written for an older layout to the current one
+    ///
+    /// Configs without `version` are layout 1. `new()` upgrades on the fly;
+    /// use this to rewrite deployed files once. Every step keeps the
+    /// effective settings, e.g. `path.linux` of a layout 1 config is
+    /// copied to `path.unix` so macOS and the BSDs keep using it.
+    ///
+    /// # Parameters
+    /// - `json_config: &str` - JSON configuration of any supported version
+    ///
+    /// # Returns
+    /// `CacheResult<String>` - Pretty-printed JSON at `CONFIG_VERSION`, or
+    /// `ConfigParse` for invalid JSON or a version newer than supported
+    pub fn upgrade(json_config: &str) -> CacheResult<String> {
+        let mut value: serde_json::Value = serde_json::from_str(json_config)
+            .map_err(|e| CacheError::ConfigParse(format!("Failed to parse config: {}", e)))?;
+        upgrade_value(&mut value)?;
+        serde_json::to_string_pretty(&value).map_err(|e| CacheError::Serialization(e.to_string()))
     }
     
+    /// Validates the configuration values
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or `InvalidConfig` describing the first problem
+    pub fn validate(&self) -> CacheResult<()> {
+        if self.path.resolve().is_empty() {
+            return Err(CacheError::InvalidConfig(
+                "cache path for the current platform cannot be empty".to_string(),
+            ));
+        }
+        if self.path.scope == CacheScope::Custom && self.path.resolve().contains("{cache_dir}") {
+            return Err(CacheError::InvalidConfig(
+                "path.scope 'custom' requires an explicit path without {cache_dir}".to_string(),
+            ));
+        }
+        if self.format.filename.is_empty() {
+            return Err(CacheError::InvalidConfig("format.filename cannot be empty".to_string()));
+        }
+        if !self.format.filename.contains("{name}") && !self.format.filename.contains("{id}") {
+            return Err(CacheError::InvalidConfig(
+                "format.filename must contain {name} or {id}, otherwise every object shares one file".to_string(),
+            ));
+        }
+        self.check_key_storage()?;
+        #[cfg(not(feature = "uuid"))]
+        if self.format.uniqueness == Some(Uniqueness::Uuid) {
+            return Err(CacheError::InvalidConfig(
+                "format.uniqueness 'uuid' needs the `uuid` feature".to_string(),
+            ));
+        }
+        #[cfg(feature = "time")]
+        if self.format.filename.contains("{time}")
+            && chrono::format::StrftimeItems::new(&self.format.time)
+                .any(|item| matches!(item, chrono::format::Item::Error))
+        {
+            return Err(CacheError::InvalidConfig(format!(
+                "format.time '{}' is not a valid time format",
+                self.format.time
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fails with `InvalidConfig` if names would be lost from file names
+    /// without metadata to keep them in
+    pub(crate) fn check_key_storage(&self) -> CacheResult<()> {
+        if self.metadata != MetadataMode::None {
+            return Ok(());
+        }
+        if self.format.key_encoding == Some(KeyEncoding::Hash) {
+            return Err(CacheError::InvalidConfig(
+                "format.key_encoding 'hash' needs metadata 'sidecar' or 'inline' to keep the original names".to_string(),
+            ));
+        }
+        if self.format.on_long_path == LongPathPolicy::Truncate {
+            return Err(CacheError::InvalidConfig(
+                "format.on_long_path 'truncate' needs metadata 'sidecar' or 'inline' to keep the original names".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Creates a new CacheConfig from JSON string, falling back to default on error
     /// 
     /// # Parameters
@@ -113,9 +709,61 @@ impl CacheConfig {
     /// # Returns
     /// New CacheConfig instance (falls back to default on parse error)
     pub fn new_or_default(json_config: &str) -> Self {
-        match Self::new(json_config) {
-            Ok(config) => config,
-            Err(_) => Self::default(),
+        Self::new(json_config).unwrap_or_default()
+    }
+}
+
+/// Merges `overrides` into `target`, recursing into objects
+fn merge_json(target: &mut serde_json::Value, overrides: &serde_json::Value) {
+    match (target, overrides) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match target.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, overrides) => *target = overrides.clone(),
+    }
+}
+
+/// Applies the upgrade steps from the config's `version` to `CONFIG_VERSION`
+fn upgrade_value(value: &mut serde_json::Value) -> CacheResult<()> {
+    let Some(config) = value.as_object_mut() else {
+        return Err(CacheError::ConfigParse("config must be a JSON object".to_string()));
+    };
+    let version = match config.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| CacheError::ConfigParse(format!("invalid config version {}", version)))?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(CacheError::ConfigParse(format!(
+            "config version {} is newer than the supported version {}",
+            version, CONFIG_VERSION
+        )));
+    }
+
+    if version < 2
+        && let Some(path) = config.get_mut("path").and_then(|path| path.as_object_mut())
+    {
+        // Layout 1 used `linux` on every Unix, make that explicit
+        let unset = |path: &serde_json::Map<String, serde_json::Value>, key: &str| {
+            path.get(key).and_then(|value| value.as_str()).is_none_or(str::is_empty)
+        };
+        if unset(path, "unix")
+            && unset(path, "default")
+            && let Some(linux) = path.get("linux").cloned()
+        {
+            path.insert("unix".to_string(), linux);
         }
     }
-}
\ No newline at end of file
+
+    config.insert("version".to_string(), CONFIG_VERSION.into());
+    Ok(())
+}
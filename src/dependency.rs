@@ -0,0 +1,192 @@
+/*
+ * @filename: dependency.rs
+ * @description: Tracking the inputs a cache object was derived from
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::manifest::sha256_hex;
+use crate::{CacheError, CacheResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+/// Source file recorded with `CacheObject::depends_on()`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SourceDependency {
+    pub(crate) path: PathBuf,
+    /// Modification time when recorded, used to skip hashing unchanged files
+    pub(crate) modified: Option<SystemTime>,
+    /// Hex SHA-256 of the content when recorded
+    pub(crate) sha256: String,
+}
+
+impl SourceDependency {
+    /// Records the current state of a source file
+    pub(crate) fn record(path: &Path) -> CacheResult<Self> {
+        let content = std::fs::read(path)?;
+        Ok(SourceDependency {
+            path: path.to_path_buf(),
+            modified: std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+            sha256: sha256_hex(&content),
+        })
+    }
+
+    /// Checks whether the source no longer matches the recorded state
+    ///
+    /// A missing or unreadable source counts as changed. Files with a new
+    /// modification time but identical content do not.
+    pub(crate) fn has_changed(&self) -> bool {
+        let modified = std::fs::metadata(&self.path).and_then(|m| m.modified());
+        match modified {
+            Ok(modified) if Some(modified) == self.modified => false,
+            Ok(_) => std::fs::read(&self.path).map_or(true, |content| sha256_hex(&content) != self.sha256),
+            Err(_) => true,
+        }
+    }
+}
+
+/// What happens to derived objects when the object they derive from is removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CascadePolicy {
+    /// Keep dependents but report them as stale
+    #[default]
+    Invalidate,
+    /// Remove dependents and their files as well
+    Delete,
+}
+
+/// Derivation edges between the objects of one cache
+#[derive(Debug, Default)]
+pub(crate) struct EntryGraph {
+    inner: RwLock<GraphState>,
+}
+
+#[derive(Debug, Default)]
+struct GraphState {
+    /// Source name to the names derived from it
+    dependents: HashMap<String, BTreeSet<String>>,
+    /// Objects whose sources changed since they were last written
+    invalidated: HashSet<String>,
+    policy: CascadePolicy,
+}
+
+impl GraphState {
+    /// Returns every object transitively derived from `name`
+    fn closure(&self, name: &str) -> Vec<String> {
+        let mut seen = Vec::new();
+        let mut stack = vec![name.to_string()];
+        while let Some(current) = stack.pop() {
+            for child in self.dependents.get(&current).into_iter().flatten() {
+                if !seen.contains(child) {
+                    seen.push(child.clone());
+                    stack.push(child.clone());
+                }
+            }
+        }
+        seen
+    }
+}
+
+impl EntryGraph {
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, GraphState> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, GraphState> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records that `derived` was computed from `source`
+    pub(crate) fn add(&self, derived: &str, source: &str) -> CacheResult<()> {
+        let mut state = self.write();
+        if derived == source || state.closure(derived).iter().any(|name| name == source) {
+            return Err(CacheError::InvalidConfig(format!(
+                "'{}' depending on '{}' would create a dependency cycle",
+                derived, source
+            )));
+        }
+        state
+            .dependents
+            .entry(source.to_string())
+            .or_default()
+            .insert(derived.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn dependents(&self, name: &str) -> Vec<String> {
+        self.read()
+            .dependents
+            .get(name)
+            .map(|children| children.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_policy(&self, policy: CascadePolicy) {
+        self.write().policy = policy;
+    }
+
+    pub(crate) fn policy(&self) -> CascadePolicy {
+        self.read().policy
+    }
+
+    /// Marks `name` as fresh and everything derived from it as invalidated
+    pub(crate) fn written(&self, name: &str) {
+        let mut state = self.write();
+        if state.dependents.is_empty() && state.invalidated.is_empty() {
+            return;
+        }
+        state.invalidated.remove(name);
+        let closure = state.closure(name);
+        state.invalidated.extend(closure);
+    }
+
+    pub(crate) fn is_invalidated(&self, name: &str) -> bool {
+        self.read().invalidated.contains(name)
+    }
+
+    /// Drops `name` from the graph and returns its transitive dependents
+    ///
+    /// Dependents are marked invalidated unless they are going to be
+    /// deleted.
+    pub(crate) fn removed(&self, name: &str) -> Vec<String> {
+        let mut state = self.write();
+        let closure = state.closure(name);
+        if state.policy == CascadePolicy::Invalidate {
+            state.invalidated.extend(closure.iter().cloned());
+        }
+        state.dependents.remove(name);
+        for children in state.dependents.values_mut() {
+            children.remove(name);
+        }
+        state.invalidated.remove(name);
+        closure
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut state = self.write();
+        state.dependents.clear();
+        state.invalidated.clear();
+    }
+}
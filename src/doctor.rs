@@ -0,0 +1,309 @@
+/*
+ * @filename: doctor.rs
+ * @description: Self-diagnostics for cache-lite configurations and storage
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::CacheConfig;
+use crate::utils::{check_share_reachable, existing_ancestor, resolve_cache_dir, unc_share_root};
+use std::fmt;
+use std::path::Path;
+
+/// Free space below which the doctor reports a warning (64 MiB)
+const LOW_SPACE_WARNING: u64 = 64 * 1024 * 1024;
+
+/// Outcome of a single diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoctorStatus {
+    /// Check passed
+    Ok,
+    /// Check passed but something looks suspicious
+    Warning,
+    /// Check failed, the cache will not work as configured
+    Error,
+}
+
+/// A single diagnostic check and its outcome
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    /// Short identifier of the check (e.g. `"writable"`)
+    pub name: &'static str,
+    /// Outcome of the check
+    pub status: DoctorStatus,
+    /// Human readable explanation
+    pub message: String,
+}
+
+/// Structured report produced by `Cache::doctor()`
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    /// All checks in the order they were run
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Checks whether no check reported an error
+    ///
+    /// # Returns
+    /// `bool` - True if every check passed or only warned
+    pub fn is_healthy(&self) -> bool {
+        !self.checks.iter().any(|c| c.status == DoctorStatus::Error)
+    }
+
+    /// Returns the checks that reported an error
+    ///
+    /// # Returns
+    /// `impl Iterator<Item = &DoctorCheck>` - Failed checks
+    pub fn errors(&self) -> impl Iterator<Item = &DoctorCheck> {
+        self.checks.iter().filter(|c| c.status == DoctorStatus::Error)
+    }
+
+    /// Returns the checks that reported a warning
+    ///
+    /// # Returns
+    /// `impl Iterator<Item = &DoctorCheck>` - Suspicious checks
+    pub fn warnings(&self) -> impl Iterator<Item = &DoctorCheck> {
+        self.checks.iter().filter(|c| c.status == DoctorStatus::Warning)
+    }
+
+    /// Returns a check by name
+    ///
+    /// # Parameters
+    /// - `name: &str` - Check identifier
+    ///
+    /// # Returns
+    /// `Option<&DoctorCheck>` - The check if it was run
+    pub fn check(&self, name: &str) -> Option<&DoctorCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    fn push(&mut self, name: &'static str, status: DoctorStatus, message: impl Into<String>) {
+        self.checks.push(DoctorCheck {
+            name,
+            status,
+            message: message.into(),
+        });
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let tag = match check.status {
+                DoctorStatus::Ok => "ok",
+                DoctorStatus::Warning => "warn",
+                DoctorStatus::Error => "error",
+            };
+            writeln!(f, "[{}] {}: {}", tag, check.name, check.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every check against the given configuration
+///
+/// `root` replaces the configured path for caches opened on a directory.
+pub(crate) fn run(config: &CacheConfig, root: Option<&Path>) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    match config.validate() {
+        Ok(()) => report.push("config", DoctorStatus::Ok, "configuration is valid"),
+        Err(e) => report.push("config", DoctorStatus::Error, e.message()),
+    }
+
+    let dir = match root.map(|root| Ok(root.to_path_buf())).unwrap_or_else(|| resolve_cache_dir(config)) {
+        Ok(dir) => dir,
+        Err(e) => {
+            report.push("directory", DoctorStatus::Error, e.message());
+            return report;
+        }
+    };
+    let dir_str = dir.to_string_lossy();
+    if cfg!(windows)
+        && let Some(root) = unc_share_root(&dir_str)
+    {
+        match check_share_reachable(&dir_str) {
+            Ok(()) => report.push("network_share", DoctorStatus::Ok, format!("{} is reachable", root)),
+            Err(e) => report.push("network_share", DoctorStatus::Error, e.message()),
+        }
+    }
+    check_directory(&mut report, &dir);
+
+    // Probe the directory itself, or the ancestor it would be created under
+    let Some(probe_dir) = existing_ancestor(&dir) else {
+        report.push(
+            "writable",
+            DoctorStatus::Error,
+            format!("no existing ancestor of {} could be found", dir.display()),
+        );
+        return report;
+    };
+
+    check_writable(&mut report, probe_dir);
+    check_permissions(&mut report, probe_dir);
+    check_free_space(&mut report, probe_dir, config.max_size, config.limits.min_free_bytes);
+
+    report
+}
+
+fn check_directory(report: &mut DoctorReport, dir: &Path) {
+    match std::fs::symlink_metadata(dir) {
+        Ok(meta) if meta.file_type().is_symlink() => report.push(
+            "directory",
+            DoctorStatus::Warning,
+            format!("{} is a symbolic link", dir.display()),
+        ),
+        Ok(meta) if meta.is_dir() => report.push(
+            "directory",
+            DoctorStatus::Ok,
+            format!("{} exists", dir.display()),
+        ),
+        Ok(_) => report.push(
+            "directory",
+            DoctorStatus::Error,
+            format!("{} exists but is not a directory", dir.display()),
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => report.push(
+            "directory",
+            DoctorStatus::Warning,
+            format!("{} does not exist yet, it will be created on first use", dir.display()),
+        ),
+        Err(e) => report.push(
+            "directory",
+            DoctorStatus::Error,
+            format!("cannot inspect {}: {}", dir.display(), e),
+        ),
+    }
+}
+
+fn check_writable(report: &mut DoctorReport, dir: &Path) {
+    let probe = dir.join(format!(".cache-lite-probe-{}", std::process::id()));
+    let result = std::fs::write(&probe, b"probe")
+        .and_then(|_| std::fs::read(&probe))
+        .and_then(|content| {
+            if content == b"probe" {
+                Ok(())
+            } else {
+                Err(std::io::Error::other("probe content mismatch"))
+            }
+        });
+    let _ = std::fs::remove_file(&probe);
+
+    match result {
+        Ok(()) => report.push(
+            "writable",
+            DoctorStatus::Ok,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => report.push(
+            "writable",
+            DoctorStatus::Error,
+            format!("{} is not writable: {} - check permissions", dir.display(), e),
+        ),
+    }
+}
+
+fn check_permissions(report: &mut DoctorReport, dir: &Path) {
+    let meta = match std::fs::metadata(dir) {
+        Ok(meta) => meta,
+        Err(e) => {
+            report.push(
+                "permissions",
+                DoctorStatus::Error,
+                format!("cannot read metadata of {}: {}", dir.display(), e),
+            );
+            return;
+        }
+    };
+
+    if meta.permissions().readonly() {
+        report.push(
+            "permissions",
+            DoctorStatus::Error,
+            format!("{} is read-only", dir.display()),
+        );
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = meta.permissions().mode() & 0o7777;
+        // World-writable without the sticky bit lets other users replace entries
+        if mode & 0o002 != 0 && mode & 0o1000 == 0 {
+            report.push(
+                "permissions",
+                DoctorStatus::Warning,
+                format!("{} is world-writable ({:o}) without the sticky bit", dir.display(), mode),
+            );
+            return;
+        }
+        report.push(
+            "permissions",
+            DoctorStatus::Ok,
+            format!("{} has mode {:o}", dir.display(), mode),
+        );
+    }
+
+    #[cfg(not(unix))]
+    report.push(
+        "permissions",
+        DoctorStatus::Ok,
+        format!("{} is not read-only", dir.display()),
+    );
+}
+
+fn check_free_space(report: &mut DoctorReport, dir: &Path, max_size: u64, min_free_bytes: Option<u64>) {
+    match fs4::available_space(dir) {
+        Ok(0) => report.push("free_space", DoctorStatus::Error, "no free space left on the volume"),
+        Ok(available) if min_free_bytes.is_some_and(|min_free| available < min_free) => report.push(
+            "free_space",
+            DoctorStatus::Error,
+            format!(
+                "{} bytes available, less than limits.min_free_bytes ({} bytes), writes will fail or evict",
+                available,
+                min_free_bytes.unwrap_or_default()
+            ),
+        ),
+        Ok(available) if max_size > 0 && available < max_size => report.push(
+            "free_space",
+            DoctorStatus::Warning,
+            format!("{} bytes available, less than max_size ({} bytes)", available, max_size),
+        ),
+        Ok(available) if available < LOW_SPACE_WARNING => report.push(
+            "free_space",
+            DoctorStatus::Warning,
+            format!("only {} bytes available", available),
+        ),
+        Ok(available) => report.push(
+            "free_space",
+            DoctorStatus::Ok,
+            format!("{} bytes available", available),
+        ),
+        Err(e) => report.push(
+            "free_space",
+            DoctorStatus::Warning,
+            format!("cannot determine free space: {}", e),
+        ),
+    }
+}
@@ -24,11 +24,17 @@
  * SOFTWARE.
  */
 
+use crate::writeback::QueueFull;
 use std::fmt;
 use std::io;
+use std::path::{Path, PathBuf};
 
 /// Cache library error types
+///
+/// New variants may be added in minor releases, keep a wildcard arm when
+/// matching.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CacheError {
     /// I/O operation failed
     Io(io::Error),
@@ -60,6 +66,25 @@ pub enum CacheError {
     Corrupted(String),
     /// Generic error with message
     Generic(String),
+    /// No cache object is registered under `name`
+    EntryNotFound {
+        /// Name that was looked up
+        name: String,
+    },
+    /// A write would leave less free space on the cache volume than `limits.min_free_bytes`
+    DiskFull {
+        /// Free bytes on the volume
+        available: u64,
+        /// Free bytes needed for the write and the reserve
+        required: u64,
+    },
+    /// A background task running a cache operation failed
+    Task {
+        /// Operation the task was running, e.g. `"read"`
+        operation: &'static str,
+        /// Why the task failed, e.g. a panic or runtime shutdown
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
 
 impl fmt::Display for CacheError {
@@ -80,11 +105,54 @@ impl fmt::Display for CacheError {
             CacheError::FileCountLimitExceeded(msg) => write!(f, "Cache file count limit exceeded: {}", msg),
             CacheError::Corrupted(msg) => write!(f, "Cache corrupted: {}", msg),
             CacheError::Generic(msg) => write!(f, "Error: {}", msg),
+            CacheError::EntryNotFound { name } => write!(f, "Cache object '{}' not found", name),
+            CacheError::DiskFull { available, required } => write!(
+                f,
+                "Not enough disk space: {} bytes available, {} bytes required",
+                available, required
+            ),
+            CacheError::Task { operation, source } => write!(f, "Cache {} task failed: {}", operation, source),
         }
     }
 }
 
-impl std::error::Error for CacheError {}
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Io(err) => Some(err),
+            CacheError::Task { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Operation, path and entry attached to an I/O error
+///
+/// Stored as the payload of the `io::Error` in `CacheError::Io`, so the
+/// error kind is preserved and existing matches keep working.
+#[derive(Debug)]
+struct IoContext {
+    operation: &'static str,
+    path: PathBuf,
+    entry: Option<String>,
+    source: io::Error,
+}
+
+impl fmt::Display for IoContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.operation, self.path.display())?;
+        if let Some(entry) = &self.entry {
+            write!(f, " (entry '{}')", entry)?;
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl std::error::Error for IoContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
 
 impl From<io::Error> for CacheError {
     fn from(err: io::Error) -> Self {
@@ -92,6 +160,95 @@ impl From<io::Error> for CacheError {
     }
 }
 
+/// Converts cache errors for use in `io::Result` functions
+///
+/// I/O errors are unwrapped as they are; other variants map to the
+/// closest `io::ErrorKind` and keep the `CacheError` as their payload.
+impl From<CacheError> for io::Error {
+    fn from(err: CacheError) -> Self {
+        let kind = match err {
+            CacheError::Io(err) => return err,
+            CacheError::NotFound(_) | CacheError::EntryNotFound { .. } => io::ErrorKind::NotFound,
+            CacheError::PermissionDenied(_) => io::ErrorKind::PermissionDenied,
+            CacheError::AlreadyExists(_) => io::ErrorKind::AlreadyExists,
+            CacheError::InvalidName(_)
+            | CacheError::ConfigParse(_)
+            | CacheError::InvalidConfig(_)
+            | CacheError::InvalidPath(_)
+            | CacheError::SymlinkDetected(_) => io::ErrorKind::InvalidInput,
+            CacheError::Serialization(_) | CacheError::Corrupted(_) => io::ErrorKind::InvalidData,
+            CacheError::SizeLimitExceeded(_) | CacheError::FileCountLimitExceeded(_) => io::ErrorKind::QuotaExceeded,
+            CacheError::DiskFull { .. } => io::ErrorKind::StorageFull,
+            CacheError::Expired(_) | CacheError::Generic(_) | CacheError::Task { .. } => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+/// Error codes (`cache_lite::<kind>`) and help text for CLI reports
+#[cfg(feature = "diagnostics")]
+impl miette::Diagnostic for CacheError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!("cache_lite::{}", self.kind())))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let help = match self {
+            CacheError::Io(err) => {
+                let dir = self
+                    .path()
+                    .and_then(Path::parent)
+                    .map_or_else(|| "the cache directory".to_string(), |dir| dir.display().to_string());
+                match err.kind() {
+                    io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+                        format!("cache directory not writable — check permissions on {}", dir)
+                    }
+                    io::ErrorKind::NotFound => format!(
+                        "the file was removed outside the cache — check that {} still exists",
+                        dir
+                    ),
+                    io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => {
+                        format!("no space left — free disk space on the volume holding {}", dir)
+                    }
+                    io::ErrorKind::WouldBlock => {
+                        "the write-back queue is full — retry later or raise io.write_back.max_queue_bytes".to_string()
+                    }
+                    _ if self.is_retryable() => {
+                        "the failure is transient — enable retries with the `retry` config".to_string()
+                    }
+                    _ => return None,
+                }
+            }
+            CacheError::InvalidName(_) => {
+                "object names must not contain path separators, `..` or reserved characters".to_string()
+            }
+            CacheError::ConfigParse(_) => "check the JSON syntax of the cache configuration".to_string(),
+            CacheError::InvalidConfig(_) => "check the cache configuration".to_string(),
+            CacheError::InvalidPath(_) => {
+                "check the `path` config; environment variables must be set unless path.undefined_vars is \"passthrough\""
+                    .to_string()
+            }
+            CacheError::PermissionDenied(_) => "check the permissions of the cache directory".to_string(),
+            CacheError::SymlinkDetected(_) => "remove the symbolic link from the cache directory".to_string(),
+            CacheError::SizeLimitExceeded(_) => "raise max_size, free disk space or remove entries".to_string(),
+            CacheError::FileCountLimitExceeded(_) => "raise max_files or remove entries".to_string(),
+            CacheError::Corrupted(_) => "delete the entry so it is rebuilt".to_string(),
+            CacheError::DiskFull { .. } => {
+                "free disk space, lower limits.min_free_bytes or set limits.on_low_space to \"evict\"".to_string()
+            }
+            CacheError::NotFound(_) | CacheError::EntryNotFound { .. } => {
+                "create the object with Cache::create() or register existing files with Cache::index_all()".to_string()
+            }
+            CacheError::Task { .. } => "the Tokio runtime shut down or the task panicked".to_string(),
+            CacheError::AlreadyExists(_)
+            | CacheError::Expired(_)
+            | CacheError::Serialization(_)
+            | CacheError::Generic(_) => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
 impl From<serde_json::Error> for CacheError {
     fn from(err: serde_json::Error) -> Self {
         CacheError::ConfigParse(err.to_string())
@@ -117,6 +274,9 @@ impl CacheError {
             CacheError::FileCountLimitExceeded(_) => "file_count_limit_exceeded",
             CacheError::Corrupted(_) => "corrupted",
             CacheError::Generic(_) => "generic",
+            CacheError::EntryNotFound { .. } => "not_found",
+            CacheError::DiskFull { .. } => "disk_full",
+            CacheError::Task { .. } => "task",
         }
     }
     
@@ -138,6 +298,11 @@ impl CacheError {
             CacheError::FileCountLimitExceeded(msg) => msg.clone(),
             CacheError::Corrupted(msg) => msg.clone(),
             CacheError::Generic(msg) => msg.clone(),
+            CacheError::EntryNotFound { name } => format!("Cache object '{}' not found", name),
+            CacheError::DiskFull { available, required } => {
+                format!("{} bytes available, {} bytes required", available, required)
+            }
+            CacheError::Task { source, .. } => source.to_string(),
         }
     }
     
@@ -153,11 +318,93 @@ impl CacheError {
     
     /// Checks if the error indicates something wasn't found
     pub fn is_not_found(&self) -> bool {
-        matches!(self, CacheError::NotFound(_))
+        matches!(self, CacheError::NotFound(_) | CacheError::EntryNotFound { .. })
     }
     
+    /// Attaches the failed operation, path and entry name to an I/O error
+    ///
+    /// Other errors, and I/O errors already carrying context, are returned
+    /// unchanged.
+    pub(crate) fn with_context(self, operation: &'static str, path: &Path, entry: Option<&str>) -> Self {
+        match self {
+            CacheError::Io(err) if io_context(&err).is_none() => CacheError::Io(io::Error::new(
+                err.kind(),
+                IoContext {
+                    operation,
+                    path: path.to_path_buf(),
+                    entry: entry.map(str::to_string),
+                    source: err,
+                },
+            )),
+            other => other,
+        }
+    }
+
+    /// Returns the operation that failed, for I/O errors with context
+    ///
+    /// # Returns
+    /// `Option<&str>` - Operation such as `"read"`, `"write"` or `"delete"`
+    pub fn operation(&self) -> Option<&str> {
+        self.io_context().map(|context| context.operation)
+    }
+
+    /// Returns the file the failed I/O operation touched
+    ///
+    /// # Returns
+    /// `Option<&Path>` - Path of the cache file, if known
+    pub fn path(&self) -> Option<&Path> {
+        self.io_context().map(|context| context.path.as_path())
+    }
+
+    /// Returns the name of the cache object the failed I/O operation targeted
+    ///
+    /// # Returns
+    /// `Option<&str>` - Cache object name, if known
+    pub fn entry(&self) -> Option<&str> {
+        self.io_context().and_then(|context| context.entry.as_deref())
+    }
+
+    fn io_context(&self) -> Option<&IoContext> {
+        match self {
+            CacheError::Io(err) => io_context(err),
+            _ => None,
+        }
+    }
+
+    /// Checks if the error is transient and the operation worth retrying
+    ///
+    /// True for I/O errors of kind `Interrupted`, `WouldBlock` or
+    /// `TimedOut`, and on Windows for sharing and lock violations (e.g.
+    /// while an antivirus scans the file). A full write-back queue with
+    /// `on_full: "fail"` is not retried.
+    ///
+    /// # Returns
+    /// `bool` - True if retrying may succeed
+    pub fn is_retryable(&self) -> bool {
+        let CacheError::Io(err) = self else {
+            return false;
+        };
+        let source = io_context(err).map_or(err, |context| &context.source);
+        if source.get_ref().is_some_and(|inner| inner.is::<QueueFull>()) {
+            return false;
+        }
+        if matches!(
+            err.kind(),
+            io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ) {
+            return true;
+        }
+        let os_error = source.raw_os_error();
+        // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        cfg!(windows) && matches!(os_error, Some(32 | 33))
+    }
+
     /// Checks if the error indicates permission was denied
     pub fn is_permission_denied(&self) -> bool {
         matches!(self, CacheError::PermissionDenied(_))
     }
-}
\ No newline at end of file
+}
+
+fn io_context(err: &io::Error) -> Option<&IoContext> {
+    err.get_ref()?.downcast_ref::<IoContext>()
+}
@@ -0,0 +1,208 @@
+/*
+ * @filename: event.rs
+ * @description: Typed cache events and observers for cache-lite library
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::CacheError;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Event emitted by a cache or one of its objects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheEvent {
+    /// A cache object was created
+    Created { name: String, id: u32 },
+    /// A cache object was read
+    Read { name: String, id: u32, size: u64 },
+    /// A cache object was written
+    Written { name: String, id: u32, size: u64 },
+    /// A cache object and its file were removed
+    Removed { name: String, id: u32 },
+    /// The file of a cache object was modified by another process
+    Changed { name: String, id: u32 },
+    /// An operation on a cache object failed
+    Error {
+        name: String,
+        id: u32,
+        /// Operation that failed (e.g. `"write"`)
+        operation: &'static str,
+        /// Error kind as returned by `CacheError::kind()`
+        kind: &'static str,
+        /// Error message
+        message: String,
+    },
+}
+
+impl CacheEvent {
+    /// Returns the name of the cache object the event refers to
+    ///
+    /// # Returns
+    /// `&str` - Cache object identifier
+    pub fn name(&self) -> &str {
+        match self {
+            CacheEvent::Created { name, .. }
+            | CacheEvent::Read { name, .. }
+            | CacheEvent::Written { name, .. }
+            | CacheEvent::Removed { name, .. }
+            | CacheEvent::Changed { name, .. }
+            | CacheEvent::Error { name, .. } => name,
+        }
+    }
+
+    /// Returns the ID of the cache object the event refers to
+    ///
+    /// # Returns
+    /// `u32` - Cache object ID, or 0 when creating the object failed
+    pub fn id(&self) -> u32 {
+        match self {
+            CacheEvent::Created { id, .. }
+            | CacheEvent::Read { id, .. }
+            | CacheEvent::Written { id, .. }
+            | CacheEvent::Removed { id, .. }
+            | CacheEvent::Changed { id, .. }
+            | CacheEvent::Error { id, .. } => *id,
+        }
+    }
+
+    /// Returns the number of bytes involved, if any
+    ///
+    /// # Returns
+    /// `Option<u64>` - Size for read and write events
+    pub fn size(&self) -> Option<u64> {
+        match self {
+            CacheEvent::Read { size, .. } | CacheEvent::Written { size, .. } => Some(*size),
+            _ => None,
+        }
+    }
+
+    /// Creates an error event from a `CacheError`
+    pub(crate) fn error(name: &str, id: u32, operation: &'static str, err: &CacheError) -> Self {
+        CacheEvent::Error {
+            name: name.to_string(),
+            id,
+            operation,
+            kind: err.kind(),
+            message: err.message(),
+        }
+    }
+}
+
+/// Receives events from a cache
+///
+/// Observers are called synchronously on the thread performing the
+/// operation, so implementations should return quickly.
+pub trait CacheObserver: Send + Sync {
+    /// Called for every event emitted by the cache
+    ///
+    /// # Parameters
+    /// - `event: &CacheEvent` - The emitted event
+    fn on_event(&self, event: &CacheEvent);
+}
+
+/// Sending half of a channel subscribed to a cache
+enum Subscriber {
+    Sync(Sender<CacheEvent>),
+    #[cfg(feature = "async")]
+    Async(tokio::sync::mpsc::UnboundedSender<CacheEvent>),
+}
+
+impl Subscriber {
+    /// Sends an event, returning false once the receiver is gone
+    fn send(&self, event: CacheEvent) -> bool {
+        match self {
+            Subscriber::Sync(sender) => sender.send(event).is_ok(),
+            #[cfg(feature = "async")]
+            Subscriber::Async(sender) => sender.send(event).is_ok(),
+        }
+    }
+}
+
+/// Registered observers shared between a cache and its objects
+#[derive(Default)]
+pub(crate) struct EventBus {
+    observers: RwLock<Vec<Arc<dyn CacheObserver>>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub(crate) fn add_observer(&self, observer: Arc<dyn CacheObserver>) {
+        self.observers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(observer);
+    }
+
+    pub(crate) fn subscribe(&self) -> Receiver<CacheEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.add_subscriber(Subscriber::Sync(sender));
+        receiver
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn subscribe_async(&self) -> tokio::sync::mpsc::UnboundedReceiver<CacheEvent> {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.add_subscriber(Subscriber::Async(sender));
+        receiver
+    }
+
+    fn add_subscriber(&self, subscriber: Subscriber) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(subscriber);
+    }
+
+    /// Dispatches an event, building it only if someone is listening
+    ///
+    /// Observers are called on a snapshot taken without holding the lock,
+    /// so they may register observers or trigger further events.
+    pub(crate) fn emit_with(&self, event: impl FnOnce() -> CacheEvent) {
+        let observers = self.observers.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let subscribed = !self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).is_empty();
+        if observers.is_empty() && !subscribed {
+            return;
+        }
+        let event = event();
+        for observer in &observers {
+            observer.on_event(&event);
+        }
+        // Sending never blocks; drop subscribers whose receiver is gone
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|subscriber| subscriber.send(event.clone()));
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let observers = self.observers.read().map(|o| o.len()).unwrap_or(0);
+        let subscribers = self.subscribers.lock().map(|s| s.len()).unwrap_or(0);
+        f.debug_struct("EventBus")
+            .field("observers", &observers)
+            .field("subscribers", &subscribers)
+            .finish()
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * @filename: file.rs
+ * @description: Seekable file handle keeping cache bookkeeping consistent
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::object::CacheObject;
+use crate::{CacheError, CacheResult};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// File handle returned by `CacheObject::open_file()`
+///
+/// Reads, writes and seeks go straight to the cache file. Unlike the raw
+/// `File` from `get_file()`, writes are reported to the cache: `sync()`,
+/// `close()` or dropping the handle after a write records the new size,
+/// refreshes the metadata checksum, marks dependent objects stale and emits
+/// the `Written` event like `write_bytes()` does.
+///
+/// ```rust
+/// use std::io::{Read, Seek, SeekFrom, Write};
+///
+/// let dir = std::env::temp_dir().join("cache-lite-doc-file");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let mut cache = cache_lite::Cache::open(&dir).unwrap();
+/// let obj = cache.create("table", None).unwrap();
+/// let mut file = obj.open_file().unwrap();
+/// file.write_all(b"hello world").unwrap();
+/// file.seek(SeekFrom::Start(6)).unwrap();
+/// let mut word = String::new();
+/// file.read_to_string(&mut word).unwrap();
+/// assert_eq!(word, "world");
+/// assert_eq!(file.close().unwrap(), 11);
+/// # obj.delete().unwrap();
+/// ```
+pub struct CacheFile<'a> {
+    object: &'a CacheObject,
+    file: File,
+    /// Set by writes not yet reported to the cache
+    dirty: bool,
+}
+
+impl<'a> CacheFile<'a> {
+    pub(crate) fn new(object: &'a CacheObject, file: File) -> Self {
+        CacheFile { object, file, dirty: false }
+    }
+
+    /// Returns the current size of the file
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Size in bytes
+    pub fn size(&self) -> CacheResult<u64> {
+        let metadata = self.file.metadata().map_err(CacheError::Io);
+        self.object.report("size", metadata).map(|metadata| metadata.len())
+    }
+
+    /// Checks whether the file has writes not yet reported to the cache
+    ///
+    /// # Returns
+    /// `bool` - True after a write until the next `sync()`
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Truncates or extends the file to `len` bytes
+    ///
+    /// # Parameters
+    /// - `len: u64` - New size in bytes
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn set_len(&mut self, len: u64) -> CacheResult<()> {
+        self.object.check_sealed()?;
+        self.object.check_entry_size(len)?;
+        self.object.report("write", self.file.set_len(len).map_err(CacheError::Io))?;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Flushes the file and reports pending writes to the cache
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn sync(&mut self) -> CacheResult<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.object.report("write", self.file.sync_data().map_err(CacheError::Io))?;
+        let size = self.size()?;
+        self.dirty = false;
+        self.object.written(size)
+    }
+
+    /// Reports pending writes and closes the file
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Final size of the file in bytes
+    pub fn close(mut self) -> CacheResult<u64> {
+        self.sync()?;
+        self.size()
+    }
+}
+
+impl Read for CacheFile<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.file.read(buf)?;
+        self.object.mark_read();
+        Ok(read)
+    }
+}
+
+impl Write for CacheFile<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.object.check_sealed()?;
+        let position = self.file.stream_position()?;
+        let end = position
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "write would overflow the file size"))?;
+        self.object.check_entry_size(end)?;
+        let written = self.file.write(buf)?;
+        self.dirty |= written > 0;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for CacheFile<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl Drop for CacheFile<'_> {
+    fn drop(&mut self) {
+        // Errors surface through `CacheEvent::Error`; call `close()` to see them
+        let _ = self.sync();
+    }
+}
@@ -0,0 +1,97 @@
+/*
+ * @filename: global.rs
+ * @description: Lazily initialized process-wide cache
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cache::Cache;
+use crate::config::CacheConfig;
+use crate::{CacheError, CacheResult};
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable holding the global cache configuration, either
+/// inline JSON or the path of a JSON file
+pub const CONFIG_ENV: &str = "CACHE_LITE_CONFIG";
+
+static GLOBAL: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+/// Returns the process-wide cache, creating it on first use
+///
+/// The configuration is read from `CACHE_LITE_CONFIG` unless `init_global()`
+/// was called first. An unset variable or an unusable configuration falls
+/// back to `CacheConfig::default()`.
+///
+/// ```rust,no_run
+/// let obj = cache_lite::global().lock().unwrap().create("answer", None).unwrap();
+/// obj.write_string("42").unwrap();
+/// ```
+///
+/// # Returns
+/// `&'static Mutex<Cache>` - The global cache
+pub fn global() -> &'static Mutex<Cache> {
+    GLOBAL.get_or_init(|| {
+        let config = config_from_env();
+        let cache = Cache::new(config).unwrap_or_else(|e| {
+            log_warn!("global cache config rejected, using defaults: {}", e);
+            Cache::new(CacheConfig::default()).expect("default config is always accepted")
+        });
+        Mutex::new(cache)
+    })
+}
+
+/// Initializes the process-wide cache with an explicit configuration
+///
+/// # Parameters
+/// - `config: CacheConfig` - Configuration of the global cache
+///
+/// # Returns
+/// `CacheResult<()>` - Success, or `AlreadyExists` if `global()` was
+/// already initialized
+pub fn init_global(config: CacheConfig) -> CacheResult<()> {
+    let cache = Cache::new(config)?;
+    GLOBAL
+        .set(Mutex::new(cache))
+        .map_err(|_| CacheError::AlreadyExists("Global cache is already initialized".to_string()))
+}
+
+/// Reads the configuration named by `CACHE_LITE_CONFIG`
+fn config_from_env() -> CacheConfig {
+    let Ok(value) = std::env::var(CONFIG_ENV) else {
+        return CacheConfig::default();
+    };
+    let json = if value.trim_start().starts_with('{') {
+        value
+    } else {
+        match std::fs::read_to_string(&value) {
+            Ok(json) => json,
+            Err(e) => {
+                log_warn!("cannot read {} file {}: {}", CONFIG_ENV, value, e);
+                return CacheConfig::default();
+            }
+        }
+    };
+    CacheConfig::new(&json).unwrap_or_else(|e| {
+        log_warn!("invalid {} configuration, using defaults: {}", CONFIG_ENV, e);
+        CacheConfig::default()
+    })
+}
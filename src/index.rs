@@ -0,0 +1,189 @@
+/*
+ * @filename: index.rs
+ * @description: Lock-protected registry shared by processes using one cache directory
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::lock::FileLock;
+use crate::{CacheError, CacheResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// File name of the index shared by processes using one cache directory
+pub const INDEX_FILE: &str = "index.json";
+
+/// File locked while the shared index is updated
+pub(crate) const INDEX_LOCK_FILE: &str = "index.lock";
+
+/// Age after which a held index lock is broken
+///
+/// Updates hold the lock for milliseconds, so an older lock belongs to
+/// a process that hung or died without releasing it.
+const INDEX_LOCK_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Current shared index format version
+const INDEX_VERSION: u32 = 1;
+
+/// Content of `index.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IndexState {
+    pub(crate) version: u32,
+    /// Incremented by every update, so readers can skip unchanged indexes
+    pub(crate) revision: u64,
+    /// Next object ID, never handed out twice
+    pub(crate) next_id: u32,
+    pub(crate) entries: BTreeMap<String, IndexEntry>,
+}
+
+impl Default for IndexState {
+    fn default() -> Self {
+        IndexState {
+            version: INDEX_VERSION,
+            revision: 0,
+            next_id: 1,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+/// One object of the shared index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct IndexEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) id: u32,
+}
+
+/// Registry kept in `index.json` next to the cached files
+///
+/// Updates take an exclusive lock on `index.lock`, read the current
+/// index, change it and replace it with a rename, so readers never see
+/// a partially written index and concurrent processes neither lose each
+/// other's entries nor hand out the same ID twice.
+#[derive(Debug)]
+pub(crate) struct SharedIndex {
+    dir: PathBuf,
+    /// Revision last applied to the local registry
+    seen: u64,
+    /// Entries of the last index this process read or wrote
+    listed: Mutex<BTreeMap<String, IndexEntry>>,
+}
+
+impl SharedIndex {
+    /// Uses the index of the cache directory `dir`, creating it if needed
+    pub(crate) fn open(dir: &Path) -> CacheResult<Self> {
+        std::fs::create_dir_all(dir)?;
+        let index = SharedIndex {
+            dir: dir.to_path_buf(),
+            seen: 0,
+            listed: Mutex::default(),
+        };
+        if !index.path().is_file() {
+            index.update(|_| Ok(()))?;
+        }
+        Ok(index)
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Blocks until this process holds the index lock
+    ///
+    /// Updates are short, so the lock goes without a heartbeat thread and
+    /// only `INDEX_LOCK_STALE_AFTER` is used to break locks of others.
+    fn lock(&self) -> CacheResult<FileLock> {
+        FileLock::acquire(&self.dir.join(INDEX_LOCK_FILE), Some(INDEX_LOCK_STALE_AFTER))
+    }
+
+    /// Reads the current index, empty if none was written yet
+    pub(crate) fn load(&self) -> CacheResult<IndexState> {
+        let json = match std::fs::read_to_string(self.path()) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(IndexState::default()),
+            Err(e) => return Err(CacheError::Io(e)),
+        };
+        let state: IndexState = serde_json::from_str(&json)
+            .map_err(|e| CacheError::Corrupted(format!("invalid shared index: {}", e)))?;
+        if state.version > INDEX_VERSION {
+            return Err(CacheError::Corrupted(format!(
+                "shared index version {} is newer than the supported version {}",
+                state.version, INDEX_VERSION
+            )));
+        }
+        Ok(state)
+    }
+
+    /// Changes the index under the lock
+    ///
+    /// Nothing is written if `change` fails. A lock left by a process
+    /// that died or hung is broken after `INDEX_LOCK_STALE_AFTER`.
+    pub(crate) fn update<T>(&self, change: impl FnOnce(&mut IndexState) -> CacheResult<T>) -> CacheResult<T> {
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        let result = change(&mut state)?;
+        state.version = INDEX_VERSION;
+        state.revision += 1;
+
+        let json = serde_json::to_vec_pretty(&state).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        let temp = self.dir.join(format!("{}.{}.tmp", INDEX_FILE, std::process::id()));
+        let mut file = File::create(&temp)?;
+        file.write_all(&json)?;
+        file.sync_data()?;
+        drop(file);
+        std::fs::rename(&temp, self.path())?;
+        self.set_listed(&state.entries);
+        Ok(result)
+    }
+
+    /// Checks whether the last index seen listed `name` at `path`
+    pub(crate) fn was_listed(&self, name: &str, path: &Path) -> bool {
+        let listed = self.listed.lock().unwrap_or_else(|e| e.into_inner());
+        listed.get(name).is_some_and(|entry| entry.path == path)
+    }
+
+    /// Records the entries of the index last seen
+    pub(crate) fn set_listed(&self, entries: &BTreeMap<String, IndexEntry>) {
+        *self.listed.lock().unwrap_or_else(|e| e.into_inner()) = entries.clone();
+    }
+
+    /// Returns the index if it changed since the last call
+    pub(crate) fn changes(&mut self) -> CacheResult<Option<IndexState>> {
+        let state = self.load()?;
+        if state.revision == self.seen {
+            return Ok(None);
+        }
+        self.seen = state.revision;
+        Ok(Some(state))
+    }
+
+    /// Checks whether `file_name` is one of the files of the index
+    pub(crate) fn is_index_file(file_name: &std::ffi::OsStr) -> bool {
+        file_name == INDEX_FILE || file_name == INDEX_LOCK_FILE
+    }
+}
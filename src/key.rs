@@ -0,0 +1,289 @@
+/*
+ * @filename: key.rs
+ * @description: Encodings turning arbitrary object names into safe file names
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::config::{CacheConfig, KeyEncoding, NamePolicy};
+use crate::manifest::sha256_hex;
+use crate::utils::{is_reserved_name, validate_name};
+use crate::{CacheError, CacheResult};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+
+/// Longest file name component accepted by common filesystems
+pub(crate) const MAX_KEY_LEN: usize = 255;
+
+/// Longest path accepted by the platform, without the terminating NUL
+pub(crate) const MAX_PATH_LEN: usize = if cfg!(windows) {
+    259
+} else if cfg!(target_os = "macos") {
+    1023
+} else {
+    4095
+};
+
+/// Length of the `~` and hash `truncate_key()` appends
+const TRUNCATED_SUFFIX_LEN: usize = 17;
+
+/// Characters `NamePolicy::Sanitize` encodes besides control characters
+const SANITIZED: &[char] = &['%', '/', '\\', '<', '>', ':', '"', '|', '?', '*'];
+
+/// Maps object names to file paths, replacing `format.filename`
+///
+/// Set on a cache with `Cache::set_key_mapper()`, e.g. to lay out an
+/// HTTP cache as `host/route/hash.bin`. Paths are relative to the cache
+/// directory; missing subdirectories are created. Any non-empty name is
+/// passed on, the mapper decides which characters reach the file system.
+pub trait KeyMapper: Send + Sync {
+    /// Returns the path of the file holding `name`
+    ///
+    /// # Parameters
+    /// - `name: &str` - Object name, after normalization
+    /// - `id: u32` - ID of the new object, 0 when probing an opened directory
+    ///
+    /// # Returns
+    /// `PathBuf` - Path relative to the cache directory
+    fn map(&self, name: &str, id: u32) -> PathBuf;
+}
+
+/// Checks a path returned by a `KeyMapper`
+///
+/// It must be relative, stay inside the cache directory and have file
+/// name components of at most `MAX_KEY_LEN` bytes.
+pub(crate) fn validate_mapped_path(name: &str, path: &Path) -> CacheResult<()> {
+    let mut file_names = 0;
+    for component in path.components() {
+        match component {
+            Component::Normal(part) if part.len() > MAX_KEY_LEN => {
+                return Err(CacheError::InvalidPath(format!(
+                    "Path {} mapped from '{}' has a component longer than {} bytes",
+                    path.display(),
+                    name,
+                    MAX_KEY_LEN
+                )));
+            }
+            Component::Normal(_) => file_names += 1,
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(CacheError::InvalidPath(format!(
+                    "Path {} mapped from '{}' must be relative and without '..'",
+                    path.display(),
+                    name
+                )));
+            }
+        }
+    }
+    if file_names == 0 {
+        return Err(CacheError::InvalidPath(format!("Cache object '{}' was mapped to an empty path", name)));
+    }
+    Ok(())
+}
+
+/// How `{name}` is derived from object names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NameMapping {
+    /// Valid names only, used as they are
+    Verbatim,
+    /// `NamePolicy::Sanitize`
+    Sanitize,
+    /// `format.key_encoding`
+    Encode(KeyEncoding),
+}
+
+impl NameMapping {
+    /// Picks the mapping of a config, `format.key_encoding` first
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        match (config.format.key_encoding, config.name_policy) {
+            (Some(encoding), _) => NameMapping::Encode(encoding),
+            (None, NamePolicy::Sanitize) => NameMapping::Sanitize,
+            (None, NamePolicy::Strict) => NameMapping::Verbatim,
+        }
+    }
+
+    /// Checks a name for `create()`
+    pub(crate) fn validate(self, name: &str) -> CacheResult<()> {
+        match self {
+            NameMapping::Verbatim => validate_name(name),
+            NameMapping::Sanitize => {
+                if name.is_empty() {
+                    return Err(CacheError::InvalidName("Cache name cannot be empty".to_string()));
+                }
+                if sanitize_name(name).len() > MAX_KEY_LEN {
+                    return Err(CacheError::InvalidName(format!(
+                        "Sanitized cache name is longer than {} bytes",
+                        MAX_KEY_LEN
+                    )));
+                }
+                Ok(())
+            }
+            NameMapping::Encode(encoding) => validate_key(encoding, name),
+        }
+    }
+
+    /// Returns what `{name}` expands to for `name`
+    pub(crate) fn file_key(self, name: &str) -> Cow<'_, str> {
+        match self {
+            NameMapping::Verbatim => Cow::Borrowed(name),
+            NameMapping::Sanitize => sanitize_name(name),
+            NameMapping::Encode(encoding) => Cow::Owned(encode_key(encoding, name)),
+        }
+    }
+
+    /// Recovers a name from the `{name}` part of a file name
+    ///
+    /// `None` for invalid names and hashes.
+    pub(crate) fn decode(self, key: String) -> Option<String> {
+        match self {
+            NameMapping::Verbatim => Some(key).filter(|name| validate_name(name).is_ok()),
+            NameMapping::Sanitize => decode_key(KeyEncoding::Percent, &key),
+            NameMapping::Encode(encoding) => decode_key(encoding, &key),
+        }
+    }
+}
+
+/// Shortens the `{name}` part of `name` to `max_len` bytes for `LongPathPolicy::Truncate`
+///
+/// Keeps the start of `key` and appends `~` and the first 16 hex digits
+/// of the SHA-256 of `name`. `None` if `max_len` leaves no room for them.
+pub(crate) fn truncate_key(key: &str, name: &str, max_len: usize) -> Option<String> {
+    let mut prefix_len = max_len.checked_sub(TRUNCATED_SUFFIX_LEN)?;
+    while !key.is_char_boundary(prefix_len) {
+        prefix_len -= 1;
+    }
+    Some(format!("{}~{}", &key[..prefix_len], &sha256_hex(name.as_bytes())[..16]))
+}
+
+/// Checks whether a `{name}` part may have been shortened by `truncate_key()`
+pub(crate) fn is_truncated_key(key: &str) -> bool {
+    key.len() >= TRUNCATED_SUFFIX_LEN
+        && key.as_bytes()[key.len() - TRUNCATED_SUFFIX_LEN] == b'~'
+        && key[key.len() - 16..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Percent-encodes the characters of `name` that are unsafe in file names
+///
+/// Dots are encoded in `.` and names containing `..`, the first letter
+/// of Windows device names, everything else is kept readable.
+fn sanitize_name(name: &str) -> Cow<'_, str> {
+    let encode_dots = name == "." || name.contains("..");
+    let unsafe_char = |c: char| c.is_control() || SANITIZED.contains(&c) || (encode_dots && c == '.');
+    if !name.contains(unsafe_char) && !is_reserved_name(name) {
+        return Cow::Borrowed(name);
+    }
+    let mut sanitized = String::with_capacity(name.len() + 8);
+    for (i, c) in name.char_indices() {
+        if unsafe_char(c) || (i == 0 && is_reserved_name(name)) {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                let _ = write!(sanitized, "%{:02X}", byte);
+            }
+        } else {
+            sanitized.push(c);
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+/// Checks a name for a cache whose file names are encoded
+///
+/// Any non-empty UTF-8 name is accepted, as long as its percent-encoded
+/// form fits in a file name.
+pub(crate) fn validate_key(encoding: KeyEncoding, name: &str) -> CacheResult<()> {
+    if name.is_empty() {
+        return Err(CacheError::InvalidName("Cache name cannot be empty".to_string()));
+    }
+    if encoding == KeyEncoding::Percent && encode_key(encoding, name).len() > MAX_KEY_LEN {
+        return Err(CacheError::InvalidName(format!(
+            "Percent-encoded cache name is longer than {} bytes, use the hash key encoding",
+            MAX_KEY_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Returns what `{name}` expands to for `name`
+pub(crate) fn encode_key(encoding: KeyEncoding, name: &str) -> String {
+    match encoding {
+        KeyEncoding::Percent => {
+            let encoded = percent_encode(name);
+            if is_reserved_name(&encoded) {
+                // `CON` and friends are devices on Windows, encode a letter
+                format!("%{:02X}{}", encoded.as_bytes()[0], &encoded[1..])
+            } else {
+                encoded
+            }
+        }
+        KeyEncoding::Hash => sha256_hex(name.as_bytes()),
+    }
+}
+
+/// Encodes every byte but ASCII letters, digits, `-` and `_` as `%XX`
+fn percent_encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_') {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` sequences, keeping malformed ones as written
+///
+/// Returns `None` if the decoded bytes are not UTF-8.
+fn percent_decode(key: &str) -> Option<String> {
+    let bytes = key.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| key.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Recovers a name from its encoded form, `None` for hashes
+pub(crate) fn decode_key(encoding: KeyEncoding, key: &str) -> Option<String> {
+    match encoding {
+        KeyEncoding::Percent => percent_decode(key),
+        KeyEncoding::Hash => None,
+    }
+}
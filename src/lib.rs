@@ -85,17 +85,90 @@
 //! various failure scenarios including I/O errors, invalid configurations,
 //! permission issues, and more.
 
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "wasm")]
+mod browser;
 mod config;
 mod object;
 mod cache;
+mod codec;
+mod dependency;
 mod error;
+mod doctor;
+mod manager;
+mod manifest;
+mod meta;
+mod event;
+mod file;
+mod index;
+mod key;
+mod lock;
+#[cfg(feature = "global")]
+mod global;
+mod middleware;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod quarantine;
+mod registry;
+mod space;
+#[cfg(feature = "http")]
+mod remote;
+#[cfg(feature = "concurrent")]
+mod shared;
+mod storage;
+mod telemetry;
+mod template;
+mod throttle;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
 mod utils;
+mod writeback;
+mod writer;
+#[cfg(feature = "notify")]
+mod watch;
 
 // Re-export public API
-pub use config::{CacheConfig, CachePathConfig, CacheFormatConfig};
-pub use object::CacheObject;
-pub use cache::Cache;
+pub use config::{AppInfo, CacheConfig, CONFIG_VERSION, PROFILE_ENV, CachePathConfig, CacheFormatConfig, CacheIoConfig, CacheLimitsConfig, CacheRetryConfig, CacheScope, CollisionPolicy, IoAdvice, KeyEncoding, LongPathPolicy, LowSpacePolicy, MetadataMode, NameCase, NamePolicy, QueueFullPolicy, UndefinedVarPolicy, Uniqueness, WriteBackConfig};
+pub use object::{CacheObject, WeakCacheObject};
+pub use cache::{Cache, CachePage, CacheStats, ConflictPolicy};
+pub use manager::CacheManager;
+pub use manifest::{ManifestSigner, MANIFEST_FILE};
+#[cfg(feature = "hmac")]
+pub use manifest::HmacSha256Signer;
+pub use meta::{EntryMeta, InlineMetaStorage};
+pub use codec::{Codec, DecodeVisitor, JsonCodec};
+pub use dependency::CascadePolicy;
 pub use error::CacheError;
+pub use doctor::{DoctorCheck, DoctorReport, DoctorStatus};
+pub use event::{CacheEvent, CacheObserver};
+pub use file::CacheFile;
+pub use index::INDEX_FILE;
+pub use key::KeyMapper;
+#[cfg(feature = "global")]
+pub use global::{global, init_global, CONFIG_ENV};
+pub use middleware::{Middleware, Next, Operation};
+#[cfg(feature = "mmap")]
+pub use mmap::CacheMmapMut;
+#[cfg(feature = "concurrent")]
+pub use shared::SharedCache;
+#[cfg(feature = "wasm")]
+pub use browser::{BrowserBackend, BrowserStorage};
+pub use storage::{AlignedBuffer, FsStorage, MemoryStorage, Storage, TunedFsStorage, DIRECT_IO_ALIGN};
+pub use throttle::{ThrottleState, ThrottledStorage};
+pub use writeback::WriteBackStorage;
+pub use writer::{CacheWriter, ChunkedWrite, WriteSummary};
+pub use utils::{platform_cache_dir, set_platform_cache_dir, system_cache_dir};
+
+/// Re-exported so custom `Codec` implementations use the same version
+pub use erased_serde;
+/// Re-exported so `Cache::find_regex()` callers use the same version
+#[cfg(feature = "regex")]
+pub use regex;
+/// Re-exported so `CacheObject::get_bytes_shared()` callers use the same version
+#[cfg(feature = "bytes")]
+pub use bytes;
 
 /// Result type alias for cache operations
 pub type CacheResult<T> = std::result::Result<T, CacheError>;
@@ -105,6 +178,14 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn temp_config(temp_dir: &tempfile::TempDir) -> CacheConfig {
+        let mut config = CacheConfig::default();
+        config.path.windows = temp_dir.path().to_string_lossy().to_string();
+        config.path.linux = temp_dir.path().to_string_lossy().to_string();
+        config.format.filename = "{name}.cache".to_string();
+        config
+    }
+
     #[test]
     fn test_cache_config_default() {
         let config = CacheConfig::default();
@@ -201,7 +282,7 @@ mod tests {
         assert!(cache_obj.exists());
 
         // Test duplicate creation fails
-        let result = cache.create("test_cache", None);
+        let result = cache.create_new("test_cache", None);
         assert!(result.is_err());
         if let Err(e) = result {
             assert!(matches!(e, CacheError::AlreadyExists(_)));
@@ -216,7 +297,8 @@ mod tests {
         let result = cache.get("nonexistent");
         assert!(result.is_err());
         if let Err(e) = result {
-            assert!(matches!(e, CacheError::NotFound(_)));
+            assert!(matches!(&e, CacheError::EntryNotFound { name } if name == "nonexistent"));
+            assert!(e.is_not_found());
         }
 
         // Test length and empty
@@ -429,7 +511,7 @@ mod tests {
         assert_eq!(generic_error.message(), "Test error");
 
         // Test error conversions
-        let io_err: std::io::Error = std::io::Error::new(std::io::ErrorKind::Other, "test");
+        let io_err: std::io::Error = std::io::Error::other("test");
         let cache_err: CacheError = io_err.into();
         assert!(cache_err.is_io_error());
 
@@ -475,12 +557,568 @@ mod tests {
         assert_eq!(cloned_content, "test data");
     }
 
+    #[test]
+    fn test_cache_object_as_path_and_key() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("keyed", None).unwrap();
+        obj.write_string("data").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&*obj).unwrap(), "data");
+        let key = String::from("keyed");
+        assert_eq!(cache.get(&key).unwrap().path(), obj.path());
+        assert_eq!(cache.get(&*obj).unwrap().id(), obj.id());
+        cache.remove(&*obj).unwrap();
+        assert!(cache.get("keyed").is_err());
+    }
+
+    #[test]
+    fn test_cache_extend() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.insert("kept", b"old").unwrap();
+        cache.extend([
+            ("kept".to_string(), b"new".to_vec()),
+            ("bad/name".to_string(), b"skipped".to_vec()),
+            ("added".to_string(), b"value".to_vec()),
+        ]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("kept").unwrap().get_string().unwrap(), "new");
+        assert_eq!(cache.get("added").unwrap().get_string().unwrap(), "value");
+
+        let collected: Cache = (0..3).map(|i| (format!("item{}", i), vec![i])).collect();
+        assert_eq!(collected.len(), 3);
+        assert_eq!(collected.get("item2").unwrap().get_bytes().unwrap(), [2]);
+    }
+
+    #[test]
+    fn test_cache_index() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.insert("indexed", b"value").unwrap();
+
+        assert_eq!(cache["indexed"].get_string().unwrap(), "value");
+        assert_eq!(cache[&"indexed".to_string()].id(), cache.try_get("indexed").unwrap().id());
+        assert!(cache.try_get("missing").is_none());
+        let missing = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cache["missing"].id()));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_get_many() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.insert("first", b"1").unwrap();
+        cache.insert("second", b"2").unwrap();
+
+        let objects = cache.get_many(&["second", "missing", "first"]);
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[0].as_ref().unwrap().name(), "second");
+        assert!(matches!(&objects[1], Err(CacheError::EntryNotFound { name }) if name == "missing"));
+        assert_eq!(objects[2].as_ref().unwrap().get_string().unwrap(), "1");
+    }
+
+    #[test]
+    fn test_weak_cache_object() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.insert("held", b"data").unwrap();
+        let weak = obj.downgrade();
+        assert_eq!(weak.upgrade().unwrap().id(), obj.id());
+
+        // Deleted behind the cache's back
+        std::fs::remove_file(obj.path()).unwrap();
+        assert!(weak.upgrade().is_none());
+        obj.write_bytes(b"again").unwrap();
+        assert!(weak.upgrade().is_some());
+
+        // Replaced, removed or invalidated, even while still referenced
+        let replacement = cache.create("held", None).unwrap();
+        assert!(weak.upgrade().is_none());
+        let weak = replacement.downgrade();
+        cache.bump_generation();
+        assert!(weak.upgrade().is_none());
+        let weak = cache.insert("other", b"data").unwrap().downgrade();
+        cache.remove("other").unwrap();
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_gc_unread_older_than() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let consumed = cache.insert("consumed", b"used").unwrap();
+        let speculative = cache.insert("speculative", b"unused").unwrap();
+        consumed.get_bytes().unwrap();
+        assert!(consumed.was_read() && !speculative.was_read());
+
+        let hour = std::time::Duration::from_secs(3600);
+        assert_eq!(cache.gc_unread_older_than(hour).unwrap(), 0);
+        assert_eq!(cache.gc_unread_older_than(std::time::Duration::ZERO).unwrap(), 1);
+        assert!(cache.try_get("consumed").is_some());
+        assert!(cache.try_get("speculative").is_none());
+        let path = speculative.path().to_path_buf();
+        drop(speculative);
+        assert!(!path.exists());
+
+        // Sidecar markers carry reads over to other processes
+        let mut config = temp_config(&temp_dir);
+        config.metadata = MetadataMode::Sidecar;
+        let mut cache = Cache::new(config.clone()).unwrap();
+        cache.insert("speculative", b"unused").unwrap();
+        cache.insert("via_file", b"file").unwrap().get_file().unwrap();
+        let via_path = cache.insert("via_path", b"path").unwrap();
+        assert!(std::fs::metadata(&*via_path).is_ok());
+        assert!(!via_path.was_read());
+        assert!(std::fs::metadata(via_path.path_for_read()).is_ok());
+        std::fs::write(temp_dir.path().join("foreign.cache"), "unknown").unwrap();
+
+        let mut reopened = Cache::new(config).unwrap();
+        reopened.index_all().unwrap();
+        assert_eq!(reopened.gc_unread_older_than(std::time::Duration::ZERO).unwrap(), 1);
+        assert!(reopened.try_get("speculative").is_none());
+        for name in ["consumed", "via_file", "via_path", "foreign"] {
+            assert!(reopened.try_get(name).is_some(), "{name} was collected");
+        }
+    }
+
+    #[test]
+    fn test_delete_with_live_clone() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let object = cache.insert("shared", b"data").unwrap();
+        let reader = (*object).clone();
+        cache.remove("shared").unwrap();
+        assert_eq!(reader.get_bytes().unwrap(), b"data");
+        drop(reader);
+        assert!(object.exists());
+        drop(object);
+        assert!(!temp_dir.path().join("shared.cache").exists());
+
+        // A new entry at the same path is not removed by an old clone
+        let object = cache.insert("reused", b"old").unwrap();
+        let reader = (*object).clone();
+        cache.remove("reused").unwrap();
+        drop(object);
+        let fresh = cache.insert("reused", b"new").unwrap();
+        drop(reader);
+        assert_eq!(fresh.get_bytes().unwrap(), b"new");
+
+        // Holding the `Arc` returned by the cache is enough to keep the file
+        let held = cache.insert("held", b"data").unwrap();
+        cache.remove("held").unwrap();
+        assert_eq!(held.get_bytes().unwrap(), b"data");
+        drop(held);
+        assert!(!temp_dir.path().join("held.cache").exists());
+    }
+
+    #[test]
+    fn test_shared_index() {
+        let temp_dir = tempdir().unwrap();
+        let mut first = Cache::new(temp_config(&temp_dir)).unwrap();
+        let mut second = Cache::new(temp_config(&temp_dir)).unwrap();
+        assert_eq!(first.use_shared_index().unwrap(), 0);
+        assert_eq!(second.use_shared_index().unwrap(), 0);
+
+        let a = first.insert("a", b"from first").unwrap();
+        let b = second.insert("b", b"from second").unwrap();
+        assert_ne!(a.id(), b.id());
+        assert!(temp_dir.path().join(crate::INDEX_FILE).is_file());
+
+        assert_eq!(first.sync_index().unwrap(), 1);
+        assert_eq!(first.get("b").unwrap().get_bytes().unwrap(), b"from second");
+        assert_eq!(first.sync_index().unwrap(), 0);
+
+        second.remove("b").unwrap();
+        drop(b);
+        first.sync_index().unwrap();
+        assert!(first.try_get("b").is_none());
+        assert_eq!(first.index_all().unwrap(), 0);
+
+        // Names created elsewhere are taken before the next sync
+        second.insert("c", b"from second").unwrap();
+        let err = first.insert("c", b"from first").unwrap_err();
+        assert!(matches!(err, CacheError::AlreadyExists(_)));
+
+        // Objects registered locally survive syncs
+        std::fs::write(temp_dir.path().join("local.cache"), b"unindexed").unwrap();
+        let manifest =
+            r#"{"version":1,"entries":[{"name":"local","path":"local.cache","id":99,"created_at":0}]}"#;
+        assert_eq!(first.import_manifest(manifest).unwrap(), 1);
+        second.insert("d", b"from second").unwrap();
+        assert!(first.sync_index().unwrap() > 0);
+        assert_eq!(first.get("local").unwrap().get_bytes().unwrap(), b"unindexed");
+        assert!(first.get("d").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stale_index_lock() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.use_shared_index().unwrap();
+
+        // A lock still held by a process that stopped responding
+        let lock_path = temp_dir.path().join("index.lock");
+        let held = std::fs::File::create(&lock_path).unwrap();
+        fs4::FileExt::lock(&held).unwrap();
+        std::fs::write(&lock_path, r#"{"pid":4294967295,"host":"elsewhere","heartbeat":0}"#).unwrap();
+
+        cache.insert("after", b"crash").unwrap();
+        assert!(!lock_path.exists());
+        assert_eq!(cache.get("after").unwrap().get_bytes().unwrap(), b"crash");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_heartbeat() {
+        let temp_dir = tempdir().unwrap();
+        let lock_path = temp_dir.path().join("held.lock");
+        let stale_after = std::time::Duration::from_secs(2);
+
+        // A long holder keeps its heartbeat fresh, a short one does not
+        let held = lock::FileLock::acquire(&lock_path, Some(stale_after)).unwrap().keep_alive(stale_after);
+        let short_path = temp_dir.path().join("short.lock");
+        let short = lock::FileLock::acquire(&short_path, Some(stale_after)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2500));
+        assert!(lock::FileLock::try_acquire(&lock_path, Some(stale_after)).unwrap().is_err());
+        let broken = lock::FileLock::try_acquire(&short_path, Some(stale_after)).unwrap().unwrap();
+        drop(short);
+        drop(broken);
+
+        drop(held);
+        assert!(!lock_path.exists());
+
+        // Once its lock is broken, the old holder leaves the new lock file alone
+        let stuck = lock::FileLock::acquire(&lock_path, None).unwrap();
+        std::fs::write(&lock_path, r#"{"pid":4294967295,"host":"elsewhere","heartbeat":0}"#).unwrap();
+        let next = lock::FileLock::try_acquire(&lock_path, Some(stale_after)).unwrap().unwrap();
+        drop(stuck);
+        assert!(lock_path.exists());
+        drop(next);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_claim_exclusive() {
+        let temp_dir = tempdir().unwrap();
+        let mut first = Cache::new(temp_config(&temp_dir)).unwrap();
+        let mut second = Cache::new(temp_config(&temp_dir)).unwrap();
+        first.claim_exclusive().unwrap();
+        first.claim_exclusive().unwrap();
+
+        let err = second.claim_exclusive().unwrap_err();
+        assert!(matches!(err, CacheError::AlreadyExists(_)));
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        assert!(first.release_exclusive());
+        second.claim_exclusive().unwrap();
+        drop(second);
+        first.claim_exclusive().unwrap();
+    }
+
+    #[test]
+    fn test_parent_cache() {
+        let machine_dir = tempdir().unwrap();
+        let mut machine = Cache::new(temp_config(&machine_dir)).unwrap();
+        machine.insert("toolchain", b"shared artifact").unwrap();
+        let machine = std::sync::Arc::new(machine);
+
+        let project_dir = tempdir().unwrap();
+        let mut project = Cache::new(temp_config(&project_dir)).unwrap();
+        project.set_parent(std::sync::Arc::clone(&machine), false);
+        let hit = project.get("toolchain").unwrap();
+        assert_eq!(hit.get_bytes().unwrap(), b"shared artifact");
+        assert!(hit.path().starts_with(machine_dir.path()));
+        assert!(matches!(hit.write_bytes(b"overwritten"), Err(CacheError::PermissionDenied(_))));
+        assert!(matches!(hit.delete(), Err(CacheError::PermissionDenied(_))));
+        assert_eq!(machine.get("toolchain").unwrap().get_bytes().unwrap(), b"shared artifact");
+        assert!(project.get("missing").is_err());
+        assert_eq!(project.len(), 0);
+
+        let promoting_dir = tempdir().unwrap();
+        let mut promoting = Cache::new(temp_config(&promoting_dir)).unwrap();
+        promoting.set_parent(machine, true);
+        let promoted = promoting.get("toolchain").unwrap();
+        assert!(promoted.path().starts_with(promoting_dir.path()));
+        assert_eq!(promoted.get_bytes().unwrap(), b"shared artifact");
+        assert_ne!(promoted.id(), 0);
+        assert_eq!(promoting.len(), 1);
+        assert_eq!(promoting.iter().next().unwrap().name(), "toolchain");
+        assert!(promoting.dump_manifest().unwrap().contains("toolchain"));
+        assert!(std::sync::Arc::ptr_eq(&promoting.get("toolchain").unwrap(), &promoted));
+
+        // The next object gets its own ID, and the promoted one stays registered
+        let local = promoting.insert("local", b"own").unwrap();
+        assert_ne!(local.id(), promoted.id());
+        assert_eq!(promoting.len(), 2);
+
+        // Files this cache did not create are not replaced by promotions
+        let protected_dir = tempdir().unwrap();
+        let foreign = protected_dir.path().join("toolchain.cache");
+        std::fs::write(&foreign, "user data").unwrap();
+        let mut config = temp_config(&protected_dir);
+        config.protect_existing_files = true;
+        let mut protected = Cache::new(config).unwrap();
+        protected.set_parent(std::sync::Arc::clone(promoting.parent().unwrap()), true);
+        let hit = protected.get("toolchain").unwrap();
+        assert!(hit.path().starts_with(machine_dir.path()));
+        assert_eq!(std::fs::read_to_string(&foreign).unwrap(), "user data");
+        assert_eq!(protected.len(), 0);
+    }
+
+    #[test]
+    fn test_promoted_entries_copy_on_write() {
+        let machine_dir = tempdir().unwrap();
+        let mut machine = Cache::new(temp_config(&machine_dir)).unwrap();
+        let original = machine.insert("artifact", b"v1").unwrap();
+        let sibling = machine.insert("sibling", b"s1").unwrap();
+        let machine = std::sync::Arc::new(machine);
+
+        let project_dir = tempdir().unwrap();
+        let mut project = Cache::new(temp_config(&project_dir)).unwrap();
+        project.set_parent(machine, true);
+        let promoted = project.get("artifact").unwrap();
+        assert_eq!(promoted.get_bytes().unwrap(), b"v1");
+
+        // Writes on either side break the sharing
+        promoted.write_bytes(b"project").unwrap();
+        assert_eq!(original.get_bytes().unwrap(), b"v1");
+        let promoted_sibling = project.get("sibling").unwrap();
+        sibling.write_at(0, b"S").unwrap();
+        assert_eq!(promoted_sibling.get_bytes().unwrap(), b"s1");
+        assert_eq!(sibling.get_bytes().unwrap(), b"S1");
+
+        // Sealing the promoted copy leaves the parent's file writable
+        let shared = machine_dir.path().join("shared.cache");
+        std::fs::write(&shared, b"linked").unwrap();
+        let mut parent = Cache::new(temp_config(&machine_dir)).unwrap();
+        parent.index_all().unwrap();
+        project.set_parent(std::sync::Arc::new(parent), true);
+        project.get("shared").unwrap().seal().unwrap();
+        assert!(!std::fs::metadata(&shared).unwrap().permissions().readonly());
+    }
+
+    #[test]
+    fn test_seal() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let artifact = cache.insert("sha256-abc", b"artifact").unwrap();
+        assert!(!artifact.is_sealed());
+        artifact.seal().unwrap();
+        assert!(artifact.is_sealed());
+        assert!(std::fs::metadata(artifact.path()).unwrap().permissions().readonly());
+
+        let err = artifact.write_bytes(b"changed").unwrap_err();
+        assert!(matches!(err, CacheError::PermissionDenied(_)));
+        assert!(artifact.write_at(0, b"X").is_err());
+        assert!(artifact.buffered_writer(16).is_err());
+        assert!(cache.create("sha256-abc", None).is_err());
+        assert_eq!(artifact.get_bytes().unwrap(), b"artifact");
+        let err = cache.migrate_format("{name}.cache", "v2-{name}.cache").unwrap_err();
+        assert!(matches!(err, CacheError::PermissionDenied(_)));
+        assert!(artifact.path().exists());
+
+        let memory = Cache::in_memory().insert("sealed", b"data").unwrap();
+        memory.seal().unwrap();
+        assert!(memory.write_bytes(b"other").is_err());
+
+        cache.remove("sha256-abc").unwrap();
+        let path = artifact.path().to_path_buf();
+        drop(artifact);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let url = "https://example.com/a b?q=1";
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.key_encoding = Some(crate::KeyEncoding::Percent);
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let obj = cache.insert(url, b"page").unwrap();
+        assert_eq!(
+            obj.path().file_name().unwrap(),
+            "https%3A%2F%2Fexample%2Ecom%2Fa%20b%3Fq%3D1.cache"
+        );
+        let mut reopened = Cache::new(config).unwrap();
+        assert_eq!(reopened.index_all().unwrap(), 1);
+        assert_eq!(reopened.get(url).unwrap().get_bytes().unwrap(), b"page");
+        let decoded = crate::key::decode_key(crate::KeyEncoding::Percent, "100%+1%2F%e2%82%ac%");
+        assert_eq!(decoded.as_deref(), Some("100%+1/€%"));
+
+        let hashed_dir = tempdir().unwrap();
+        let mut config = temp_config(&hashed_dir);
+        config.format.key_encoding = Some(crate::KeyEncoding::Hash);
+        // Hashed names can only be recovered from the metadata
+        assert!(matches!(config.validate(), Err(CacheError::InvalidConfig(_))));
+        let err = Cache::new(config.clone()).unwrap().create("https://example.com", None).unwrap_err();
+        assert!(matches!(err, CacheError::InvalidConfig(_)));
+        config.metadata = crate::MetadataMode::Sidecar;
+        config.validate().unwrap();
+        let long_key = format!("/srv/{}", "segment/".repeat(64));
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let obj = cache.insert(&long_key, b"deep").unwrap();
+        assert_eq!(obj.path().file_name().unwrap().len(), 64 + ".cache".len());
+        let mut reopened = Cache::new(config).unwrap();
+        assert_eq!(reopened.index_all().unwrap(), 1);
+        assert_eq!(reopened.get(&long_key).unwrap().get_bytes().unwrap(), b"deep");
+        assert!(Cache::new(temp_config(&hashed_dir)).unwrap().create("a/b", None).is_err());
+    }
+
+    #[test]
+    fn test_name_policy_sanitize() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        assert!(Cache::new(config.clone()).unwrap().create("a/b", None).is_err());
+
+        config.name_policy = crate::NamePolicy::Sanitize;
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let nested = cache.insert("reports/2026:q1", b"sales").unwrap();
+        assert_eq!(nested.path().file_name().unwrap(), "reports%2F2026%3Aq1.cache");
+        let plain = cache.insert("café", b"menu").unwrap();
+        assert_eq!(plain.path().file_name().unwrap(), "café.cache");
+        assert_eq!(cache.insert("../up", b"").unwrap().path().parent().unwrap(), temp_dir.path());
+
+        let mut reopened = Cache::new(config).unwrap();
+        assert_eq!(reopened.index_all().unwrap(), 3);
+        assert_eq!(reopened.get("reports/2026:q1").unwrap().get_bytes().unwrap(), b"sales");
+
+        // Names that are path components on their own are encoded too
+        let bare_dir = tempdir().unwrap();
+        let mut config = temp_config(&bare_dir);
+        config.name_policy = crate::NamePolicy::Sanitize;
+        config.format.filename = "{name}".to_string();
+        let mut bare = Cache::new(config).unwrap();
+        for (name, file_name) in [(".", "%2E"), ("..", "%2E%2E")] {
+            let obj = bare.insert(name, name.as_bytes()).unwrap();
+            assert_eq!(obj.path(), bare_dir.path().join(file_name));
+            assert_eq!(bare.get(name).unwrap().get_bytes().unwrap(), name.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_nfc_names() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.quarantine_after = 1;
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let decomposed = cache.insert("cafe\u{301}", b"menu").unwrap();
+        assert_eq!(decomposed.name(), "caf\u{e9}");
+        assert_eq!(cache.get("caf\u{e9}").unwrap().get_bytes().unwrap(), b"menu");
+        assert_eq!(cache.get("cafe\u{301}").unwrap().path(), decomposed.path());
+        assert_eq!(cache["cafe\u{301}"].id(), decomposed.id());
+
+        // Every spelling of a quarantined name is refused and can release it
+        let broken = cache.insert("nai\u{308}ve", b"data").unwrap();
+        std::fs::remove_file(broken.path()).unwrap();
+        std::fs::create_dir(broken.path()).unwrap();
+        assert!(broken.get_bytes().is_err());
+        assert!(cache.get("na\u{ef}ve").is_err());
+        assert!(cache.get("nai\u{308}ve").is_err());
+        assert!(cache.release_quarantine("nai\u{308}ve"));
+        std::fs::remove_dir(broken.path()).unwrap();
+        assert_eq!(cache.remove_prefix("nai\u{308}").unwrap(), 1);
+        cache.remove("cafe\u{301}").unwrap();
+        assert!(cache.get("caf\u{e9}").is_err());
+
+        config.normalize_names = false;
+        cache.set_config(config);
+        cache.insert("cafe\u{301}", b"nfd").unwrap();
+        assert!(cache.get("caf\u{e9}").is_err());
+        assert_eq!(cache.get("cafe\u{301}").unwrap().get_bytes().unwrap(), b"nfd");
+    }
+
+    #[test]
+    fn test_name_case() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let upper = cache.insert("Report", b"upper").unwrap();
+        let lower = cache.insert("report", b"lower").unwrap();
+        // Case-insensitive file systems get a suffixed file instead of a shared one
+        assert_ne!(crate::utils::path_key(upper.path()), crate::utils::path_key(lower.path()));
+        assert_eq!(cache.get("Report").unwrap().get_bytes().unwrap(), b"upper");
+        assert_eq!(cache.get("report").unwrap().get_bytes().unwrap(), b"lower");
+
+        config.name_case = crate::NameCase::Insensitive;
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.insert("Quarterly Report", b"sales").unwrap();
+        assert_eq!(obj.name(), "quarterly report");
+        assert_eq!(cache.get("QUARTERLY report").unwrap().get_bytes().unwrap(), b"sales");
+        assert_eq!(cache["QUARTERLY REPORT"].id(), obj.id());
+        assert!(cache.insert("quarterly REPORT", b"").is_err());
+        cache.remove("Quarterly Report").unwrap();
+        assert!(cache.get("quarterly report").is_err());
+    }
+
+    #[test]
+    fn test_long_path_truncation() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        let long_name = format!("{}final", "report_".repeat(35));
+        let result = Cache::new(config.clone()).unwrap().create(&long_name, None);
+        assert!(matches!(result, Err(CacheError::InvalidPath(_))));
+
+        config.format.on_long_path = crate::LongPathPolicy::Truncate;
+        // Truncated names can only be recovered from the metadata
+        assert!(matches!(config.validate(), Err(CacheError::InvalidConfig(_))));
+        let result = Cache::new(config.clone()).unwrap().create(&long_name, None);
+        assert!(matches!(result, Err(CacheError::InvalidConfig(_))));
+        config.metadata = MetadataMode::Sidecar;
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let obj = cache.insert(&long_name, b"long").unwrap();
+        assert!(obj.path().file_name().unwrap().len() + ".meta".len() <= 255);
+        let other = cache.insert(&long_name.replace("final", "other"), b"other").unwrap();
+        assert_ne!(obj.path(), other.path());
+        assert_eq!(cache.get(&long_name).unwrap().get_bytes().unwrap(), b"long");
+
+        let mut opened = Cache::open(temp_dir.path()).unwrap();
+        opened.set_config(config.clone());
+        assert_eq!(opened.get(&long_name).unwrap().path(), obj.path());
+
+        let mut reopened = Cache::new(config).unwrap();
+        assert_eq!(reopened.index_all().unwrap(), 2);
+        assert_eq!(reopened.get(&long_name).unwrap().get_bytes().unwrap(), b"long");
+    }
+
+    #[test]
+    fn test_key_mapper() {
+        struct HttpLayout;
+        impl crate::KeyMapper for HttpLayout {
+            fn map(&self, name: &str, _id: u32) -> std::path::PathBuf {
+                let url = name.trim_start_matches("https://");
+                let (host, route) = url.split_once('/').unwrap_or((url, ""));
+                let hash = &crate::manifest::sha256_hex(name.as_bytes())[..16];
+                [host, &route.replace('/', "_"), &format!("{}.bin", hash)].iter().collect()
+            }
+        }
+        struct Escaping;
+        impl crate::KeyMapper for Escaping {
+            fn map(&self, name: &str, _id: u32) -> std::path::PathBuf {
+                std::path::Path::new("..").join(name)
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.set_key_mapper(std::sync::Arc::new(HttpLayout));
+        let obj = cache.insert("https://example.com/api/users", b"[]").unwrap();
+        let relative = obj.path().strip_prefix(temp_dir.path()).unwrap();
+        assert!(relative.starts_with("example.com/api_users"));
+        assert_eq!(relative.extension().unwrap(), "bin");
+        assert_eq!(cache.get("https://example.com/api/users").unwrap().get_bytes().unwrap(), b"[]");
+
+        cache.set_key_mapper(std::sync::Arc::new(Escaping));
+        assert!(matches!(cache.create("outside", None), Err(CacheError::InvalidPath(_))));
+    }
+
     #[test]
     fn test_expand_path() {
         // Test tilde expansion
         let path_with_tilde = "~/test/path";
         let expanded = crate::utils::expand_path(path_with_tilde);
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::utils::home_dir() {
             let home_str = home.to_string_lossy();
             assert!(expanded.starts_with(&*home_str));
         }
@@ -541,8 +1179,10 @@ mod tests {
         assert_eq!(cache.len(), 0);
         assert!(cache.is_empty());
         
-        // File should be deleted
-        assert!(!cache_obj.exists());
+        // File should be deleted once the last handle is gone
+        let path = cache_obj.path().to_path_buf();
+        drop(cache_obj);
+        assert!(!path.exists());
     }
 
     #[test]
@@ -556,6 +1196,144 @@ mod tests {
 
         let permission_error = CacheError::PermissionDenied("test".to_string());
         assert!(permission_error.is_permission_denied());
+
+        // Cache errors convert into io::Error for io::Result code
+        let io_err: std::io::Error = CacheError::EntryNotFound { name: "entry".to_string() }.into();
+        assert_eq!(io_err.kind(), std::io::ErrorKind::NotFound);
+        assert!(io_err.get_ref().unwrap().downcast_ref::<CacheError>().is_some());
+        let io_err: std::io::Error = CacheError::Corrupted("bad".to_string()).into();
+        assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidData);
+        let io_err: std::io::Error = CacheError::Io(std::io::ErrorKind::Interrupted.into()).into();
+        assert_eq!(io_err.kind(), std::io::ErrorKind::Interrupted);
+
+        // Wrapped errors are chained through source()
+        use std::error::Error;
+        let io_error = CacheError::Io(std::io::Error::other("disk"));
+        assert_eq!(io_error.source().unwrap().to_string(), "disk");
+        let task_error = CacheError::Task { operation: "read", source: "cancelled".into() };
+        assert_eq!(task_error.kind(), "task");
+        assert_eq!(task_error.source().unwrap().to_string(), "cancelled");
+        assert!(CacheError::NotFound("test".to_string()).source().is_none());
+        let entry_error = CacheError::EntryNotFound { name: "entry".to_string() };
+        assert!(entry_error.is_not_found());
+        assert_eq!(entry_error.to_string(), "Cache object 'entry' not found");
+    }
+
+    #[test]
+    fn test_error_context() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("missing", None).unwrap();
+        std::fs::remove_file(obj.path()).unwrap();
+
+        let err = obj.get_bytes().unwrap_err();
+        assert!(err.is_io_error());
+        assert_eq!(err.operation(), Some("read"));
+        assert_eq!(err.path(), Some(obj.path()));
+        assert_eq!(err.entry(), Some("missing"));
+        assert!(err.to_string().contains(&obj.path().display().to_string()));
+        match &err {
+            CacheError::Io(e) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+        assert_eq!(obj.size().unwrap_err().operation(), Some("size"));
+
+        let plain = CacheError::Io(std::io::Error::other("no context"));
+        assert_eq!((plain.path(), plain.entry()), (None, None));
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Debug, Default)]
+        struct Flaky {
+            inner: MemoryStorage,
+            failures: AtomicUsize,
+        }
+
+        impl Storage for Flaky {
+            fn read(&self, path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+                if self.failures.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                self.inner.read(path)
+            }
+            fn write(&self, path: &std::path::Path, content: &[u8]) -> std::io::Result<()> {
+                self.inner.write(path, content)
+            }
+            fn remove(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.remove(path)
+            }
+            fn exists(&self, path: &std::path::Path) -> bool {
+                self.inner.exists(path)
+            }
+            fn size(&self, path: &std::path::Path) -> std::io::Result<u64> {
+                self.inner.size(path)
+            }
+            fn create_dir_all(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.create_dir_all(path)
+            }
+            fn create_file(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.create_file(path)
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let storage = std::sync::Arc::new(Flaky::default());
+        let config = CacheConfig {
+            retry: CacheRetryConfig { max_attempts: 3, backoff_ms: 1, max_backoff_ms: 2 },
+            ..temp_config(&temp_dir)
+        };
+        let mut cache = Cache::with_storage(config, storage.clone()).unwrap();
+        let obj = cache.create("flaky", None).unwrap();
+        obj.write_string("eventually").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "eventually");
+        assert_eq!(storage.failures.load(Ordering::SeqCst), 3);
+
+        // Only the storage is retried, never the caller's writer
+        struct TimingOut(usize);
+        impl std::io::Write for TimingOut {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                self.0 += 1;
+                Err(std::io::ErrorKind::TimedOut.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut writer = TimingOut(0);
+        assert!(obj.copy_to_writer(&mut writer).is_err());
+        assert_eq!(writer.0, 1);
+
+        // Without retries the transient error surfaces
+        storage.failures.store(0, Ordering::SeqCst);
+        cache.set_config(temp_config(&temp_dir));
+        let err = obj.get_string().unwrap_err();
+        assert!(err.is_retryable());
+        assert!(!CacheError::NotFound("gone".to_string()).is_retryable());
+        assert!(!CacheError::Io(std::io::ErrorKind::NotFound.into()).is_retryable());
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn test_error_diagnostics() {
+        use miette::Diagnostic;
+
+        let err = CacheError::Io(std::io::ErrorKind::PermissionDenied.into()).with_context(
+            "write",
+            std::path::Path::new("/tmp/Rust/Cache/entry.cache"),
+            Some("entry"),
+        );
+        assert_eq!(err.code().unwrap().to_string(), "cache_lite::io");
+        assert_eq!(
+            err.help().unwrap().to_string(),
+            "cache directory not writable — check permissions on /tmp/Rust/Cache"
+        );
+        let err = CacheError::EntryNotFound { name: "entry".to_string() };
+        assert_eq!(err.code().unwrap().to_string(), "cache_lite::not_found");
+        assert!(err.help().unwrap().to_string().contains("index_all()"));
+        assert!(CacheError::new("plain").help().is_none());
     }
 
     #[test]
@@ -571,4 +1349,2010 @@ mod tests {
         assert_eq!(config.format.filename, parsed_config.format.filename);
         assert_eq!(config.format.time, parsed_config.format.time);
     }
+
+    #[test]
+    fn test_doctor_report() {
+        let temp_dir = tempdir().unwrap();
+        let cache = Cache::new(temp_config(&temp_dir)).unwrap();
+
+        let report = cache.doctor();
+        assert!(report.is_healthy(), "{}", report);
+        assert_eq!(report.check("writable").unwrap().status, DoctorStatus::Ok);
+        assert!(report.check("free_space").is_some());
+
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "static.cache".to_string();
+        let cache = Cache::new(config).unwrap();
+        let report = cache.doctor();
+        assert!(!report.is_healthy());
+        assert_eq!(report.errors().next().unwrap().name, "config");
+    }
+
+    #[test]
+    fn test_cache_observer_events() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<CacheEvent>>);
+
+        impl CacheObserver for Recorder {
+            fn on_event(&self, event: &CacheEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let recorder = Arc::new(Recorder::default());
+        cache.add_observer(recorder.clone());
+
+        let obj = cache.create("observed", None).unwrap();
+        obj.write_string("hello").unwrap();
+        obj.get_string().unwrap();
+        cache.remove("observed").unwrap();
+        assert!(cache.create("bad/name", None).is_err());
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], CacheEvent::Created { name: "observed".to_string(), id: obj.id() });
+        assert_eq!(events[1].size(), Some(5));
+        assert!(matches!(events[2], CacheEvent::Read { size: 5, .. }));
+        assert!(matches!(events[3], CacheEvent::Removed { .. }));
+        assert!(matches!(events[4], CacheEvent::Error { operation: "create", kind: "invalid_name", .. }));
+        drop(events);
+
+        // Observers may use the cache again, the dispatch holds no lock
+        struct Reader(Mutex<Option<Arc<CacheObject>>>);
+
+        impl CacheObserver for Reader {
+            fn on_event(&self, event: &CacheEvent) {
+                let obj = self.0.lock().unwrap().clone();
+                if let (CacheEvent::Written { .. }, Some(obj)) = (event, obj) {
+                    obj.get_bytes().unwrap();
+                }
+            }
+        }
+
+        let _receiver = cache.subscribe();
+        let obj = cache.create("reentrant", None).unwrap();
+        cache.add_observer(Arc::new(Reader(Mutex::new(Some(Arc::clone(&obj))))));
+        obj.write_string("again").unwrap();
+        assert!(matches!(recorder.0.lock().unwrap().last(), Some(CacheEvent::Read { size: 5, .. })));
+    }
+
+    #[test]
+    fn test_cache_subscribe() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let receiver = cache.subscribe();
+
+        let handle = std::thread::spawn(move || receiver.iter().take(2).collect::<Vec<_>>());
+
+        let obj = cache.create("subscribed", None).unwrap();
+        obj.write_bytes(&[1, 2, 3]).unwrap();
+
+        let events = handle.join().unwrap();
+        assert!(matches!(events[0], CacheEvent::Created { .. }));
+        assert_eq!(events[1].size(), Some(3));
+
+        // The receiver is gone, emitting must not fail
+        obj.write_bytes(&[4]).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_cache_event_stream() {
+        use tokio_stream::StreamExt;
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let mut stream = cache.event_stream();
+
+        let obj = cache.create("streamed", None).unwrap();
+        obj.write_string("async").unwrap();
+
+        assert!(matches!(stream.next().await, Some(CacheEvent::Created { .. })));
+        assert_eq!(stream.next().await.unwrap().size(), Some(5));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_object_io() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("async_io", None).unwrap();
+
+        obj.write_bytes_async(b"off the runtime".to_vec()).await.unwrap();
+        assert_eq!(obj.get_bytes_async().await.unwrap(), b"off the runtime");
+        obj.delete().unwrap();
+        assert!(matches!(obj.get_bytes_async().await, Err(CacheError::Io(_))));
+    }
+
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_uring_object_io() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("uring", None).unwrap();
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+        // Goes through io_uring where the kernel allows it, the blocking pool otherwise
+        obj.write_bytes_async(content.clone()).await.unwrap();
+        assert_eq!(std::fs::read(obj.path()).unwrap(), content);
+        assert_eq!(obj.get_bytes_async().await.unwrap(), content);
+        let reads = tokio::join!(obj.get_bytes_async(), obj.get_bytes_async(), obj.get_bytes_async());
+        assert!([reads.0, reads.1, reads.2].into_iter().all(|read| read.unwrap() == content));
+
+        // Middleware needs the synchronous pipeline
+        struct Deny;
+        impl Middleware for Deny {
+            fn call(&self, _op: &Operation<'_>, _next: Next<'_>) -> CacheResult<()> {
+                Err(CacheError::PermissionDenied("denied".to_string()))
+            }
+        }
+        cache.add_middleware(std::sync::Arc::new(Deny));
+        assert!(matches!(obj.get_bytes_async().await, Err(CacheError::PermissionDenied(_))));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_get_many_bytes_async() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.insert("first", b"1").unwrap();
+        cache.insert("second", b"2").unwrap();
+
+        let contents = cache.get_many_bytes_async(&["second", "missing", "first"]).await;
+        assert_eq!(contents[0].as_deref().unwrap(), b"2");
+        assert!(contents[1].as_ref().is_err_and(CacheError::is_not_found));
+        assert_eq!(contents[2].as_deref().unwrap(), b"1");
+    }
+
+    #[test]
+    fn test_write_back() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "{name}.cache".to_string();
+        config.io.write_back = Some(WriteBackConfig {
+            interval_ms: 60_000,
+            max_dirty_bytes: 1024,
+            ..WriteBackConfig::default()
+        });
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("deferred", None).unwrap();
+        obj.write_string("first").unwrap();
+        obj.write_string("second").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "second");
+        assert_eq!(std::fs::read_to_string(obj.path()).unwrap(), "");
+        cache.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(obj.path()).unwrap(), "second");
+
+        // Crossing the dirty threshold flushes without waiting for the timer
+        let big = cache.create("big", None).unwrap();
+        big.write_bytes(&[1; 2048]).unwrap();
+        let start = std::time::Instant::now();
+        while std::fs::metadata(big.path()).unwrap().len() != 2048 {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        obj.write_string("gone").unwrap();
+        obj.delete().unwrap();
+        cache.flush().unwrap();
+        assert!(!obj.path().exists());
+
+        let last = cache.create("last", None).unwrap();
+        last.write_string("on drop").unwrap();
+        let path = last.path().to_path_buf();
+        drop((obj, big, last, cache));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "on drop");
+    }
+
+    #[test]
+    fn test_write_back_queue_limit() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        let write_back = WriteBackConfig {
+            interval_ms: 60_000,
+            max_queue_bytes: Some(100),
+            on_full: QueueFullPolicy::Fail,
+            ..WriteBackConfig::default()
+        };
+        config.io.write_back = Some(write_back.clone());
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let first = cache.create("first", None).unwrap();
+        let second = cache.create("second", None).unwrap();
+        first.write_bytes(&[1; 80]).unwrap();
+        // Rewriting a queued entry only counts the new content
+        first.write_bytes(&[2; 90]).unwrap();
+        let err = second.write_bytes(&[3; 20]).unwrap_err();
+        assert!(matches!(&err, CacheError::Io(e) if e.kind() == std::io::ErrorKind::WouldBlock), "{:?}", err);
+        // Failing fast is the point of the policy, retries would only wait
+        assert!(!err.is_retryable());
+        cache.flush().unwrap();
+        second.write_bytes(&[3; 20]).unwrap();
+
+        // Blocked writers wake the flusher and wait for it
+        config.io.write_back = Some(WriteBackConfig { on_full: QueueFullPolicy::Block, ..write_back });
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("blocking", None).unwrap();
+        for i in 0..5u8 {
+            obj.write_bytes(&[i; 80]).unwrap();
+            cache.create(&format!("blocking_{}", i), None).unwrap().write_bytes(&[i; 80]).unwrap();
+        }
+        assert_eq!(obj.get_bytes().unwrap(), [4; 80]);
+
+        // Blocked writers give up once a flush fails to make room
+        #[derive(Debug, Default)]
+        struct ReadOnly(MemoryStorage);
+
+        impl Storage for ReadOnly {
+            fn read(&self, path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+                self.0.read(path)
+            }
+            fn write(&self, _path: &std::path::Path, _content: &[u8]) -> std::io::Result<()> {
+                Err(std::io::ErrorKind::PermissionDenied.into())
+            }
+            fn remove(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.0.remove(path)
+            }
+            fn exists(&self, path: &std::path::Path) -> bool {
+                self.0.exists(path)
+            }
+            fn size(&self, path: &std::path::Path) -> std::io::Result<u64> {
+                self.0.size(path)
+            }
+            fn create_dir_all(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.0.create_dir_all(path)
+            }
+            fn create_file(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.0.create_file(path)
+            }
+        }
+
+        let blocking = WriteBackConfig {
+            interval_ms: 60_000,
+            max_queue_bytes: Some(100),
+            ..WriteBackConfig::default()
+        };
+        let storage = WriteBackStorage::new(std::sync::Arc::new(ReadOnly::default()), blocking);
+        storage.write(std::path::Path::new("first"), &[1; 80]).unwrap();
+        let err = storage.write(std::path::Path::new("second"), &[2; 80]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(storage.read(std::path::Path::new("first")).unwrap(), [1; 80]);
+    }
+
+    #[test]
+    fn test_write_rate_limit() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.io.max_write_bytes_per_sec = Some(100_000);
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("throttled", None).unwrap();
+
+        // The first second worth of bytes passes as a burst
+        let start = std::time::Instant::now();
+        obj.write_bytes(&[0; 100_000]).unwrap();
+        obj.write_bytes(&[0; 20_000]).unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(150));
+
+        let throttle = cache.stats().throttle.unwrap();
+        assert_eq!(throttle.bytes_per_sec, 100_000);
+        assert_eq!(throttle.throttled_writes, 1);
+        assert_eq!(throttle.waiting, 0);
+        assert!(throttle.throttled_for >= std::time::Duration::from_millis(150));
+        assert!(Cache::new(temp_config(&temp_dir)).unwrap().stats().throttle.is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_write_back_flush_all() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.io.write_back = Some(WriteBackConfig::default());
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("synced", None).unwrap();
+        obj.write_string("later").unwrap();
+        cache.flush_all().await.unwrap();
+        assert_eq!(std::fs::read_to_string(obj.path()).unwrap(), "later");
+    }
+
+    #[test]
+    fn test_cache_middleware() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct ReadOnly;
+
+        impl Middleware for ReadOnly {
+            fn call(&self, op: &Operation<'_>, mut next: Next<'_>) -> CacheResult<()> {
+                match op {
+                    Operation::Write { name: "frozen", .. } => {
+                        Err(CacheError::PermissionDenied("frozen".to_string()))
+                    }
+                    _ => next.run(op),
+                }
+            }
+        }
+
+        #[derive(Default)]
+        struct Counter(AtomicUsize);
+
+        impl Middleware for Counter {
+            fn call(&self, op: &Operation<'_>, mut next: Next<'_>) -> CacheResult<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                next.run(op)
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let counter = Arc::new(Counter::default());
+        cache.add_middleware(counter.clone());
+        cache.add_middleware(Arc::new(ReadOnly));
+
+        let frozen = cache.create("frozen", None).unwrap();
+        let result = frozen.write_string("nope");
+        assert!(matches!(result, Err(CacheError::PermissionDenied(_))));
+
+        cache.get("frozen").unwrap();
+        let open = cache.create("open", None).unwrap();
+        open.write_string("yes").unwrap();
+        assert_eq!(open.get_string().unwrap(), "yes");
+
+        // create, write, get, create, write, read
+        assert_eq!(counter.0.load(Ordering::SeqCst), 6);
+
+        // Streamed writes are staged, a rejected one leaves the entry intact
+        std::fs::write(frozen.path(), "kept").unwrap();
+        let result = frozen.copy_from_reader(&mut "replaced".as_bytes());
+        assert!(matches!(result, Err(CacheError::PermissionDenied(_))));
+        assert_eq!(std::fs::read_to_string(frozen.path()).unwrap(), "kept");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_cache_object_values() {
+        use std::sync::Arc;
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Entry {
+            id: u32,
+            tags: Vec<String>,
+        }
+
+        /// Stores values as pretty-printed JSON
+        struct PrettyJson;
+
+        impl Codec for PrettyJson {
+            fn encode(&self, value: &dyn erased_serde::Serialize) -> CacheResult<Vec<u8>> {
+                serde_json::to_vec_pretty(value).map_err(|e| CacheError::Serialization(e.to_string()))
+            }
+
+            fn decode(&self, bytes: &[u8], visit: &mut DecodeVisitor<'_>) -> CacheResult<()> {
+                JsonCodec.decode(bytes, visit)
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("values", None).unwrap();
+        let entry = Entry { id: 7, tags: vec!["a".to_string()] };
+
+        obj.write_value(&entry).unwrap();
+        assert_eq!(obj.get_string().unwrap(), r#"{"id":7,"tags":["a"]}"#);
+        assert_eq!(obj.get_value::<Entry>().unwrap(), entry);
+
+        cache.set_codec(Arc::new(PrettyJson));
+        obj.write_value(&entry).unwrap();
+        assert!(obj.get_string().unwrap().contains('\n'));
+        assert_eq!(obj.get_value::<Entry>().unwrap(), entry);
+
+        obj.write_value_with(&JsonCodec, &entry).unwrap();
+        assert!(!obj.get_string().unwrap().contains('\n'));
+
+        obj.write_string("not json").unwrap();
+        assert!(matches!(obj.get_value::<Entry>(), Err(CacheError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_in_memory_cache() {
+        let mut cache = Cache::in_memory();
+        assert!(cache.is_in_memory());
+
+        let obj = cache.create("memory", None).unwrap();
+        assert_eq!(obj.path(), std::path::Path::new("memory"));
+        assert!(obj.exists());
+        assert_eq!(obj.size().unwrap(), 0);
+
+        obj.write_string("kept in memory").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "kept in memory");
+        assert_eq!(cache.get("memory").unwrap().size().unwrap(), 14);
+        assert!(!std::path::Path::new("memory").exists());
+        assert!(matches!(obj.get_file(), Err(CacheError::Io(_))));
+
+        cache.remove("memory").unwrap();
+        assert!(obj.exists());
+        drop(obj);
+        assert!(cache.try_get("memory").is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_browser_storage() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let opened = runtime.block_on(crate::BrowserStorage::open("cache-lite"));
+        assert_eq!(opened.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+
+        let storage = crate::BrowserStorage::detached();
+        assert_eq!(storage.backend(), crate::BrowserBackend::Detached);
+        let mut config = CacheConfig::default();
+        config.path.linux = "browser".to_string();
+        config.path.windows = "browser".to_string();
+        let mut cache = Cache::with_storage(config, std::sync::Arc::new(storage.clone())).unwrap();
+        cache.insert("page", b"<html>").unwrap();
+        cache.insert("style", b"body {}").unwrap();
+        assert_eq!(cache.get("page").unwrap().get_bytes().unwrap(), b"<html>");
+        assert_eq!(storage.pending(), 2);
+        cache.flush().unwrap();
+
+        assert_eq!(runtime.block_on(storage.sync()).unwrap(), 2);
+        assert_eq!(storage.pending(), 0);
+        cache.remove("style").unwrap();
+        assert_eq!(storage.pending(), 1);
+    }
+
+    #[test]
+    fn test_platform_cache_dir_placeholder() {
+        /// Puts back the directory other tests may rely on
+        struct RestorePlatformCacheDir(Option<std::path::PathBuf>);
+        impl Drop for RestorePlatformCacheDir {
+            fn drop(&mut self) {
+                crate::utils::replace_platform_cache_dir(self.0.take());
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let _restore = RestorePlatformCacheDir(crate::utils::replace_platform_cache_dir(None));
+        set_platform_cache_dir(temp_dir.path());
+        assert_eq!(platform_cache_dir().unwrap(), temp_dir.path());
+
+        let mut config = CacheConfig::default();
+        config.path.windows = "{cache_dir}/nested".to_string();
+        config.path.linux = "{cache_dir}/nested".to_string();
+        assert_eq!(config.path.android, "{cache_dir}/Rust/Cache");
+
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("mobile", None).unwrap();
+        assert!(obj.path().starts_with(temp_dir.path().join("nested")));
+    }
+
+    #[test]
+    fn test_path_resolution_order() {
+        let mut paths = CachePathConfig {
+            windows: String::new(),
+            linux: String::new(),
+            android: String::new(),
+            ios: String::new(),
+            unix: String::new(),
+            default: "/fallback".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(paths.resolve(), "/fallback");
+
+        paths.unix = "/unix".to_string();
+        paths.windows = "C:/windows".to_string();
+        #[cfg(windows)]
+        assert_eq!(paths.resolve(), "C:/windows");
+        #[cfg(all(unix, not(any(target_os = "android", target_os = "ios"))))]
+        assert_eq!(paths.resolve(), "/unix");
+
+        paths.linux = "/linux".to_string();
+        #[cfg(target_os = "linux")]
+        assert_eq!(paths.resolve(), "/linux");
+        #[cfg(target_os = "freebsd")]
+        assert_eq!(paths.resolve(), "/unix");
+
+        let mut base = CachePathConfig::default();
+        base.merge(&paths);
+        assert_eq!(base.default, "/fallback");
+        assert_eq!(base.android, "{cache_dir}/Rust/Cache");
+    }
+
+    #[test]
+    fn test_unc_paths() {
+        use crate::utils::{is_unc_path, normalize_unc_path, unc_share_root};
+
+        assert!(is_unc_path(r"\\server\share\cache"));
+        assert!(is_unc_path("//server/share/cache"));
+        assert!(!is_unc_path(r"\\?\C:\cache"));
+        assert!(!is_unc_path("C:/cache"));
+
+        // CacheConfig::new turns every backslash into a forward slash
+        let config = CacheConfig::new(r#"{"path": {"windows": "\\\\server\\share\\cache"}}"#).unwrap();
+        assert_eq!(config.path.windows, "////server//share//cache");
+        assert_eq!(normalize_unc_path(&config.path.windows), r"\\server\share\cache");
+        assert_eq!(unc_share_root("//server/share/a/b").unwrap(), r"\\server\share");
+        assert_eq!(unc_share_root("C:/cache"), None);
+    }
+
+    #[test]
+    fn test_platform_path_map() {
+        let config = CacheConfig::new(r#"{
+            "path": {
+                "platforms": {
+                    "default": "/layered/default",
+                    "plan9": "/layered/plan9"
+                }
+            }
+        }"#)
+        .unwrap();
+        assert_eq!(config.path.resolve(), "/layered/default");
+        // Built-in fields keep their defaults but rank below the map
+        assert!(!config.path.linux.is_empty());
+
+        let mut paths = config.path.clone();
+        paths.platforms.insert(std::env::consts::FAMILY.to_string(), "/layered/family".to_string());
+        assert_eq!(paths.resolve(), "/layered/family");
+        paths.platforms.insert(std::env::consts::OS.to_string(), "/layered/os".to_string());
+        assert_eq!(paths.resolve(), "/layered/os");
+
+        let mut base = CachePathConfig::default();
+        base.merge(&paths);
+        assert_eq!(base.resolve(), "/layered/os");
+        assert_eq!(base.platforms.len(), 4);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_xdg_cache_dir() {
+        let mut config = CacheConfig::default();
+        assert_eq!(config.path.linux, "{cache_dir}/Rust/Cache");
+
+        config.path.legacy_tmp_dir = true;
+        assert_eq!(
+            crate::utils::resolve_cache_dir(&config).unwrap(),
+            std::path::PathBuf::from("/tmp/Rust/Cache")
+        );
+
+        let legacy = CacheConfig::new(r#"{"path": {"legacy_tmp_dir": true}}"#).unwrap();
+        assert!(legacy.path.legacy_tmp_dir);
+    }
+
+    #[test]
+    fn test_env_var_expansion() {
+        use crate::utils::{expand_env_vars, expand_vars_with};
+
+        let env = |name: &str| (name == "CACHE_LITE_TEST_ROOT").then(|| "/env/root".to_string());
+        let expanded =
+            expand_vars_with("${CACHE_LITE_TEST_ROOT}/a/%CACHE_LITE_TEST_ROOT%", UndefinedVarPolicy::Error, env).unwrap();
+        assert_eq!(expanded, "/env/root/a//env/root");
+        assert_eq!(
+            expand_vars_with("%cache_lite_test_root%", UndefinedVarPolicy::Error, env).unwrap(),
+            "/env/root"
+        );
+        assert_eq!(expand_env_vars("100% $5 50%", UndefinedVarPolicy::Error).unwrap(), "100% $5 50%");
+
+        let result = expand_env_vars("${CACHE_LITE_UNDEFINED}/x", UndefinedVarPolicy::Error);
+        assert!(matches!(result, Err(CacheError::InvalidPath(_))));
+        assert_eq!(
+            expand_env_vars("${CACHE_LITE_UNDEFINED}/x", UndefinedVarPolicy::Passthrough).unwrap(),
+            "${CACHE_LITE_UNDEFINED}/x"
+        );
+
+        let mut config = CacheConfig::default();
+        config.path.platforms.insert("default".to_string(), "${CACHE_LITE_UNDEFINED}".to_string());
+        let mut cache = Cache::new(config).unwrap();
+        assert!(matches!(cache.create("env", None), Err(CacheError::InvalidPath(_))));
+        assert!(!cache.doctor().is_healthy());
+    }
+
+    #[test]
+    fn test_cache_scope() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let mut config = CacheConfig::default();
+        config.path.scope = CacheScope::Custom;
+        assert!(config.validate().is_err());
+        assert!(Cache::new(config).unwrap().create("scoped", None).is_err());
+
+        let mut config = temp_config(&temp_dir);
+        config.path.scope = CacheScope::System;
+        config.path.platforms.insert("default".to_string(), "{cache_dir}/App".to_string());
+        if let Some(dir) = system_cache_dir() {
+            assert_eq!(crate::utils::resolve_cache_dir(&config).unwrap(), dir.join("App"));
+        }
+
+        // Explicit paths are kept, but entries are opened up to other users
+        config.path.platforms.clear();
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("scoped", None).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(obj.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o664);
+
+            // Staged writes keep the entry open to other users
+            let mut write = obj.begin_write().unwrap();
+            write.push_chunk(b"staged").unwrap();
+            write.commit().unwrap();
+            let mut writer = obj.buffered_writer(64).unwrap();
+            std::io::Write::write_all(&mut writer, b"buffered").unwrap();
+            writer.finish().unwrap();
+            let mode = std::fs::metadata(obj.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o664);
+        }
+        assert!(obj.path().starts_with(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_cache_open() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("100% ${literal}");
+        std::fs::create_dir(&root).unwrap();
+
+        let mut cache = Cache::open(&root).unwrap();
+        assert_eq!(cache.root(), Some(root.as_path()));
+        let obj = cache.create("opened", None).unwrap();
+        assert_eq!(obj.path().parent(), Some(root.as_path()));
+        assert!(cache.doctor().check("writable").is_some_and(|c| c.status == DoctorStatus::Ok));
+
+        // Time-stamped file names are resolved through manifest.json
+        obj.write_string("lazy").unwrap();
+        std::fs::write(root.join(MANIFEST_FILE), cache.dump_manifest().unwrap()).unwrap();
+        let reopened = Cache::open(&root).unwrap();
+        let resolved = reopened.get("opened").unwrap();
+        assert_eq!((resolved.id(), resolved.path()), (obj.id(), obj.path()));
+        assert_eq!(resolved.get_string().unwrap(), "lazy");
+        assert!(reopened.get("unknown").is_err());
+
+        assert!(matches!(Cache::open(temp_dir.path().join("missing")), Err(CacheError::NotFound(_))));
+        assert!(matches!(Cache::open(obj.path()), Err(CacheError::InvalidPath(_))));
+    }
+
+    #[test]
+    fn test_create_replaces_existing() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "{id}.{name}.cache".to_string();
+        let mut cache = Cache::new(config).unwrap();
+        let old = cache.create("entry", None).unwrap();
+        old.write_string("old").unwrap();
+
+        // File names embed the id, so the old file is removed with its last handle
+        let new = cache.create("entry", None).unwrap();
+        assert_ne!(old.path(), new.path());
+        let old_path = old.path().to_path_buf();
+        drop(old);
+        assert!(!old_path.exists());
+        assert_eq!(new.get_string().unwrap(), "");
+        assert_eq!(cache.len(), 1);
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        // The same path is truncated and reused
+        cache.set_config(temp_config(&temp_dir));
+        let first = cache.create("fixed", None).unwrap();
+        first.write_string("first").unwrap();
+        let second = cache.create("fixed", None).unwrap();
+        assert_eq!(first.path(), second.path());
+        assert_eq!(second.get_string().unwrap(), "");
+
+        assert!(matches!(cache.create_new("fixed", None), Err(CacheError::AlreadyExists(_))));
+        assert!(cache.create_new("other", None).is_ok());
+    }
+
+    #[test]
+    fn test_protect_existing_files() {
+        let temp_dir = tempdir().unwrap();
+        let foreign = temp_dir.path().join("notes.cache");
+        std::fs::write(&foreign, "user data").unwrap();
+
+        let mut config = temp_config(&temp_dir);
+        config.protect_existing_files = true;
+        let mut cache = Cache::new(config).unwrap();
+        assert!(matches!(cache.create("notes", None), Err(CacheError::AlreadyExists(_))));
+        assert_eq!(std::fs::read_to_string(&foreign).unwrap(), "user data");
+
+        // Files of this cache can still be replaced
+        let obj = cache.create("own", None).unwrap();
+        obj.write_string("mine").unwrap();
+        assert!(cache.create("own", None).is_ok());
+
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("notes", None).unwrap();
+        assert_eq!(std::fs::read_to_string(&foreign).unwrap(), "");
+    }
+
+    #[test]
+    fn test_filename_collision() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "shared.cache".to_string();
+        let mut cache = Cache::new(config.clone()).unwrap();
+
+        let a = cache.create("a", None).unwrap();
+        let b = cache.create("b", None).unwrap();
+        assert_eq!(b.path(), temp_dir.path().join("shared~1.cache"));
+        a.write_string("a").unwrap();
+        b.write_string("b").unwrap();
+        assert_eq!(a.get_string().unwrap(), "a");
+
+        config.format.on_collision = CollisionPolicy::Error;
+        let mut cache = Cache::new(config).unwrap();
+        cache.create("a", None).unwrap();
+        assert!(matches!(cache.create("b", None), Err(CacheError::AlreadyExists(_))));
+        assert!(cache.create("a", None).is_ok());
+    }
+
+    #[test]
+    fn test_filename_uniqueness() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "shared.cache".to_string();
+
+        config.format.uniqueness = Some(Uniqueness::Counter);
+        std::fs::write(temp_dir.path().join("shared-1.cache"), "foreign").unwrap();
+        let mut cache = Cache::new(config.clone()).unwrap();
+        let a = cache.create("a", None).unwrap();
+        assert_eq!(a.path(), temp_dir.path().join("shared-2.cache"));
+        assert_eq!(cache.create("b", None).unwrap().path(), temp_dir.path().join("shared-3.cache"));
+
+        config.format.uniqueness = Some(Uniqueness::Uuid);
+        let mut cache = Cache::new(config.clone()).unwrap();
+        #[cfg(feature = "uuid")]
+        {
+            let a = cache.create("a", None).unwrap();
+            let b = cache.create("b", None).unwrap();
+            assert_ne!(a.path(), b.path());
+            assert!(a.path().to_string_lossy().ends_with(".cache"));
+        }
+        #[cfg(not(feature = "uuid"))]
+        {
+            assert!(config.validate().is_err());
+            assert!(matches!(cache.create("a", None), Err(CacheError::InvalidConfig(_))));
+        }
+
+        config.format.uniqueness = Some(Uniqueness::NameHash);
+        let mut cache = Cache::new(config).unwrap();
+        let a = cache.create("a", None).unwrap().path().to_path_buf();
+        assert_ne!(a, cache.create("b", None).unwrap().path());
+        assert_eq!(a, cache.create("a", None).unwrap().path());
+    }
+
+    #[test]
+    fn test_sidecar_metadata() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.metadata = MetadataMode::Sidecar;
+        let mut cache = Cache::new(config).unwrap();
+
+        let obj = cache.create("data", None).unwrap();
+        obj.write_string("payload").unwrap();
+        obj.update_metadata(|meta| {
+            meta.tags.push("images".to_string());
+            meta.ttl_secs = Some(60);
+        })
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(obj.path()).unwrap(), "payload");
+
+        let sidecar = temp_dir.path().join("data.cache.meta");
+        let stored: EntryMeta = serde_json::from_slice(&std::fs::read(&sidecar).unwrap()).unwrap();
+        assert_eq!(stored, obj.metadata().unwrap());
+        assert_eq!(stored.tags, ["images"]);
+        assert_eq!(stored.sha256.as_deref(), Some(crate::manifest::sha256_hex(b"payload").as_str()));
+
+        obj.write_bytes_vectored(&[std::io::IoSlice::new(b"pay"), std::io::IoSlice::new(b"load")]).unwrap();
+        assert_eq!(obj.metadata().unwrap().sha256, stored.sha256);
+        obj.write_at(3, b"LOAD").unwrap();
+        let sha256 = obj.metadata().unwrap().sha256;
+        assert_eq!(sha256.as_deref(), Some(crate::manifest::sha256_hex(b"payLOAD").as_str()));
+        obj.write_bytes_with_type(b"{}", "application/json").unwrap();
+        assert_eq!(obj.metadata().unwrap().content_type.as_deref(), Some("application/json"));
+        obj.write_string("payload").unwrap();
+        assert_eq!(obj.metadata().unwrap().content_type, None);
+
+        obj.set_meta("source", "https://example.com/data").unwrap();
+        obj.set_meta("schema", &(2u32, "rev")).unwrap();
+        assert_eq!(obj.get_meta::<String>("source").unwrap().as_deref(), Some("https://example.com/data"));
+        assert_eq!(obj.get_meta::<(u32, String)>("schema").unwrap(), Some((2, "rev".to_string())));
+        assert_eq!(obj.get_meta::<u32>("missing").unwrap(), None);
+        assert!(matches!(obj.get_meta::<u32>("source"), Err(CacheError::Serialization(_))));
+
+        assert_eq!(cache.index_all().unwrap(), 0);
+        cache.remove("data").unwrap();
+        drop(obj);
+        assert!(!sidecar.exists());
+
+        let plain = Cache::in_memory().create("plain", None).unwrap();
+        assert!(plain.metadata().unwrap().sha256.is_none());
+        assert!(matches!(plain.update_metadata(|_| {}), Err(CacheError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_cache_file() {
+        use std::io::{Read, Seek, SeekFrom, Write};
+
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.metadata = MetadataMode::Sidecar;
+        let mut cache = Cache::new(config).unwrap();
+        let events = cache.subscribe();
+
+        let obj = cache.create("table", None).unwrap();
+        let mut file = obj.open_file().unwrap();
+        file.write_all(b"0123456789").unwrap();
+        file.seek(SeekFrom::Start(2)).unwrap();
+        file.write_all(b"ab").unwrap();
+        assert!(file.is_dirty());
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "01ab456789");
+        file.set_len(6).unwrap();
+        assert_eq!(file.close().unwrap(), 6);
+
+        let sha256 = obj.metadata().unwrap().sha256;
+        assert_eq!(sha256.as_deref(), Some(crate::manifest::sha256_hex(b"01ab45").as_str()));
+        let sizes: Vec<_> = events.try_iter().filter_map(|event| event.size()).collect();
+        assert_eq!(sizes, [6]);
+
+        // Dropping a handle after a write reports it as well
+        obj.open_file().unwrap().write_all(b"X").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "X1ab45");
+        assert!(events.try_iter().any(|event| event.size() == Some(6)));
+        assert!(Cache::in_memory().create("memory", None).unwrap().open_file().is_err());
+    }
+
+    #[test]
+    fn test_inline_metadata() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.metadata = MetadataMode::Inline;
+        let mut cache = Cache::new(config.clone()).unwrap();
+
+        let obj = cache.create("data", None).unwrap();
+        obj.write_string("payload").unwrap();
+        obj.update_metadata(|meta| meta.tags.push("images".to_string())).unwrap();
+        obj.write_string("payload2").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "payload2");
+        assert_eq!(obj.size().unwrap(), 8);
+        let meta = obj.metadata().unwrap();
+        assert_eq!(meta.tags, ["images"]);
+        assert_eq!(meta.sha256.as_deref(), Some(crate::manifest::sha256_hex(b"payload2").as_str()));
+
+        let raw = std::fs::read(obj.path()).unwrap();
+        assert!(raw.starts_with(b"\x89CLMETA\n") && raw.ends_with(b"payload2"));
+        assert!(!temp_dir.path().join("data.cache.meta").exists());
+
+        obj.write_bytes_with_type(b"\x89PNG", "image/png").unwrap();
+        assert_eq!(obj.get_bytes_with_type().unwrap(), (b"\x89PNG".to_vec(), Some("image/png".to_string())));
+        obj.write_bytes(b"text").unwrap();
+        assert_eq!(obj.get_bytes_with_type().unwrap(), (b"text".to_vec(), None));
+        assert_eq!(obj.metadata().unwrap().tags, ["images"]);
+        assert!(matches!(
+            Cache::in_memory().create("plain", None).unwrap().write_bytes_with_type(b"", "text/plain"),
+            Err(CacheError::InvalidConfig(_))
+        ));
+
+        // Files without a header are read verbatim
+        std::fs::write(obj.path(), "plain").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "plain");
+
+        // Reading the header of a deleted entry does not bring its file back
+        let removed = cache.insert("removed", b"gone").unwrap();
+        removed.delete().unwrap();
+        assert!(removed.metadata().is_err());
+        assert!(removed.size().is_err());
+        assert!(!removed.path().exists());
+
+        config.metadata = MetadataMode::Sidecar;
+        cache.set_config(config);
+        assert_eq!(cache.get_config().metadata, MetadataMode::Inline);
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn test_purge_written_by_older_than() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.metadata = MetadataMode::Sidecar;
+        config.app = Some(AppInfo { name: "viewer".to_string(), version: "1.4.0".to_string() });
+        let mut cache = Cache::new(config.clone()).unwrap();
+        cache.create("old", None).unwrap().write_string("v1").unwrap();
+        cache.create("foreign", None).unwrap().update_metadata(|meta| {
+            meta.written_by = Some(AppInfo { name: "other".to_string(), version: "0.1.0".to_string() });
+        })
+        .unwrap();
+
+        config.app = Some(AppInfo { name: "viewer".to_string(), version: "2.1.0".to_string() });
+        cache.set_config(config);
+        let new = cache.create("new", None).unwrap();
+        new.write_string("v2").unwrap();
+        assert_eq!(new.metadata().unwrap().written_by.unwrap().version, "2.1.0");
+
+        assert_eq!(cache.purge_written_by_older_than("2.0.0").unwrap(), 1);
+        assert!(cache.get("old").is_err());
+        assert!(cache.get("foreign").is_ok() && cache.get("new").is_ok());
+        assert!(matches!(cache.purge_written_by_older_than("two"), Err(CacheError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_migrate_format() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("a", None).unwrap().write_string("A").unwrap();
+        let b = cache.create("b", None).unwrap();
+        std::fs::write(temp_dir.path().join("c.cache"), "C").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "user").unwrap();
+
+        assert!(matches!(
+            cache.migrate_format("{name}.cache", "same.data"),
+            Err(CacheError::AlreadyExists(_))
+        ));
+        assert!(b.path().exists());
+
+        // A failing rename moves the files already renamed back
+        let a = cache.get("a").unwrap();
+        std::fs::write(temp_dir.path().join("a.cache.meta"), "{}").unwrap();
+        let blocker = temp_dir.path().join(format!("v2-a-{}.data.meta", a.id()));
+        std::fs::create_dir_all(blocker.join("taken")).unwrap();
+        assert!(cache.migrate_format("{name}.cache", "v2-{name}-{id}.data").is_err());
+        assert_eq!(a.get_string().unwrap(), "A");
+        assert!(temp_dir.path().join("a.cache.meta").exists());
+        std::fs::remove_dir_all(blocker).unwrap();
+        std::fs::remove_file(temp_dir.path().join("a.cache.meta")).unwrap();
+
+        assert_eq!(cache.migrate_format("{name}.cache", "v2-{name}-{id}.data").unwrap(), 3);
+        assert!(matches!(a.write_string("stale"), Err(CacheError::NotFound(_))));
+        assert!(!temp_dir.path().join("a.cache").exists());
+        let a = cache.get("a").unwrap();
+        assert_eq!(a.path(), temp_dir.path().join(format!("v2-a-{}.data", a.id())));
+        assert_eq!(a.get_string().unwrap(), "A");
+        assert_eq!(cache.get("c").unwrap().get_string().unwrap(), "C");
+        assert!(!b.path().exists() && temp_dir.path().join("notes.txt").exists());
+        assert_eq!(cache.get_config().format.filename, "v2-{name}-{id}.data");
+        assert!(cache.create("d", None).unwrap().path().ends_with("v2-d-4.data"));
+    }
+
+    #[test]
+    fn test_max_entry_bytes() {
+        use std::io::Write;
+
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.limits.max_entry_bytes = Some(8);
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("limited", None).unwrap();
+
+        obj.write_bytes(b"12345678").unwrap();
+        let err = obj.write_bytes(b"123456789").unwrap_err();
+        assert!(matches!(err, CacheError::SizeLimitExceeded(_)));
+        assert!(err.to_string().contains("9 bytes") && err.to_string().contains("(8 bytes)"));
+        assert_eq!(obj.get_bytes().unwrap(), b"12345678");
+
+        assert!(obj.write_at(6, b"abc").is_err());
+        assert!(obj.begin_write().unwrap().push_chunk(&[0; 9]).is_err());
+        let mut writer = obj.buffered_writer(4).unwrap();
+        writer.write_all(b"1234").unwrap();
+        assert!(writer.write_all(b"56789").is_err());
+
+        obj.force_write_bytes(b"deliberately large").unwrap();
+        assert_eq!(obj.size().unwrap(), 18);
+    }
+
+    #[test]
+    fn test_low_disk_space() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        let mut cache = Cache::new(config.clone()).unwrap();
+        cache.use_shared_index().unwrap();
+        let old = cache.insert("old", b"old").unwrap().path().to_path_buf();
+        let held = cache.insert("held", b"held").unwrap();
+        cache.insert("sealed", b"sealed").unwrap().seal().unwrap();
+        let obj = cache.create("new", None).unwrap();
+        std::fs::write(temp_dir.path().join("unrelated.txt"), "keep").unwrap();
+        std::fs::write(temp_dir.path().join("stray.cache"), "keep").unwrap();
+
+        // No volume has this much free space
+        config.limits.min_free_bytes = Some(u64::MAX / 2);
+        cache.set_config(config.clone());
+        let err = obj.write_bytes(b"data").unwrap_err();
+        assert!(matches!(err, CacheError::DiskFull { required, .. } if required == u64::MAX / 2 + 4));
+        assert!(old.exists());
+
+        // Only registered objects nobody else holds are evicted
+        config.limits.on_low_space = LowSpacePolicy::Evict;
+        cache.set_config(config);
+        assert!(matches!(obj.write_bytes(b"data"), Err(CacheError::DiskFull { .. })));
+        assert!(!old.exists());
+        assert!(held.exists() && cache.get("sealed").unwrap().exists());
+        for file in ["unrelated.txt", "stray.cache", crate::INDEX_FILE] {
+            assert!(temp_dir.path().join(file).exists());
+        }
+        assert!(cache.get("old").unwrap_err().is_not_found());
+        assert_eq!(cache.gc().unwrap(), 1);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.quarantine_after = 2;
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.insert("broken", b"data").unwrap();
+
+        // A directory in place of the file fails every read
+        std::fs::remove_file(obj.path()).unwrap();
+        std::fs::create_dir(obj.path()).unwrap();
+        assert!(matches!(obj.get_bytes(), Err(CacheError::Io(_))));
+        assert!(cache.quarantined().is_empty());
+        assert!(matches!(obj.get_bytes(), Err(CacheError::Io(_))));
+        assert_eq!(cache.quarantined(), ["broken"]);
+        assert!(matches!(obj.get_bytes(), Err(CacheError::EntryNotFound { .. })));
+        assert!(cache.get("broken").unwrap_err().is_not_found());
+
+        assert!(cache.release_quarantine("broken"));
+        assert!(cache.get("broken").is_ok());
+        std::fs::remove_dir(obj.path()).unwrap();
+        obj.write_bytes(b"repaired").unwrap();
+        assert_eq!(obj.get_bytes().unwrap(), b"repaired");
+        assert!(cache.quarantined().is_empty());
+    }
+
+    #[test]
+    fn test_publish() {
+        let temp_dir = tempdir().unwrap();
+        let staging_dir = tempdir().unwrap();
+        let (root, staging) = (temp_dir.path(), staging_dir.path());
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.use_shared_index().unwrap();
+        let old_a = cache.insert("a", b"old").unwrap();
+        for (name, content) in [("a", "new a"), ("b", "new b"), ("cafe\u{301}", "new c")] {
+            std::fs::write(staging.join(name), content).unwrap();
+        }
+
+        // A failed switch of the shared index moves every file back
+        let index = std::fs::read(root.join(crate::INDEX_FILE)).unwrap();
+        std::fs::write(root.join(crate::INDEX_FILE), "{").unwrap();
+        assert!(cache.publish(staging).is_err());
+        assert_eq!(cache.get("a").unwrap().get_string().unwrap(), "old");
+        assert!(cache.try_get("b").is_none());
+        assert_eq!(std::fs::read_dir(staging).unwrap().count(), 3);
+        let publish_dirs = || {
+            std::fs::read_dir(root)
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(".publish-"))
+                .count()
+        };
+        assert_eq!(publish_dirs(), 0);
+        std::fs::write(root.join(crate::INDEX_FILE), index).unwrap();
+
+        let published = cache.publish(staging).unwrap();
+        let names: Vec<&str> = published.iter().map(|obj| obj.name()).collect();
+        assert_eq!(names, ["a", "b", "caf\u{e9}"]);
+        assert_eq!(cache.get("a").unwrap().get_string().unwrap(), "new a");
+        assert_eq!(cache["caf\u{e9}"].get_string().unwrap(), "new c");
+        assert!(published.iter().all(|obj| obj.path().parent().unwrap() == root.join(".publish-1")));
+        assert_eq!(std::fs::read_dir(staging).unwrap().count(), 0);
+        assert_eq!(cache.len(), 3);
+
+        // The replaced file stays readable until its last handle is gone
+        assert_eq!(old_a.get_string().unwrap(), "old");
+        let old_path = old_a.path().to_path_buf();
+        drop(old_a);
+        assert!(!old_path.exists());
+
+        let mut other = Cache::new(temp_config(&temp_dir)).unwrap();
+        other.use_shared_index().unwrap();
+        assert_eq!(other.get("a").unwrap().get_string().unwrap(), "new a");
+
+        // Sealed objects are not replaced
+        cache.get("b").unwrap().seal().unwrap();
+        std::fs::write(staging.join("b"), "newer b").unwrap();
+        assert!(matches!(cache.publish(staging), Err(CacheError::PermissionDenied(_))));
+        assert_eq!(cache.get("b").unwrap().get_string().unwrap(), "new b");
+        assert!(staging.join("b").exists());
+
+        // Staged names follow the name policy of the cache
+        let sanitized_dir = tempdir().unwrap();
+        let mut config = temp_config(&sanitized_dir);
+        config.name_policy = NamePolicy::Sanitize;
+        let mut sanitized = Cache::new(config).unwrap();
+        let staging_dir = tempdir().unwrap();
+        std::fs::write(staging_dir.path().join("v1..2"), "dotted").unwrap();
+        sanitized.publish(staging_dir.path()).unwrap();
+        assert_eq!(sanitized.get("v1..2").unwrap().get_string().unwrap(), "dotted");
+    }
+
+    #[test]
+    fn test_config_upgrade() {
+        let legacy = r#"{ "path": { "windows": "C:/cache", "linux": "/srv/cache" }, "max_files": 10 }"#;
+        let upgraded: serde_json::Value = serde_json::from_str(&CacheConfig::upgrade(legacy).unwrap()).unwrap();
+        assert_eq!(upgraded["version"], CONFIG_VERSION);
+        assert_eq!(upgraded["path"]["unix"], "/srv/cache");
+        assert_eq!(upgraded["max_files"], 10);
+
+        let config = CacheConfig::new(legacy).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.path.unix, "/srv/cache");
+        assert_eq!(CacheConfig::default().version, CONFIG_VERSION);
+
+        // Current configs are left alone
+        let current = r#"{ "version": 2, "path": { "linux": "/srv/cache" } }"#;
+        assert_eq!(CacheConfig::new(current).unwrap().path.unix, "");
+        assert!(matches!(CacheConfig::new(r#"{ "version": 99 }"#), Err(CacheError::ConfigParse(_))));
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        let json = r#"{
+            "version": 2,
+            "path": { "linux": "/srv/cache", "windows": "C:/cache" },
+            "max_files": 10,
+            "profiles": {
+                "dev": { "path": { "linux": "/tmp/dev-cache" } },
+                "prod": { "max_files": 1000, "retry": { "max_attempts": 3 } }
+            }
+        }"#;
+        let config = CacheConfig::new(json).unwrap();
+        let dev = config.with_profile("dev").unwrap();
+        assert_eq!(dev.path.linux, "/tmp/dev-cache");
+        assert_eq!(dev.path.windows, "C:/cache");
+        assert_eq!(dev.max_files, 10);
+        let prod = config.with_profile("prod").unwrap();
+        assert_eq!((prod.max_files, prod.retry.max_attempts, prod.retry.backoff_ms), (1000, 3, 10));
+        assert!(matches!(config.with_profile("staging"), Err(CacheError::InvalidConfig(_))));
+
+        let env = |name: &str| (name == PROFILE_ENV).then(|| "prod".to_string());
+        assert_eq!(CacheConfig::new_with(json, env).unwrap().max_files, 1000);
+        assert_eq!(CacheConfig::new_with(json, |_| None).unwrap().max_files, 10);
+    }
+
+    #[test]
+    fn test_insert_upsert_replace() {
+        let mut cache = Cache::in_memory();
+        let obj = cache.insert("entry", b"first").unwrap();
+        assert_eq!(obj.get_bytes().unwrap(), b"first");
+        assert!(matches!(cache.insert("entry", b"again"), Err(CacheError::AlreadyExists(_))));
+        assert_eq!(obj.get_bytes().unwrap(), b"first");
+
+        let same = cache.upsert("entry", b"second").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&obj, &same));
+        assert_eq!(obj.get_bytes().unwrap(), b"second");
+        cache.upsert("fresh", b"new").unwrap();
+        assert_eq!(cache.get("fresh").unwrap().get_bytes().unwrap(), b"new");
+
+        cache.replace("entry", b"third").unwrap();
+        assert_eq!(obj.get_bytes().unwrap(), b"third");
+        assert!(matches!(cache.replace("missing", b"x"), Err(CacheError::EntryNotFound { .. })));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_filename_template() {
+        let template = template::FilenameTemplate::new("{id}-{name}{x}.{time}", "%Y");
+        let year = chrono::Local::now().format("%Y").to_string();
+        assert_eq!(template.format("entry", 7), format!("7-entry{{x}}.{}", year));
+        assert_eq!(template.format("other", 8), format!("8-other{{x}}.{}", year));
+
+        let temp_dir = tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("first", None).unwrap();
+        assert!(obj.path().starts_with(temp_dir.path()));
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "{name}.bin".to_string();
+        cache.set_config(config);
+        let obj = cache.create("second", None).unwrap();
+        assert_eq!(obj.path(), temp_dir.path().join("second.bin"));
+    }
+
+    #[test]
+    fn test_cache_open_lazy_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for name in ["first", "second"] {
+            std::fs::write(temp_dir.path().join(format!("{}.cache", name)), name).unwrap();
+        }
+        std::fs::write(temp_dir.path().join("unrelated.txt"), "").unwrap();
+
+        let mut cache = Cache::open(temp_dir.path()).unwrap();
+        cache.set_config(temp_config(&temp_dir));
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get("second").unwrap().get_string().unwrap(), "second");
+        assert!(matches!(cache.get("missing"), Err(CacheError::EntryNotFound { .. })));
+
+        assert_eq!(cache.index_all().unwrap(), 2);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.index_all().unwrap(), 0);
+        assert_eq!(cache.create("third", None).unwrap().id(), 3);
+    }
+
+    #[test]
+    fn test_cache_manager() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager = CacheManager::new();
+
+        let thumbnails = manager.register("thumbnails", temp_config(&temp_dir)).unwrap();
+        thumbnails.create("a", None).unwrap().write_bytes(b"1234").unwrap();
+        let mut http = Cache::in_memory();
+        http.create("b", None).unwrap().write_bytes(b"56").unwrap();
+        manager.insert("http", http).unwrap();
+        assert!(manager.register("http", CacheConfig::default()).is_err());
+
+        assert_eq!(manager.iter().map(|(name, _)| name).collect::<Vec<_>>(), ["http", "thumbnails"]);
+        assert_eq!(manager.stats(), CacheStats { objects: 2, bytes: 6, throttle: None });
+
+        manager.clear_all().unwrap();
+        assert_eq!(manager.stats(), CacheStats::default());
+        assert!(manager.unregister("http").is_some());
+        assert_eq!(manager.len(), 1);
+    }
+
+    #[cfg(feature = "global")]
+    #[test]
+    fn test_global_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        init_global(temp_config(&temp_dir)).unwrap();
+        assert!(init_global(CacheConfig::default()).is_err());
+
+        let obj = global().lock().unwrap().create("global", None).unwrap();
+        assert!(obj.path().starts_with(temp_dir.path()));
+        assert!(global().lock().unwrap().get("global").is_ok());
+    }
+
+    #[test]
+    fn test_cache_fork() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let branch_dir = tempfile::tempdir().unwrap();
+        let mut base = Cache::new(temp_config(&base_dir)).unwrap();
+        base.create("a", None).unwrap().write_string("baseline").unwrap();
+        base.create("b", None).unwrap();
+
+        let mut branch = base.fork(temp_config(&branch_dir)).unwrap();
+        assert_eq!(branch.len(), 2);
+        let a = branch.get("a").unwrap();
+        assert!(a.path().starts_with(branch_dir.path()));
+        assert_eq!(a.get_string().unwrap(), "baseline");
+
+        a.write_string("branch").unwrap();
+        branch.remove("b").unwrap();
+        assert_eq!(base.get("a").unwrap().get_string().unwrap(), "baseline");
+        assert!(base.get("b").unwrap().exists());
+
+        // Invalidated objects are not carried over
+        base.bump_generation();
+        base.create("c", None).unwrap();
+        let next_dir = tempfile::tempdir().unwrap();
+        let next = base.fork(temp_config(&next_dir)).unwrap();
+        assert_eq!(next.len(), 1);
+        assert!(next.try_get("a").is_none());
+    }
+
+    #[test]
+    fn test_cache_merge_from() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut worker = Cache::in_memory();
+        worker.create("shared", None).unwrap().write_string("worker").unwrap();
+        worker.create("own", None).unwrap().write_string("own").unwrap();
+
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("shared", None).unwrap().write_string("local").unwrap();
+
+        assert_eq!(cache.merge_from(&worker, ConflictPolicy::Skip).unwrap(), 1);
+        assert_eq!(cache.get("shared").unwrap().get_string().unwrap(), "local");
+        assert_eq!(cache.get("own").unwrap().get_string().unwrap(), "own");
+
+        assert_eq!(cache.merge_from(&worker, ConflictPolicy::Rename).unwrap(), 2);
+        assert_eq!(cache.get("shared-1").unwrap().get_string().unwrap(), "worker");
+        assert!(cache.get("own-1").is_ok());
+
+        assert_eq!(cache.merge_from(&worker, ConflictPolicy::Overwrite).unwrap(), 2);
+        assert_eq!(cache.get("shared").unwrap().get_string().unwrap(), "worker");
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn test_cache_sync_to() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let a = cache.create("a", None).unwrap();
+        a.write_string("one").unwrap();
+        cache.create("b", None).unwrap().write_string("two").unwrap();
+
+        assert_eq!(cache.sync_to(mirror_dir.path()).unwrap(), 2);
+        assert_eq!(cache.sync_to(mirror_dir.path()).unwrap(), 0);
+
+        a.write_string("changed").unwrap();
+        assert_eq!(cache.sync_to(mirror_dir.path()).unwrap(), 1);
+        let mirrored = mirror_dir.path().join(a.path().file_name().unwrap());
+        assert_eq!(std::fs::read_to_string(mirrored).unwrap(), "changed");
+
+        let mut memory = Cache::in_memory();
+        memory.create("m", None).unwrap().write_string("mem").unwrap();
+        assert_eq!(memory.sync_to(mirror_dir.path()).unwrap(), 1);
+        assert_eq!(memory.sync_to(mirror_dir.path()).unwrap(), 0);
+
+        // Sidecars are mirrored with their entries
+        let sidecar_dir = tempfile::tempdir().unwrap();
+        let mut config = temp_config(&sidecar_dir);
+        config.metadata = MetadataMode::Sidecar;
+        let mut cache = Cache::new(config).unwrap();
+        let tagged = cache.insert("tagged", b"content").unwrap();
+        let sidecar_mirror = tempfile::tempdir().unwrap();
+        assert_eq!(cache.sync_to(sidecar_mirror.path()).unwrap(), 2);
+        assert_eq!(cache.sync_to(sidecar_mirror.path()).unwrap(), 0);
+        let file_name = tagged.path().file_name().unwrap().to_string_lossy().into_owned();
+        let mirrored_sidecar = sidecar_mirror.path().join(format!("{file_name}.meta"));
+        assert_eq!(std::fs::read(mirrored_sidecar).unwrap(), std::fs::read(crate::meta::sidecar_path(tagged.path())).unwrap());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_cache_push_pull() {
+        use std::collections::HashMap;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        // Minimal object store answering PUT and GET, one request per connection
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cache", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let mut store: HashMap<String, Vec<u8>> = HashMap::new();
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((key, value)) = header.split_once(':')
+                        && key.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let response = match method.as_str() {
+                    "PUT" => {
+                        store.insert(path, body);
+                        Vec::new()
+                    }
+                    _ => store.get(&path).cloned().unwrap_or_default(),
+                };
+                let mut stream = reader.into_inner();
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", response.len()).unwrap();
+                stream.write_all(&response).unwrap();
+            }
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("a b", None).unwrap().write_string("first").unwrap();
+        cache.create("c", None).unwrap().write_string("second").unwrap();
+        assert_eq!(cache.push(&url).unwrap(), 2);
+
+        let mut runner = Cache::in_memory();
+        runner.create("c", None).unwrap().write_string("stale").unwrap();
+        assert_eq!(runner.pull(&url).unwrap(), 2);
+        assert_eq!(runner.get("a b").unwrap().get_string().unwrap(), "first");
+        assert_eq!(runner.get("c").unwrap().get_string().unwrap(), "second");
+    }
+
+    #[test]
+    fn test_cache_base_layer() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let local_dir = tempfile::tempdir().unwrap();
+        let mut base = Cache::new(temp_config(&base_dir)).unwrap();
+        base.create("shared", None).unwrap().write_string("team").unwrap();
+
+        let mut cache = Cache::new(temp_config(&local_dir)).unwrap();
+        cache.set_base(base_dir.path()).unwrap();
+        let shared = cache.create("shared", None).unwrap();
+        let local_only = cache.create("local", None).unwrap();
+        assert!(!shared.path().exists());
+        assert_eq!(shared.get_string().unwrap(), "team");
+        assert_eq!(shared.size().unwrap(), 4);
+        assert!(local_only.exists());
+
+        shared.write_string("mine").unwrap();
+        assert_eq!(shared.get_string().unwrap(), "mine");
+        assert_eq!(base.get("shared").unwrap().get_string().unwrap(), "team");
+
+        base.create("unknown", None).unwrap().write_string("base").unwrap();
+        let unknown = cache.get("unknown").unwrap();
+        assert_eq!(unknown.get_string().unwrap(), "base");
+        let mut file = unknown.get_file().unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut file, &mut content).unwrap();
+        assert_eq!(content, "base");
+        assert!(unknown.path().exists());
+        assert_eq!(base.get("unknown").unwrap().get_string().unwrap(), "base");
+
+        let mut timed = Cache::new(CacheConfig::default()).unwrap();
+        assert!(timed.set_base(base_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_cache_manifest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("a", None).unwrap().write_string("hello").unwrap();
+        cache.create("b", None).unwrap();
+
+        let manifest = cache.dump_manifest().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(json["entries"][0]["name"], "a");
+        assert_eq!(
+            json["entries"][0]["sha256"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        let mut imported = Cache::in_memory();
+        assert!(matches!(imported.import_manifest("{"), Err(CacheError::Corrupted(_))));
+        let mut imported = Cache::new(temp_config(&temp_dir)).unwrap();
+        assert_eq!(imported.import_manifest(&manifest).unwrap(), 2);
+        assert_eq!(imported.get("a").unwrap().get_string().unwrap(), "hello");
+        assert_eq!(imported.create("c", None).unwrap().id(), 3);
+
+        std::fs::write(temp_dir.path().join(MANIFEST_FILE), &manifest).unwrap();
+        let mut opened = Cache::open(temp_dir.path()).unwrap();
+        assert_eq!(opened.len(), 0);
+        assert_eq!(opened.index_all().unwrap(), 2);
+        assert_eq!(opened.len(), 2);
+
+        let mut escaping = json.clone();
+        escaping["entries"][1]["path"] = serde_json::json!("../outside.cache");
+        let mut rejected = Cache::new(temp_config(&temp_dir)).unwrap();
+        let err = rejected.import_manifest(&escaping.to_string()).unwrap_err();
+        assert!(matches!(err, CacheError::InvalidPath(_)));
+        assert!(rejected.is_empty());
+        let mut overflowing = json.clone();
+        overflowing["entries"][1]["id"] = serde_json::json!(u32::MAX);
+        let err = rejected.import_manifest(&overflowing.to_string()).unwrap_err();
+        assert!(matches!(err, CacheError::Corrupted(_)));
+        assert!(rejected.is_empty());
+
+        cache.get("a").unwrap().write_string("tampered").unwrap();
+        let mut rejected = Cache::new(temp_config(&temp_dir)).unwrap();
+        assert!(matches!(rejected.import_manifest(&manifest), Err(CacheError::Corrupted(_))));
+    }
+
+    #[cfg(feature = "hmac")]
+    #[test]
+    fn test_signed_manifest() {
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.create("a", None).unwrap().write_string("bundle").unwrap();
+        let unsigned = cache.dump_manifest().unwrap();
+
+        cache.set_manifest_signer(Arc::new(HmacSha256Signer::new("secret")));
+        let signed = cache.dump_manifest().unwrap();
+        assert!(signed.contains("hmac-sha256"));
+
+        let mut consumer = Cache::new(temp_config(&temp_dir)).unwrap();
+        consumer.set_manifest_signer(Arc::new(HmacSha256Signer::new("secret")));
+        assert!(matches!(consumer.import_manifest(&unsigned), Err(CacheError::Corrupted(_))));
+        let forged = signed.replace("\"a\"", "\"b\"");
+        assert!(matches!(consumer.import_manifest(&forged), Err(CacheError::Corrupted(_))));
+        assert_eq!(consumer.import_manifest(&signed).unwrap(), 1);
+
+        let mut other_key = Cache::in_memory();
+        other_key.set_manifest_signer(Arc::new(HmacSha256Signer::new("other")));
+        assert!(other_key.import_manifest(&signed).is_err());
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_cache_watch() {
+        use std::time::Duration;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        // Entries picked up later belong to the current generation
+        cache.bump_generation();
+        let watched = cache.create("watched", None).unwrap();
+        cache.watch().unwrap();
+        let events = cache.subscribe();
+
+        // Another process rewrites one entry and adds a new one
+        std::fs::write(watched.path(), "external").unwrap();
+        std::fs::write(temp_dir.path().join("added.cache"), "new").unwrap();
+        let changed = std::iter::from_fn(|| events.recv_timeout(Duration::from_secs(5)).ok())
+            .find(|event| matches!(event, CacheEvent::Changed { .. }))
+            .unwrap();
+        assert_eq!(changed.name(), "watched");
+
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(cache.refresh() >= 1);
+        assert_eq!(cache.get("added").unwrap().get_string().unwrap(), "new");
+
+        std::fs::remove_file(watched.path()).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        cache.refresh();
+        assert!(cache.get("watched").is_err());
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_cache_on_invalidated() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let settings = cache.create("settings.ui", None).unwrap();
+        let other = cache.create("other", None).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        cache
+            .on_invalidated("settings.*", move |event| {
+                let _ = tx.lock().unwrap().send(event.clone());
+            })
+            .unwrap();
+        assert!(cache.on_invalidated("[", |_| {}).is_err());
+        cache.watch().unwrap();
+
+        settings.write_string("own write").unwrap();
+        std::fs::write(other.path(), "external").unwrap();
+        std::fs::write(settings.path(), "external").unwrap();
+
+        let event = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event, CacheEvent::Changed { name: "settings.ui".to_string(), id: settings.id() });
+    }
+
+    #[test]
+    fn test_cache_object_depends_on() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("config.toml");
+        std::fs::write(&source, "a = 1").unwrap();
+
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let parsed = cache.create("parsed", None).unwrap();
+        assert!(parsed.depends_on(temp_dir.path().join("missing")).is_err());
+        parsed.depends_on(&source).unwrap();
+        assert!(!parsed.is_stale());
+        assert_eq!(cache.get("parsed").unwrap().dependencies(), vec![source.clone()]);
+
+        // Same content with a new modification time is not stale
+        let file = std::fs::File::options().write(true).open(&source).unwrap();
+        file.set_modified(std::time::SystemTime::UNIX_EPOCH).unwrap();
+        assert!(!parsed.is_stale());
+
+        std::fs::write(&source, "a = 2").unwrap();
+        assert!(cache.get("parsed").unwrap().is_stale());
+
+        let mut imported = Cache::new(temp_config(&temp_dir)).unwrap();
+        imported.import_manifest(&cache.dump_manifest().unwrap()).unwrap();
+        assert!(imported.get("parsed").unwrap().is_stale());
+
+        parsed.depends_on(&source).unwrap();
+        assert!(!parsed.is_stale());
+        std::fs::remove_file(&source).unwrap();
+        assert!(parsed.is_stale());
+    }
+
+    #[test]
+    fn test_cache_dependency_graph() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let source = cache.create("source", None).unwrap();
+        let derived = cache.create("derived", None).unwrap();
+        let final_obj = cache.create("final", None).unwrap();
+        cache.add_dependency("derived", "source").unwrap();
+        cache.add_dependency("final", "derived").unwrap();
+        assert!(cache.add_dependency("source", "final").is_err());
+        assert!(cache.add_dependency("derived", "missing").is_err());
+        assert_eq!(cache.dependents("source"), ["derived"]);
+
+        source.write_string("v2").unwrap();
+        assert!(derived.is_stale() && final_obj.is_stale());
+        derived.write_string("rebuilt").unwrap();
+        assert!(!derived.is_stale() && final_obj.is_stale());
+        final_obj.write_string("rebuilt").unwrap();
+        assert!(!final_obj.is_stale());
+
+        cache.remove("source").unwrap();
+        assert!(derived.is_stale());
+        assert_eq!(cache.len(), 2);
+
+        cache.set_cascade_policy(CascadePolicy::Delete);
+        cache.remove("derived").unwrap();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_cache_generations() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.format.filename = "{name}-{id}.cache".to_string();
+        let mut cache = Cache::new(config).unwrap();
+        let old = cache.create("thumbs.a", None).unwrap();
+        cache.create("http.a", None).unwrap();
+
+        assert_eq!(cache.bump_namespace_generation("thumbs."), 1);
+        assert!(cache.get("thumbs.a").is_err());
+        assert!(cache.get("http.a").is_ok());
+        assert_eq!(cache.len(), 1);
+
+        let new = cache.create("thumbs.a", None).unwrap();
+        assert_eq!(new.generation(), 1);
+        assert!(old.path().exists());
+
+        cache.bump_generation();
+        assert!(cache.is_empty());
+        assert_eq!(cache.gc().unwrap(), 3);
+        let paths = [old.path().to_path_buf(), new.path().to_path_buf()];
+        drop((old, new));
+        assert!(paths.iter().all(|path| !path.exists()));
+        assert_eq!(cache.gc().unwrap(), 0);
+
+        // A manifest brings invalidated names back in the current generation
+        cache.insert("kept", b"v").unwrap();
+        let manifest = cache.dump_manifest().unwrap();
+        cache.bump_generation();
+        assert_eq!(cache.import_manifest(&manifest).unwrap(), 1);
+        assert_eq!(cache.get("kept").unwrap().get_bytes().unwrap(), b"v");
+        assert_eq!(cache.gc().unwrap(), 1);
+        assert_eq!(cache.get("kept").unwrap().get_bytes().unwrap(), b"v");
+    }
+
+    #[test]
+    fn test_cache_remove_prefix() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let a = cache.create("user_42_avatar", None).unwrap();
+        cache.create("user_42_feed", None).unwrap();
+        cache.create("user_420_feed", None).unwrap();
+
+        assert_eq!(cache.remove_prefix("user_42_").unwrap(), 2);
+        let path = a.path().to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.remove_prefix("none_").unwrap(), 0);
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn test_cache_glob_matching() {
+        let mut cache = Cache::in_memory();
+        for name in ["thumb_1", "thumb_2", "thumbnail", "page_1"] {
+            cache.create(name, None).unwrap();
+        }
+
+        let mut names: Vec<&str> = cache.iter_matching("thumb_?").unwrap().map(|obj| obj.name()).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["thumb_1", "thumb_2"]);
+        assert!(cache.iter_matching("[").is_err());
+
+        assert_eq!(cache.remove_matching("thumb*").unwrap(), 3);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_cache_find_regex() {
+        let mut cache = Cache::in_memory();
+        for name in ["build-2024-01", "build-2025-07", "build-latest"] {
+            cache.create(name, None).unwrap();
+        }
+
+        let pattern = regex::Regex::new(r"^build-\d{4}-\d{2}$").unwrap();
+        let mut names: Vec<&str> = cache.find_regex(&pattern).map(|obj| obj.name()).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["build-2024-01", "build-2025-07"]);
+    }
+
+    #[test]
+    fn test_streamed_writes() {
+        use std::io::Write;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let events = cache.subscribe();
+        let written = || {
+            events.try_iter().find_map(|event| match event {
+                CacheEvent::Written { size, .. } => Some(size),
+                _ => None,
+            })
+        };
+        let obj = cache.create("stream", None).unwrap();
+        obj.write_string("previous content").unwrap();
+        assert_eq!(written(), Some(16));
+
+        let mut writer = obj.buffered_writer(4).unwrap();
+        for chunk in ["hello", ", ", "world"] {
+            writer.write_all(chunk.as_bytes()).unwrap();
+        }
+        let summary = writer.finish().unwrap();
+        assert_eq!(summary.size, 12);
+        assert_eq!(summary.sha256, crate::manifest::sha256_hex(b"hello, world"));
+        assert_eq!(obj.get_string().unwrap(), "hello, world");
+        assert_eq!(written(), Some(12));
+
+        let (header, body) = (b"v1\n".as_slice(), b"payload".as_slice());
+        obj.write_bytes_vectored(&[std::io::IoSlice::new(header), std::io::IoSlice::new(body)]).unwrap();
+        assert_eq!(obj.get_string().unwrap(), "v1\npayload");
+        assert_eq!(written(), Some(10));
+
+        let mut source = std::io::Cursor::new(vec![7u8; 100_000]);
+        assert_eq!(obj.copy_from_reader_with(&mut source, 4096).unwrap(), 100_000);
+        let mut copy = Vec::new();
+        assert_eq!(obj.copy_to_writer(&mut copy).unwrap(), 100_000);
+        assert_eq!(copy, vec![7u8; 100_000]);
+        obj.write_bytes_vectored(&[std::io::IoSlice::new(header), std::io::IoSlice::new(body)]).unwrap();
+
+        #[cfg(feature = "bytes")]
+        {
+            let shared = obj.get_bytes_shared().unwrap();
+            let clone = shared.clone();
+            assert_eq!(&clone[..], b"v1\npayload");
+            assert_eq!(shared.as_ptr(), clone.as_ptr());
+        }
+
+        let mut memory = Cache::in_memory();
+        let obj = memory.create("stream", None).unwrap();
+        obj.write_bytes_vectored(&[std::io::IoSlice::new(header), std::io::IoSlice::new(body)]).unwrap();
+        assert_eq!(obj.get_string().unwrap(), "v1\npayload");
+        let mut writer = obj.buffered_writer(1024).unwrap();
+        writer.write_all(b"collected").unwrap();
+        assert!(!obj.get_bytes().unwrap().starts_with(b"collected"));
+        writer.finish().unwrap();
+        assert_eq!(obj.get_string().unwrap(), "collected");
+        obj.copy_from_reader(&mut "from reader".as_bytes()).unwrap();
+        let mut copy = Vec::new();
+        obj.copy_to_writer(&mut copy).unwrap();
+        assert_eq!(copy, b"from reader");
+    }
+
+    #[test]
+    fn test_chunked_write() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("artifact", None).unwrap();
+        obj.write_string("old").unwrap();
+        let staging = temp_dir.path().join("artifact.cache.partial");
+
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&progress);
+        let mut write = obj
+            .begin_write()
+            .unwrap()
+            .on_progress(move |written| recorded.lock().unwrap().push(written));
+        write.push_chunk(b"new ").unwrap();
+        write.push_chunk(b"content").unwrap();
+        assert!(staging.exists());
+        assert_eq!(obj.get_string().unwrap(), "old");
+        assert_eq!(write.commit().unwrap().size, 11);
+        assert_eq!(*progress.lock().unwrap(), [4, 11]);
+        assert_eq!(obj.get_string().unwrap(), "new content");
+        assert!(!staging.exists());
+
+        let mut interrupted = obj.begin_write().unwrap();
+        interrupted.push_chunk(b"partial").unwrap();
+        drop(interrupted);
+        assert!(!staging.exists());
+        assert_eq!(obj.get_string().unwrap(), "new content");
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_mut() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("bitmap", None).unwrap();
+        obj.write_bytes(&[1, 2]).unwrap();
+
+        let mut map = unsafe { obj.mmap_mut(8) }.unwrap();
+        assert_eq!(&map[..], [1, 2, 0, 0, 0, 0, 0, 0]);
+        map[7] = 0xff;
+        map.flush_range(7, 1).unwrap();
+        drop(map);
+        assert_eq!(obj.get_bytes().unwrap(), [1, 2, 0, 0, 0, 0, 0, 0xff]);
+
+        let memory = Cache::in_memory().create("bitmap", None).unwrap();
+        assert!(unsafe { memory.mmap_mut(8) }.is_err());
+    }
+
+    #[test]
+    fn test_reserve() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("download", None).unwrap();
+        obj.write_bytes(b"head").unwrap();
+
+        obj.reserve(64 * 1024).unwrap();
+        assert_eq!(obj.size().unwrap(), 64 * 1024);
+        assert!(obj.get_bytes().unwrap().starts_with(b"head"));
+        assert!(matches!(obj.reserve(u64::MAX / 2), Err(CacheError::DiskFull { .. } | CacheError::Io(_))));
+
+        let memory = Cache::in_memory().create("download", None).unwrap();
+        memory.reserve(1024).unwrap();
+    }
+
+    #[test]
+    fn test_sparse_write_at() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        let obj = cache.create("pieces", None).unwrap();
+
+        let piece = vec![1u8; 4096];
+        obj.write_at(16 * 1024 * 1024, &piece).unwrap();
+        obj.write_at(0, b"head").unwrap();
+        assert_eq!(obj.size().unwrap(), 16 * 1024 * 1024 + 4096);
+        assert!(obj.allocated_size().unwrap() > 0);
+        #[cfg(target_os = "linux")]
+        assert!(obj.allocated_size().unwrap() < obj.size().unwrap());
+
+        let memory = Cache::in_memory().create("pieces", None).unwrap();
+        memory.write_at(4, b"tail").unwrap();
+        memory.write_at(0, b"he").unwrap();
+        assert_eq!(memory.get_bytes().unwrap(), b"he\0\0tail");
+        assert_eq!(memory.allocated_size().unwrap(), 8);
+        assert!(matches!(memory.write_at(u64::MAX, b"x"), Err(CacheError::SizeLimitExceeded(_))));
+
+        // A failed read of the existing content leaves it alone
+        #[derive(Debug, Default)]
+        struct Unreadable {
+            inner: MemoryStorage,
+            failing: std::sync::atomic::AtomicBool,
+        }
+
+        impl Storage for Unreadable {
+            fn read(&self, path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+                if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                    return Err(std::io::ErrorKind::PermissionDenied.into());
+                }
+                self.inner.read(path)
+            }
+            fn write(&self, path: &std::path::Path, content: &[u8]) -> std::io::Result<()> {
+                self.inner.write(path, content)
+            }
+            fn remove(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.remove(path)
+            }
+            fn exists(&self, path: &std::path::Path) -> bool {
+                self.inner.exists(path)
+            }
+            fn size(&self, path: &std::path::Path) -> std::io::Result<u64> {
+                self.inner.size(path)
+            }
+            fn create_dir_all(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.create_dir_all(path)
+            }
+            fn create_file(&self, path: &std::path::Path) -> std::io::Result<()> {
+                self.inner.create_file(path)
+            }
+        }
+
+        let storage = std::sync::Arc::new(Unreadable::default());
+        let mut cache = Cache::with_storage(temp_config(&temp_dir), storage.clone()).unwrap();
+        let obj = cache.insert("guarded", b"original").unwrap();
+        storage.failing.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(matches!(obj.write_at(2, b"XY"), Err(CacheError::Io(_))));
+        storage.failing.store(false, std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(obj.get_bytes().unwrap(), b"original");
+    }
+
+    #[test]
+    fn test_io_tuning() {
+        let mut buffer = AlignedBuffer::from_slice(b"aligned");
+        assert_eq!(buffer.as_ptr() as usize % DIRECT_IO_ALIGN, 0);
+        assert_eq!(buffer.padded().len(), DIRECT_IO_ALIGN);
+        buffer.truncate(3);
+        assert_eq!(&buffer[..], b"ali");
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config = temp_config(&temp_dir);
+        config.io.direct = true;
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("direct", None).unwrap();
+        let content = vec![42u8; 3 * DIRECT_IO_ALIGN + 17];
+        obj.write_bytes(&content).unwrap();
+        assert_eq!(obj.size().unwrap(), content.len() as u64);
+        assert_eq!(obj.get_bytes().unwrap(), content);
+        obj.write_string("short").unwrap();
+        assert_eq!(obj.get_string().unwrap(), "short");
+
+        let config: CacheConfig =
+            serde_json::from_str(r#"{"io": {"advise": "dontneed"}}"#).unwrap();
+        assert_eq!(config.io.advise, IoAdvice::DontNeed);
+        let mut config = temp_config(&temp_dir);
+        config.io.advise = IoAdvice::DontNeed;
+        let mut cache = Cache::new(config).unwrap();
+        let obj = cache.create("advised", None).unwrap();
+        let content = vec![7u8; 2 * 1024 * 1024];
+        obj.write_bytes(&content).unwrap();
+        assert_eq!(obj.get_bytes().unwrap(), content);
+
+        let config: CacheConfig =
+            serde_json::from_str(r#"{"io": {"read_buffer": 4096, "write_buffer": 8192}}"#).unwrap();
+        assert_eq!((config.io.read_buffer, config.io.write_buffer), (4096, 8192));
+        assert!(!config.io.direct);
+        let mut cache = Cache::in_memory();
+        cache.set_config(config);
+        let obj = cache.create("buffered", None).unwrap();
+        assert_eq!(obj.buffer_sizes(), (4096, 8192));
+        assert_eq!(obj.copy_from_reader(&mut &content[..]).unwrap(), content.len() as u64);
+        let mut copied = Vec::new();
+        obj.copy_to_writer(&mut copied).unwrap();
+        assert_eq!(copied, content);
+    }
+
+    #[test]
+    fn test_parallel_clear_and_gc() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache = Cache::new(temp_config(&temp_dir)).unwrap();
+        cache.set_delete_parallelism(3);
+        let paths: Vec<_> = (0..200)
+            .map(|i| cache.create(&format!("entry{}", i), None).unwrap().path().to_path_buf())
+            .collect();
+
+        cache.bump_generation();
+        cache.create("entry0", None).unwrap();
+        assert_eq!(cache.gc().unwrap(), 200);
+        assert_eq!(paths.iter().filter(|path| path.exists()).count(), 1);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        assert!(!paths[0].exists());
+    }
+
+    #[test]
+    fn test_cache_pages() {
+        let mut cache = Cache::in_memory();
+        for i in (0..25).rev() {
+            cache.create(&format!("entry_{:02}", i), None).unwrap();
+        }
+
+        let first = cache.page(None, 10);
+        assert_eq!(first.objects.len(), 10);
+        assert_eq!(first.objects[0].name(), "entry_00");
+        assert_eq!(first.next_cursor.as_deref(), Some("entry_09"));
+
+        let pages: Vec<Vec<std::sync::Arc<CacheObject>>> = cache.iter_pages(10).collect();
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [10, 10, 5]);
+        assert_eq!(pages[2][4].name(), "entry_24");
+        assert!(cache.page(Some("entry_20"), 10).next_cursor.is_none());
+        assert_eq!(Cache::in_memory().iter_pages(10).count(), 0);
+    }
+
+    #[cfg(feature = "concurrent")]
+    #[test]
+    fn test_shared_cache() {
+        let shared = std::sync::Arc::new(SharedCache::new(Cache::in_memory()));
+        shared.create("config", None).unwrap().write_string("{}").unwrap();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = std::sync::Arc::clone(&shared);
+                std::thread::spawn(move || (0..100).all(|_| shared.get("config").is_ok()))
+            })
+            .collect();
+        assert!(readers.into_iter().all(|reader| reader.join().unwrap()));
+
+        shared.create("derived", None).unwrap();
+        shared.with_cache(|cache| cache.add_dependency("derived", "config")).unwrap();
+        shared.with_cache(|cache| cache.set_cascade_policy(CascadePolicy::Delete));
+        shared.remove("config").unwrap();
+        assert!(shared.is_empty());
+
+        shared.create("next", None).unwrap();
+        shared.with_cache(Cache::bump_generation);
+        assert!(matches!(shared.get("next"), Err(CacheError::EntryNotFound { .. })));
+        assert_eq!(std::sync::Arc::into_inner(shared).unwrap().into_inner().len(), 0);
+    }
 }
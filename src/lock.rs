@@ -0,0 +1,277 @@
+/*
+ * @filename: lock.rs
+ * @description: Cross-process lock files that detect and break stale owners
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::{CacheError, CacheResult};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Lock file of `Cache::claim_exclusive()`
+pub(crate) const CLAIM_FILE: &str = "cache.lock";
+
+/// Heartbeat age after which a directory claim is considered hung
+pub(crate) const CLAIM_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Delay between attempts to take a busy lock
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Process holding a lock, recorded in the lock file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LockOwner {
+    pub(crate) pid: u32,
+    pub(crate) host: String,
+    /// Last time the owner confirmed it holds the lock, in Unix seconds
+    pub(crate) heartbeat: u64,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        LockOwner {
+            pid: std::process::id(),
+            host: host_name(),
+            heartbeat: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        }
+    }
+
+    /// Reads the owner recorded in an open lock file, if any
+    fn read(mut file: &File) -> Option<Self> {
+        let mut record = Vec::new();
+        file.seek(SeekFrom::Start(0)).ok()?;
+        file.read_to_end(&mut record).ok()?;
+        serde_json::from_slice(&record).ok()
+    }
+
+    /// Records this process as the owner of a lock file, with the current time
+    fn write(mut file: &File) -> CacheResult<()> {
+        let record = serde_json::to_vec(&LockOwner::current()).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&record)?;
+        file.set_len(record.len() as u64)?;
+        Ok(())
+    }
+
+    /// Checks whether the owner is gone or stopped confirming the lock
+    ///
+    /// Processes are only checked on the host that runs them; the
+    /// heartbeat catches owners on other hosts and hung processes.
+    fn is_stale(&self, stale_after: Option<Duration>) -> bool {
+        if self.host == host_name() && process_alive(self.pid) == Some(false) {
+            return true;
+        }
+        stale_after.is_some_and(|stale_after| {
+            let heartbeat = UNIX_EPOCH + Duration::from_secs(self.heartbeat);
+            SystemTime::now().duration_since(heartbeat).is_ok_and(|age| age > stale_after)
+        })
+    }
+}
+
+/// Exclusive lock on a file, shared by every process using it
+///
+/// The lock is an OS file lock, released when the holder exits. The
+/// lock file also records the holder, so a lock left behind by a
+/// process that died without the OS releasing it (e.g. on a network
+/// share) or by a hung process is broken instead of blocking every
+/// later user: the file is replaced, and the lock is taken on the new
+/// file. Short holds record the heartbeat once; long holds use
+/// `keep_alive()` so a background thread keeps it fresh. Dropping the
+/// lock removes the file, unless the lock was broken and the path
+/// already belongs to the next holder.
+#[derive(Debug)]
+pub(crate) struct FileLock {
+    file: Arc<File>,
+    path: PathBuf,
+    heartbeat: Option<Heartbeat>,
+}
+
+/// Thread refreshing the heartbeat of a held lock
+#[derive(Debug)]
+struct Heartbeat {
+    /// Dropped to stop the thread
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl Heartbeat {
+    /// Refreshes the owner record of `file` several times per `stale_after`
+    fn start(file: Arc<File>, stale_after: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let interval = stale_after / 3;
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if let Err(e) = LockOwner::write(&file) {
+                    log_warn!("failed to refresh lock heartbeat: {}", e);
+                }
+            }
+        });
+        Heartbeat { stop, thread }
+    }
+
+    fn stop(self) {
+        drop(self.stop);
+        let _ = self.thread.join();
+    }
+}
+
+impl FileLock {
+    /// Blocks until the lock is held, breaking stale locks
+    ///
+    /// # Parameters
+    /// - `path: &Path` - Lock file
+    /// - `stale_after: Option<Duration>` - Heartbeat age after which the
+    ///   owner is considered hung, `None` to only break locks of dead
+    ///   processes; the lock taken here does not refresh its own heartbeat
+    pub(crate) fn acquire(path: &Path, stale_after: Option<Duration>) -> CacheResult<Self> {
+        loop {
+            if let Ok(lock) = Self::try_acquire(path, stale_after)? {
+                return Ok(lock);
+            }
+            std::thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+    }
+
+    /// Takes the lock if it is free or stale
+    ///
+    /// # Returns
+    /// `CacheResult<Result<Self, Option<LockOwner>>>` - The lock, or the
+    /// live owner holding it (`None` if it did not record itself yet)
+    pub(crate) fn try_acquire(path: &Path, stale_after: Option<Duration>) -> CacheResult<Result<Self, Option<LockOwner>>> {
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            match fs4::FileExt::try_lock(&file) {
+                Ok(()) => {
+                    // The file may have been replaced by a process breaking the lock
+                    if !is_current(&file, path) {
+                        continue;
+                    }
+                    LockOwner::write(&file)?;
+                    return Ok(Ok(FileLock {
+                        file: Arc::new(file),
+                        path: path.to_path_buf(),
+                        heartbeat: None,
+                    }));
+                }
+                Err(fs4::TryLockError::WouldBlock) => {
+                    // Read the owner of the file that refused the lock, not of
+                    // whatever is at the path by now
+                    let owner = LockOwner::read(&file);
+                    if let Some(stale) = owner.as_ref().filter(|owner| owner.is_stale(stale_after)) {
+                        // Another process may have broken the lock already and
+                        // put a live one in place: only remove the stale file
+                        if !is_current(&file, path) {
+                            continue;
+                        }
+                        log_warn!(
+                            "breaking stale lock {} of process {} on {}",
+                            path.display(),
+                            stale.pid,
+                            stale.host
+                        );
+                        if std::fs::remove_file(path).is_ok() {
+                            continue;
+                        }
+                    }
+                    return Ok(Err(owner));
+                }
+                Err(fs4::TryLockError::Error(e)) => return Err(CacheError::Io(e)),
+            }
+        }
+    }
+
+    /// Refreshes the heartbeat in the background while the lock is held
+    ///
+    /// Only needed for holds that may outlast the `stale_after` other
+    /// processes check against, it costs a thread per lock.
+    ///
+    /// # Parameters
+    /// - `stale_after: Duration` - Heartbeat age other processes consider stale
+    pub(crate) fn keep_alive(mut self, stale_after: Duration) -> Self {
+        if self.heartbeat.is_none() {
+            self.heartbeat = Some(Heartbeat::start(Arc::clone(&self.file), stale_after));
+        }
+        self
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.stop();
+        }
+        // Waiting processes notice the removal and retry on a new file
+        if is_current(&self.file, &self.path) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Checks whether `file` is still the file at `path`
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(open), Ok(current)) => open.dev() == current.dev() && open.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Open files cannot be removed on other platforms
+#[cfg(not(unix))]
+fn is_current(_file: &File, _path: &Path) -> bool {
+    true
+}
+
+/// Checks whether a process of this host is running, if that can be told
+fn process_alive(pid: u32) -> Option<bool> {
+    if pid == std::process::id() {
+        return Some(true);
+    }
+    if cfg!(target_os = "linux") {
+        Some(Path::new("/proc").join(pid.to_string()).exists())
+    } else {
+        None
+    }
+}
+
+/// Name of this host, empty if unknown
+fn host_name() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|host| host.trim().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,69 @@
+/*
+ * @filename: macros.rs
+ * @description: Internal instrumentation macros for cache-lite library
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+//! Instrumentation macros that compile to nothing unless the matching
+//! cargo feature is enabled. Arguments are still type-checked through
+//! `format_args!` so disabled builds don't trip unused-variable lints.
+
+/// Emits a `log::debug!` record under the `cache_lite` target
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!(target: "cache_lite", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Emits a `log::warn!` record under the `cache_lite` target
+macro_rules! log_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!(target: "cache_lite", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Increments a `metrics` counter by the given amount
+macro_rules! metric_counter {
+    ($name:expr, $value:expr $(, $label:expr => $label_value:expr)*) => {{
+        #[cfg(feature = "metrics")]
+        metrics::counter!($name $(, $label => $label_value)*).increment($value);
+        #[cfg(not(feature = "metrics"))]
+        let _ = ($name, $value $(, $label, &$label_value)*);
+    }};
+}
+
+/// Records a value in a `metrics` histogram
+macro_rules! metric_histogram {
+    ($name:expr, $value:expr $(, $label:expr => $label_value:expr)*) => {{
+        #[cfg(feature = "metrics")]
+        metrics::histogram!($name $(, $label => $label_value)*).record($value);
+        #[cfg(not(feature = "metrics"))]
+        let _ = ($name, $value $(, $label, &$label_value)*);
+    }};
+}
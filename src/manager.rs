@@ -0,0 +1,182 @@
+/*
+ * @filename: manager.rs
+ * @description: Registry of named caches with aggregate operations
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cache::{Cache, CacheStats};
+use crate::config::CacheConfig;
+use crate::{CacheError, CacheResult};
+use std::collections::BTreeMap;
+
+/// Owns several named caches, each with its own configuration and limits
+///
+/// ```rust
+/// use cache_lite::{CacheConfig, CacheManager};
+///
+/// let mut manager = CacheManager::new();
+/// manager.register("thumbnails", CacheConfig::default()).unwrap();
+/// manager.register("http", CacheConfig::default()).unwrap();
+/// assert_eq!(manager.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct CacheManager {
+    caches: BTreeMap<String, Cache>,
+}
+
+impl CacheManager {
+    /// Creates an empty manager
+    ///
+    /// # Returns
+    /// New CacheManager instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a cache from `config` and registers it under `name`
+    ///
+    /// # Parameters
+    /// - `name: &str` - Registry name (e.g. `"thumbnails"`)
+    /// - `config: CacheConfig` - Configuration of the new cache
+    ///
+    /// # Returns
+    /// `CacheResult<&mut Cache>` - The registered cache or `AlreadyExists`
+    pub fn register(&mut self, name: &str, config: CacheConfig) -> CacheResult<&mut Cache> {
+        self.insert(name, Cache::new(config)?)
+    }
+
+    /// Registers an already constructed cache under `name`
+    ///
+    /// # Parameters
+    /// - `name: &str` - Registry name
+    /// - `cache: Cache` - Cache to take ownership of
+    ///
+    /// # Returns
+    /// `CacheResult<&mut Cache>` - The registered cache or `AlreadyExists`
+    pub fn insert(&mut self, name: &str, cache: Cache) -> CacheResult<&mut Cache> {
+        if self.caches.contains_key(name) {
+            return Err(CacheError::AlreadyExists(format!(
+                "Cache '{}' is already registered",
+                name
+            )));
+        }
+        Ok(self.caches.entry(name.to_string()).or_insert(cache))
+    }
+
+    /// Returns a registered cache
+    ///
+    /// # Parameters
+    /// - `name: &str` - Registry name
+    ///
+    /// # Returns
+    /// `Option<&Cache>` - The cache if registered
+    pub fn get(&self, name: &str) -> Option<&Cache> {
+        self.caches.get(name)
+    }
+
+    /// Returns a registered cache for modification
+    ///
+    /// # Parameters
+    /// - `name: &str` - Registry name
+    ///
+    /// # Returns
+    /// `Option<&mut Cache>` - The cache if registered
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Cache> {
+        self.caches.get_mut(name)
+    }
+
+    /// Removes a cache from the registry without touching its objects
+    ///
+    /// # Parameters
+    /// - `name: &str` - Registry name
+    ///
+    /// # Returns
+    /// `Option<Cache>` - The unregistered cache
+    pub fn unregister(&mut self, name: &str) -> Option<Cache> {
+        self.caches.remove(name)
+    }
+
+    /// Returns the number of registered caches
+    ///
+    /// # Returns
+    /// `usize` - Count of caches
+    pub fn len(&self) -> usize {
+        self.caches.len()
+    }
+
+    /// Checks whether no cache is registered
+    ///
+    /// # Returns
+    /// `bool` - True if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.caches.is_empty()
+    }
+
+    /// Returns the registered caches ordered by name
+    ///
+    /// # Returns
+    /// `impl Iterator<Item = (&str, &Cache)>` - Name and cache pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Cache)> {
+        self.caches.iter().map(|(name, cache)| (name.as_str(), cache))
+    }
+
+    /// Sums the statistics of every registered cache
+    ///
+    /// # Returns
+    /// `CacheStats` - Total object count and size
+    pub fn stats(&self) -> CacheStats {
+        self.caches
+            .values()
+            .map(Cache::stats)
+            .fold(CacheStats::default(), |total, stats| CacheStats {
+                objects: total.objects + stats.objects,
+                bytes: total.bytes + stats.bytes,
+                throttle: None,
+            })
+    }
+
+    /// Clears every registered cache
+    ///
+    /// All caches are cleared even if some of them fail.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error listing the failed caches
+    pub fn clear_all(&mut self) -> CacheResult<()> {
+        let mut errors = Vec::new();
+
+        for (name, cache) in &mut self.caches {
+            if let Err(e) = cache.clear() {
+                errors.push(format!("'{}': {}", name, e));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(CacheError::Generic(format!(
+                "Errors occurred while clearing caches: {}",
+                errors.join("; ")
+            )));
+        }
+
+        Ok(())
+    }
+}
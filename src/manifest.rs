@@ -0,0 +1,258 @@
+/*
+ * @filename: manifest.rs
+ * @description: JSON manifest describing the objects of a cache
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::dependency::SourceDependency;
+use crate::object::CacheObject;
+use crate::{CacheError, CacheResult};
+#[cfg(feature = "hmac")]
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// File name `Cache::index_all()` loads a manifest from
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Current manifest format version
+const MANIFEST_VERSION: u32 = 1;
+
+/// Signs manifests on export and verifies them on import
+///
+/// Set on a cache with `Cache::set_manifest_signer()`. The signature covers
+/// the format version and every entry, including the content checksums,
+/// so a verified manifest vouches for the content of each object.
+pub trait ManifestSigner: Send + Sync {
+    /// Short identifier stored next to the signature (e.g. `"hmac-sha256"`)
+    fn algorithm(&self) -> &str;
+
+    /// Signs the canonical manifest payload
+    ///
+    /// # Parameters
+    /// - `payload: &[u8]` - Bytes to sign
+    ///
+    /// # Returns
+    /// `CacheResult<Vec<u8>>` - Signature bytes
+    fn sign(&self, payload: &[u8]) -> CacheResult<Vec<u8>>;
+
+    /// Verifies a signature produced by `sign()`
+    ///
+    /// # Parameters
+    /// - `payload: &[u8]` - Signed bytes
+    /// - `signature: &[u8]` - Signature to check
+    ///
+    /// # Returns
+    /// `bool` - True if the signature is valid
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool;
+}
+
+/// HMAC-SHA256 signer for producers and consumers sharing a secret key
+#[cfg(feature = "hmac")]
+pub struct HmacSha256Signer {
+    key: Vec<u8>,
+}
+
+#[cfg(feature = "hmac")]
+impl HmacSha256Signer {
+    /// Creates a signer from a shared secret
+    ///
+    /// # Parameters
+    /// - `key: impl Into<Vec<u8>>` - Secret key
+    ///
+    /// # Returns
+    /// New HmacSha256Signer instance
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        HmacSha256Signer { key: key.into() }
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+#[cfg(feature = "hmac")]
+impl ManifestSigner for HmacSha256Signer {
+    fn algorithm(&self) -> &str {
+        "hmac-sha256"
+    }
+
+    fn sign(&self, payload: &[u8]) -> CacheResult<Vec<u8>> {
+        Ok(self.mac(payload).finalize().into_bytes().to_vec())
+    }
+
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool {
+        self.mac(payload).verify_slice(signature).is_ok()
+    }
+}
+
+/// Serialized registry of a cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    pub(crate) version: u32,
+    pub(crate) entries: Vec<ManifestEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) signature: Option<ManifestSignature>,
+}
+
+/// Signature over the version and entries of a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ManifestSignature {
+    pub(crate) algorithm: String,
+    /// Hex-encoded signature bytes
+    pub(crate) value: String,
+}
+
+/// One object of a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ManifestEntry {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) id: u32,
+    /// Creation time in seconds since the Unix epoch
+    pub(crate) created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) size: Option<u64>,
+    /// Hex SHA-256 of the content, comparable with `sha256sum`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) sha256: Option<String>,
+    /// Source files recorded with `CacheObject::depends_on()`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sources: Vec<SourceDependency>,
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Returns the hex SHA-256 digest of `content`
+pub(crate) fn sha256_hex(content: &[u8]) -> String {
+    hex(&Sha256::digest(content))
+}
+
+impl Manifest {
+    /// Describes the given objects, reading each one to checksum it
+    pub(crate) fn from_objects<'a>(objects: impl Iterator<Item = &'a CacheObject>) -> CacheResult<Self> {
+        let mut entries = Vec::new();
+        for obj in objects {
+            let content = if obj.exists() { Some(obj.get_bytes()?) } else { None };
+            entries.push(ManifestEntry {
+                name: obj.name().to_string(),
+                path: obj.path().to_path_buf(),
+                id: obj.id(),
+                created_at: obj
+                    .created_at()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                size: content.as_ref().map(|c| c.len() as u64),
+                sha256: content.as_deref().map(sha256_hex),
+                sources: obj.sources(),
+            });
+        }
+        entries.sort_by_key(|entry| entry.id);
+        Ok(Manifest {
+            version: MANIFEST_VERSION,
+            entries,
+            signature: None,
+        })
+    }
+
+    /// Returns the bytes covered by the signature
+    fn payload(&self) -> CacheResult<Vec<u8>> {
+        serde_json::to_vec(&(self.version, &self.entries))
+            .map_err(|e| CacheError::Serialization(e.to_string()))
+    }
+
+    /// Serializes the manifest, signing it if a signer is given
+    pub(crate) fn into_json(mut self, signer: Option<&dyn ManifestSigner>) -> CacheResult<String> {
+        if let Some(signer) = signer {
+            self.signature = Some(ManifestSignature {
+                algorithm: signer.algorithm().to_string(),
+                value: hex(&signer.sign(&self.payload()?)?),
+            });
+        }
+        serde_json::to_string_pretty(&self).map_err(|e| CacheError::Serialization(e.to_string()))
+    }
+
+    /// Parses a manifest produced by `Cache::dump_manifest()`
+    ///
+    /// With a signer, unsigned manifests and invalid signatures are rejected.
+    pub(crate) fn parse(json: &str, signer: Option<&dyn ManifestSigner>) -> CacheResult<Self> {
+        let manifest: Manifest = serde_json::from_str(json)
+            .map_err(|e| CacheError::Corrupted(format!("invalid manifest: {}", e)))?;
+        if manifest.version > MANIFEST_VERSION {
+            return Err(CacheError::Corrupted(format!(
+                "manifest version {} is newer than the supported version {}",
+                manifest.version, MANIFEST_VERSION
+            )));
+        }
+        if let Some(signer) = signer {
+            let Some(signature) = &manifest.signature else {
+                return Err(CacheError::Corrupted("manifest is not signed".to_string()));
+            };
+            let valid = signature.algorithm == signer.algorithm()
+                && unhex(&signature.value)
+                    .is_some_and(|value| signer.verify(&manifest.payload().unwrap_or_default(), &value));
+            if !valid {
+                return Err(CacheError::Corrupted(format!(
+                    "manifest signature ({}) is invalid",
+                    signature.algorithm
+                )));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+impl ManifestEntry {
+    /// Returns the recorded creation time
+    pub(crate) fn created_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.created_at)
+    }
+
+    /// Checks `content` against the recorded checksum, if any
+    pub(crate) fn verify(&self, content: &[u8]) -> CacheResult<()> {
+        match &self.sha256 {
+            Some(expected) if *expected != sha256_hex(content) => Err(CacheError::Corrupted(format!(
+                "checksum mismatch for cache object '{}'",
+                self.name
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+/*
+ * @filename: meta.rs
+ * @description: Per-entry metadata kept next to cache content
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::AppInfo;
+use crate::manifest::sha256_hex;
+use crate::storage::Storage;
+use crate::throttle::ThrottleState;
+use crate::{CacheError, CacheResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Extension appended to the file name of an entry for its sidecar
+pub(crate) const SIDECAR_EXTENSION: &str = "meta";
+
+/// First bytes of an entry with an inline metadata header
+const HEADER_MAGIC: &[u8; 8] = b"\x89CLMETA\n";
+
+/// Length of the magic and the little-endian `u32` JSON length
+const HEADER_PREFIX: usize = HEADER_MAGIC.len() + 4;
+
+/// Metadata of a cache entry, see `CacheConfig::metadata`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryMeta {
+    /// Creation time in seconds since the Unix epoch
+    pub created_at: u64,
+    /// Object name, recorded when `format.key_encoding` encodes it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Time to live in seconds, recorded for readers of the metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+    /// Hex SHA-256 of the content as last written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Application (`CacheConfig::app`) that last wrote the content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written_by: Option<AppInfo>,
+    /// MIME type of the content (e.g. `image/png`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Time of the first read by a process, in seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<u64>,
+    /// Free-form labels
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Application-defined values
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub user: BTreeMap<String, serde_json::Value>,
+}
+
+/// Returns the sidecar path of an entry (`<file>.meta`)
+pub(crate) fn sidecar_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".");
+    file_name.push(SIDECAR_EXTENSION);
+    path.with_file_name(file_name)
+}
+
+impl EntryMeta {
+    /// Reads the sidecar of an entry, `None` if it has none yet
+    pub(crate) fn load(storage: &dyn Storage, path: &Path) -> CacheResult<Option<Self>> {
+        let sidecar = sidecar_path(path);
+        if !storage.exists(&sidecar) {
+            return Ok(None);
+        }
+        let json = storage.read(&sidecar)?;
+        serde_json::from_slice(&json)
+            .map(Some)
+            .map_err(|e| CacheError::Corrupted(format!("invalid metadata in {}: {}", sidecar.display(), e)))
+    }
+
+    /// Writes the sidecar of an entry
+    pub(crate) fn save(&self, storage: &dyn Storage, path: &Path) -> CacheResult<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        storage.write(&sidecar_path(path), &json)?;
+        Ok(())
+    }
+}
+
+/// Storage backend framing a metadata header in front of every entry
+///
+/// Entries start with `\x89CLMETA\n`, the length of the JSON encoded
+/// `EntryMeta` as a little-endian `u32` and the JSON itself, followed by
+/// the content. `read()` and `size()` skip the header and `write()` keeps
+/// it, updating the checksum and writer, so callers only see the content. Entries
+/// without the magic (e.g. written by other tools) are read verbatim.
+///
+/// `stream()` and `open()` are unsupported, so writers buffer the content
+/// and `get_file()` / `mmap_mut()` fail. Used by `Cache::new()` and
+/// `Cache::with_storage()` when `metadata` is `"inline"`.
+#[derive(Debug)]
+pub struct InlineMetaStorage {
+    inner: Arc<dyn Storage>,
+    /// `CacheConfig::app` of the owning cache
+    app: Arc<RwLock<Option<AppInfo>>>,
+}
+
+impl InlineMetaStorage {
+    /// Wraps a backend, framing a metadata header into its entries
+    ///
+    /// # Parameters
+    /// - `inner: Arc<dyn Storage>` - Backend storing the framed entries
+    ///
+    /// # Returns
+    /// New InlineMetaStorage instance
+    pub fn new(inner: Arc<dyn Storage>) -> Self {
+        InlineMetaStorage {
+            inner,
+            app: Arc::default(),
+        }
+    }
+
+    /// Shares the application stamped into written entries with the cache
+    pub(crate) fn with_app(mut self, app: Arc<RwLock<Option<AppInfo>>>) -> Self {
+        self.app = app;
+        self
+    }
+
+    /// Reads the header of an entry and returns it with its length
+    fn header(&self, path: &Path) -> io::Result<(Option<EntryMeta>, u64)> {
+        // `open()` may create the file, a removed entry must stay removed
+        if !self.inner.exists(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist", path.display()),
+            ));
+        }
+        let raw = match self.inner.open(path) {
+            Ok(file) => {
+                let mut prefix = Vec::with_capacity(HEADER_PREFIX);
+                (&file).take(HEADER_PREFIX as u64).read_to_end(&mut prefix)?;
+                match json_len(&prefix) {
+                    Some(len) => {
+                        let mut raw = prefix;
+                        (&file).take(len as u64).read_to_end(&mut raw)?;
+                        raw
+                    }
+                    None => return Ok((None, 0)),
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => self.inner.read(path)?,
+            Err(e) => return Err(e),
+        };
+        let (meta, body) = split(&raw)?;
+        Ok((meta, (raw.len() - body.len()) as u64))
+    }
+
+    /// Returns the header of an entry, `None` if it has none
+    pub(crate) fn read_meta(&self, path: &Path) -> CacheResult<Option<EntryMeta>> {
+        Ok(self.header(path)?.0)
+    }
+
+    /// Replaces the header of an entry, keeping its content
+    pub(crate) fn write_meta(&self, path: &Path, meta: &EntryMeta) -> CacheResult<()> {
+        let raw = self.inner.read(path)?;
+        let (_, body) = split(&raw)?;
+        self.inner.write(path, &frame(meta, body)?)?;
+        Ok(())
+    }
+}
+
+/// Returns the JSON length announced by a header prefix, if it is one
+fn json_len(raw: &[u8]) -> Option<usize> {
+    let len = raw.get(HEADER_MAGIC.len()..HEADER_PREFIX)?;
+    raw.starts_with(HEADER_MAGIC)
+        .then(|| u32::from_le_bytes(len.try_into().expect("slice of 4 bytes")) as usize)
+}
+
+/// Splits a framed entry into its header and content
+fn split(raw: &[u8]) -> io::Result<(Option<EntryMeta>, &[u8])> {
+    let Some(len) = json_len(raw) else {
+        return Ok((None, raw));
+    };
+    let json = raw
+        .get(HEADER_PREFIX..HEADER_PREFIX + len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated metadata header"))?;
+    let meta = serde_json::from_slice(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((Some(meta), &raw[HEADER_PREFIX + len..]))
+}
+
+/// Prepends the header for `meta` to `body`
+fn frame(meta: &EntryMeta, body: &[u8]) -> io::Result<Vec<u8>> {
+    let json = serde_json::to_vec(meta).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let len = u32::try_from(json.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "metadata header too large"))?;
+    let mut framed = Vec::with_capacity(HEADER_PREFIX + json.len() + body.len());
+    framed.extend_from_slice(HEADER_MAGIC);
+    framed.extend_from_slice(&len.to_le_bytes());
+    framed.extend_from_slice(&json);
+    framed.extend_from_slice(body);
+    Ok(framed)
+}
+
+impl Storage for InlineMetaStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut raw = self.inner.read(path)?;
+        let header_len = raw.len() - split(&raw)?.1.len();
+        raw.drain(..header_len);
+        Ok(raw)
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        let existing = if self.inner.exists(path) { self.header(path)?.0 } else { None };
+        let mut meta = existing.unwrap_or_else(|| EntryMeta {
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            ..EntryMeta::default()
+        });
+        meta.sha256 = Some(sha256_hex(content));
+        if let Some(app) = &*self.app.read().unwrap_or_else(|e| e.into_inner()) {
+            meta.written_by = Some(app.clone());
+        }
+        self.inner.write(path, &frame(&meta, content)?)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        let size = self.inner.size(path)?;
+        Ok(size - self.header(path)?.1)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_file(path)
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_new(path)
+    }
+
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        self.inner.set_shared(path)
+    }
+
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        self.inner.unshare(path)
+    }
+
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        self.inner.seal(path)
+    }
+
+    fn is_sealed(&self, path: &Path) -> bool {
+        self.inner.is_sealed(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn throttle(&self) -> Option<ThrottleState> {
+        self.inner.throttle()
+    }
+}
@@ -0,0 +1,219 @@
+/*
+ * @filename: middleware.rs
+ * @description: Middleware pipeline around cache operations
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::CacheRetryConfig;
+use crate::{CacheError, CacheResult};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Cache operation passed through the middleware pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation<'a> {
+    /// `Cache::create`
+    Create { name: &'a str },
+    /// `Cache::get`
+    Get { name: &'a str },
+    /// Reading a cache object's content
+    Read { name: &'a str },
+    /// Writing `size` bytes to a cache object
+    Write { name: &'a str, size: u64 },
+    /// `Cache::remove` or `CacheObject::delete`
+    Remove { name: &'a str },
+}
+
+impl Operation<'_> {
+    /// Returns the name of the cache object the operation targets
+    ///
+    /// # Returns
+    /// `&str` - Cache object identifier
+    pub fn name(&self) -> &str {
+        match self {
+            Operation::Create { name }
+            | Operation::Get { name }
+            | Operation::Read { name }
+            | Operation::Write { name, .. }
+            | Operation::Remove { name } => name,
+        }
+    }
+}
+
+/// Logic wrapped around every cache operation
+///
+/// A middleware receives the operation and the rest of the pipeline.
+/// Calling `next.run()` executes the remaining middlewares and then the
+/// operation itself; it may be called several times (e.g. for retries)
+/// or not at all to reject the operation.
+///
+/// # Example
+///
+/// ```
+/// use cache_lite::{CacheResult, Middleware, Next, Operation};
+///
+/// struct Timing;
+///
+/// impl Middleware for Timing {
+///     fn call(&self, op: &Operation<'_>, mut next: Next<'_>) -> CacheResult<()> {
+///         let start = std::time::Instant::now();
+///         let result = next.run(op);
+///         println!("{:?} took {:?}", op, start.elapsed());
+///         result
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Handles an operation
+    ///
+    /// # Parameters
+    /// - `op: &Operation` - Operation being performed
+    /// - `next: Next` - Remaining pipeline
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Outcome of the operation
+    fn call(&self, op: &Operation<'_>, next: Next<'_>) -> CacheResult<()>;
+}
+
+/// Remaining part of a middleware pipeline
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn Middleware>],
+    operation: &'a mut dyn FnMut() -> CacheResult<()>,
+}
+
+impl Next<'_> {
+    /// Runs the remaining middlewares and the operation
+    ///
+    /// # Parameters
+    /// - `op: &Operation` - Operation being performed
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Outcome of the operation
+    pub fn run(&mut self, op: &Operation<'_>) -> CacheResult<()> {
+        match self.middlewares.split_first() {
+            Some((first, rest)) => first.call(
+                op,
+                Next {
+                    middlewares: rest,
+                    operation: &mut *self.operation,
+                },
+            ),
+            None => (self.operation)(),
+        }
+    }
+}
+
+/// Ordered list of middlewares, the first one added runs outermost
+#[derive(Default)]
+pub(crate) struct MiddlewareStack {
+    middlewares: RwLock<Vec<Arc<dyn Middleware>>>,
+    /// Retry policy applied around the operation itself
+    retry: RwLock<CacheRetryConfig>,
+}
+
+/// Runs `f`, retrying transient failures according to `policy`
+fn with_retries<T>(policy: &CacheRetryConfig, op: &Operation<'_>, mut f: impl FnMut() -> CacheResult<T>) -> CacheResult<T> {
+    let mut backoff = Duration::from_millis(policy.backoff_ms);
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(e) if e.is_retryable() && attempt < policy.max_attempts => {
+                log_debug!("retrying {:?} after {:?} (attempt {}): {}", op, backoff, attempt, e);
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(Duration::from_millis(policy.max_backoff_ms));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+impl MiddlewareStack {
+    /// Checks whether operations run as they are, without middleware or retries
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub(crate) fn is_passthrough(&self) -> bool {
+        self.retry.read().unwrap_or_else(|e| e.into_inner()).max_attempts <= 1
+            && self.middlewares.read().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    pub(crate) fn set_retry(&self, policy: CacheRetryConfig) {
+        *self.retry.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    pub(crate) fn push(&self, middleware: Arc<dyn Middleware>) {
+        self.middlewares
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(middleware);
+    }
+
+    /// Runs `f` through the pipeline and returns its value
+    pub(crate) fn run<T>(
+        &self,
+        op: &Operation<'_>,
+        mut f: impl FnMut() -> CacheResult<T>,
+    ) -> CacheResult<T> {
+        let retry = self.retry.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut f = || {
+            if retry.max_attempts > 1 {
+                with_retries(&retry, op, &mut f)
+            } else {
+                f()
+            }
+        };
+        let middlewares = self
+            .middlewares
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if middlewares.is_empty() {
+            return f();
+        }
+
+        let mut value = None;
+        let mut operation = || {
+            value = Some(f()?);
+            Ok(())
+        };
+        Next {
+            middlewares: &middlewares,
+            operation: &mut operation,
+        }
+        .run(op)?;
+
+        value.ok_or_else(|| {
+            CacheError::Generic(format!(
+                "Operation on '{}' was not executed by the middleware pipeline",
+                op.name()
+            ))
+        })
+    }
+}
+
+impl fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.middlewares.read().map(|m| m.len()).unwrap_or(0);
+        f.debug_struct("MiddlewareStack").field("middlewares", &count).finish()
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * @filename: mmap.rs
+ * @description: Writable memory-mapped cache entries
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::object::CacheObject;
+use crate::{CacheError, CacheResult};
+use memmap2::MmapMut;
+use std::ops::{Deref, DerefMut};
+
+/// Writable mapping of a cache file returned by `CacheObject::mmap_mut()`
+///
+/// Dereferences to the mapped bytes. Changes reach the file when the OS
+/// writes the pages back; `flush()` forces it and reports the write to the
+/// cache like `write_bytes()` does.
+pub struct CacheMmapMut<'a> {
+    object: &'a CacheObject,
+    map: MmapMut,
+}
+
+impl<'a> CacheMmapMut<'a> {
+    pub(crate) fn new(object: &'a CacheObject, map: MmapMut) -> Self {
+        CacheMmapMut { object, map }
+    }
+
+    /// Writes modified pages back to the file and reports the write
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn flush(&self) -> CacheResult<()> {
+        self.object.report("write", self.map.flush().map_err(CacheError::Io))?;
+        self.object.written(self.map.len() as u64)
+    }
+
+    /// Writes back the pages of a byte range only
+    ///
+    /// # Parameters
+    /// - `offset: usize` - Start of the range
+    /// - `len: usize` - Length of the range
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn flush_range(&self, offset: usize, len: usize) -> CacheResult<()> {
+        self.object
+            .report("write", self.map.flush_range(offset, len).map_err(CacheError::Io))?;
+        self.object.written(self.map.len() as u64)
+    }
+}
+
+impl Deref for CacheMmapMut<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl DerefMut for CacheMmapMut<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
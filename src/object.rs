@@ -24,9 +24,29 @@
  * SOFTWARE.
  */
 
+use std::borrow::Borrow;
+use std::io::{BufRead, BufReader, IoSlice, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use crate::cache::CacheContext;
+use crate::codec::{self, Codec, JsonCodec};
+use crate::config::{AppInfo, CacheIoConfig, MetadataMode};
+use crate::dependency::SourceDependency;
+use crate::event::CacheEvent;
+use crate::file::CacheFile;
+use crate::manifest::{hex, sha256_hex};
+use crate::meta::{sidecar_path, EntryMeta};
+use crate::middleware::Operation;
+use crate::storage::{FsStorage, Storage};
+use crate::telemetry::OpSpan;
+use crate::writer::{CacheWriter, ChunkedWrite};
+#[cfg(feature = "mmap")]
+use crate::mmap::CacheMmapMut;
 use crate::{CacheError, CacheResult};
+use sha2::{Digest, Sha256};
+
 
 /// Represents an individual cache object with file operations
 #[derive(Debug)]
@@ -34,7 +54,85 @@ pub struct CacheObject {
     name: String,
     path: PathBuf,
     id: u32,
-    created_at: SystemTime
+    created_at: SystemTime,
+    context: Option<Arc<CacheContext>>,
+    base: Option<PathBuf>,
+    sources: Arc<Mutex<Vec<SourceDependency>>>,
+    generation: u64,
+    /// Set once the object left the registry of its cache, shared by clones
+    detached: Arc<AtomicBool>,
+    moved_to: Arc<OnceLock<PathBuf>>,
+    /// Set by the first successful read, shared by clones
+    read: Arc<AtomicBool>,
+    /// Whether every read of the content happened through this process
+    reads_tracked: bool,
+    /// Set by `seal()`, shared by clones
+    sealed: Arc<AtomicBool>,
+    /// Whether this handle refuses writes, see `read_only_view()`
+    read_only: bool,
+    /// Whether the entry was opened up to other users on creation
+    shared: bool,
+    /// File ownership shared by clones, see `delete()`
+    handle: Arc<EntryHandle>
+}
+
+/// Deletion state shared by an object and its clones
+///
+/// A `delete()` while other clones exist only records what to remove;
+/// dropping the last clone removes it, unless the cache created a new
+/// entry at the same path in the meantime.
+#[derive(Debug, Default)]
+struct EntryHandle {
+    pending_delete: Mutex<Option<(Option<Arc<CacheContext>>, PathBuf)>>,
+}
+
+impl Drop for EntryHandle {
+    fn drop(&mut self) {
+        let pending = self.pending_delete.get_mut().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some((context, path)) = pending {
+            let storage: &dyn Storage = match &context {
+                Some(context) if !context.take_deferred_delete(&path) => return,
+                Some(context) => &*context.storage,
+                None => &FsStorage,
+            };
+            let _ = storage.remove(&path);
+            let sidecar = sidecar_path(&path);
+            if storage.exists(&sidecar) {
+                let _ = storage.remove(&sidecar);
+            }
+        }
+    }
+}
+
+/// Non-owning handle created by `CacheObject::downgrade()`
+///
+/// Long-lived components can hold it without keeping a removed entry
+/// alive: `upgrade()` only hands the object out while it is still
+/// registered in its cache and its file still exists.
+///
+/// ```rust
+/// let mut cache = cache_lite::Cache::in_memory();
+/// let weak = cache.insert("session", b"token").unwrap().downgrade();
+/// assert!(weak.upgrade().is_some());
+/// cache.remove("session").unwrap();
+/// assert!(weak.upgrade().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeakCacheObject {
+    object: Weak<CacheObject>,
+}
+
+impl WeakCacheObject {
+    /// Returns the object if it is still registered and its file exists
+    ///
+    /// # Returns
+    /// `Option<Arc<CacheObject>>` - The object, or `None` if it was removed,
+    /// cleared, invalidated or its file was deleted behind the cache's back
+    pub fn upgrade(&self) -> Option<Arc<CacheObject>> {
+        let object = self.object.upgrade()?;
+        let evicted = object.context.as_ref().is_some_and(|context| context.space.is_evicted(&object.path));
+        (!object.detached.load(Ordering::Relaxed) && !evicted && object.exists()).then_some(object)
+    }
 }
 
 impl CacheObject {
@@ -44,14 +142,272 @@ impl CacheObject {
         path: PathBuf, 
         id: u32
     ) -> Self {
-        let obj = CacheObject {
+        CacheObject {
             name,
             path,
             id,
-            created_at: SystemTime::now()
-        };
-        
-        obj
+            created_at: SystemTime::now(),
+            context: None,
+            base: None,
+            sources: Arc::default(),
+            generation: 0,
+            detached: Arc::default(),
+            moved_to: Arc::default(),
+            read: Arc::default(),
+            reads_tracked: false,
+            sealed: Arc::default(),
+            read_only: false,
+            shared: false,
+            handle: Arc::default()
+        }
+    }
+
+    /// Returns a handle that does not keep the object alive
+    ///
+    /// # Returns
+    /// `WeakCacheObject` - Handle whose `upgrade()` re-checks the registry and the file
+    pub fn downgrade(self: &Arc<Self>) -> WeakCacheObject {
+        WeakCacheObject { object: Arc::downgrade(self) }
+    }
+
+    /// Marks the object as no longer registered in its cache
+    pub(crate) fn detach(&self) {
+        self.detached.store(true, Ordering::Relaxed);
+    }
+
+    /// Detaches the object after its file was moved to `target`, refusing further writes
+    pub(crate) fn relocated(&self, target: &Path) {
+        let _ = self.moved_to.set(target.to_path_buf());
+        self.detach();
+    }
+
+    /// Returns a clone of the object that refuses writes and deletion
+    ///
+    /// Used to hand out objects of a parent cache, which the child never
+    /// writes to.
+    pub(crate) fn read_only_view(&self) -> Self {
+        CacheObject { read_only: true, ..self.clone() }
+    }
+
+    /// Records that the content was consumed, see `was_read()`
+    ///
+    /// With `MetadataMode::Sidecar` the first read is also stored as
+    /// `EntryMeta::read_at`, so other processes see it.
+    pub(crate) fn mark_read(&self) {
+        if !self.read.swap(true, Ordering::Relaxed) && !self.read_only && self.metadata_mode() == MetadataMode::Sidecar {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            let _ = self.store_metadata(|meta| {
+                meta.read_at.get_or_insert(now);
+            });
+        }
+    }
+
+    /// Marks the object as created by this process, which sees all its reads
+    pub(crate) fn tracking_reads(mut self) -> Self {
+        self.reads_tracked = true;
+        self
+    }
+
+    /// Checks whether the content was read since the object was created
+    ///
+    /// Only reads through this process count; objects registered from an
+    /// existing directory start out unread. Handing out the file through
+    /// `get_file()`, `mmap_mut()` or `path_for_read()` counts as a read.
+    ///
+    /// # Returns
+    /// `bool` - True after the first successful read
+    pub fn was_read(&self) -> bool {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// Tells whether the content was ever read, `None` if that is unknown
+    ///
+    /// Objects created by this process are known to be unread until read;
+    /// for others only a sidecar `read_at` marker tells.
+    pub(crate) fn read_state(&self) -> CacheResult<Option<bool>> {
+        if self.was_read() {
+            return Ok(Some(true));
+        }
+        if self.metadata_mode() == MetadataMode::Sidecar
+            && let Some(meta) = self.load_metadata()?
+        {
+            return Ok(Some(meta.read_at.is_some()));
+        }
+        Ok(self.reads_tracked.then_some(false))
+    }
+
+    /// Makes the content immutable
+    ///
+    /// Every later write through this object, its clones or any object of
+    /// the same file returns `PermissionDenied`, as do `get_file()`,
+    /// `Cache::create()` of the same name and the other write paths. The
+    /// file is made read-only, so content-addressed artifacts cannot
+    /// change once published. Sealed objects can still be deleted. A file
+    /// still shared with a parent cache is copied first, so the parent's
+    /// entry stays writable.
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn seal(&self) -> CacheResult<()> {
+        self.check_writable()?;
+        self.unshare()?;
+        self.storage().flush().map_err(|e| self.contextual("seal", e))?;
+        self.storage().seal(&self.path).map_err(|e| self.contextual("seal", e))?;
+        self.sealed.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Checks whether the content was sealed with `seal()`
+    ///
+    /// # Returns
+    /// `bool` - True if writes are refused
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::Relaxed) || self.storage().is_sealed(&self.path)
+    }
+
+    /// Fails with `PermissionDenied` once the object is sealed or for a
+    /// read-only view, or with `NotFound` once its file was moved by
+    /// `Cache::migrate_format()`
+    pub(crate) fn check_sealed(&self) -> CacheResult<()> {
+        self.check_writable()?;
+        if let Some(target) = self.moved_to.get() {
+            return Err(CacheError::NotFound(format!(
+                "Cache object '{}' was moved to {}, get a new handle",
+                self.name,
+                target.display()
+            )));
+        }
+        if self.is_sealed() {
+            return Err(CacheError::PermissionDenied(format!(
+                "Cache object '{}' is sealed",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fails with `PermissionDenied` for a read-only view of a parent cache object
+    fn check_writable(&self) -> CacheResult<()> {
+        if self.read_only {
+            return Err(CacheError::PermissionDenied(format!(
+                "Cache object '{}' belongs to a parent cache and is read-only",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Attaches the shared state of the owning cache
+    pub(crate) fn with_context(mut self, context: Arc<CacheContext>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Sets the cache generation the object was created in
+    pub(crate) fn with_generation(mut self, generation: u64) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Marks an object whose entry was opened up to other users on creation
+    ///
+    /// Covers per-object configs with a system scope, the cache-wide
+    /// scope is read from the context.
+    pub(crate) fn with_shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    /// Checks whether replaced files must be opened up to other users again
+    pub(crate) fn is_shared(&self) -> bool {
+        self.shared || self.context.as_ref().is_some_and(|context| context.shared.load(Ordering::Relaxed))
+    }
+
+    /// Restores the creation time of an imported object
+    pub(crate) fn with_created_at(mut self, created_at: SystemTime) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    /// Sets the read-only file consulted while no local entry exists
+    pub(crate) fn with_base(mut self, base: PathBuf) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Returns the base layer file if it currently backs this object
+    fn base_fallback(&self) -> Option<&Path> {
+        self.base
+            .as_deref()
+            .filter(|_| !self.storage().exists(&self.path))
+    }
+
+    /// Copies the base layer file into the local entry before an in-place write
+    ///
+    /// Handles opened on the local file would otherwise start from an empty
+    /// entry and hide the base content.
+    fn copy_up(&self) -> CacheResult<()> {
+        if let Some(base) = self.base_fallback() {
+            let content = std::fs::read(base).map_err(|e| self.contextual("open", e))?;
+            self.storage().write(&self.path, &content).map_err(|e| self.contextual("open", e))?;
+        }
+        Ok(())
+    }
+
+    /// Sends an event to the observers of the owning cache, if any
+    fn emit(&self, event: impl FnOnce() -> CacheEvent) {
+        if let Some(context) = &self.context {
+            context.events.emit_with(event);
+        }
+    }
+
+    /// Returns the storage backend of the owning cache
+    pub(crate) fn storage(&self) -> &dyn Storage {
+        match &self.context {
+            Some(context) => &*context.storage,
+            None => &FsStorage,
+        }
+    }
+
+    /// Returns the `io.read_buffer` and `io.write_buffer` sizes of the owning cache
+    pub(crate) fn buffer_sizes(&self) -> (usize, usize) {
+        match &self.context {
+            Some(context) => (
+                context.read_buffer.load(Ordering::Relaxed),
+                context.write_buffer.load(Ordering::Relaxed),
+            ),
+            None => {
+                let io = CacheIoConfig::default();
+                (io.read_buffer, io.write_buffer)
+            }
+        }
+    }
+
+    /// Runs a file operation through the middleware of the owning cache
+    pub(crate) fn run<T>(&self, op: Operation<'_>, f: impl FnMut() -> CacheResult<T>) -> CacheResult<T> {
+        match &self.context {
+            Some(context) => context.middleware.run(&op, f),
+            None => {
+                let mut f = f;
+                f()
+            }
+        }
+    }
+
+    /// Reports a failed operation to observers and passes the error through
+    ///
+    /// I/O errors get the operation, path and object name attached.
+    pub(crate) fn report<T>(&self, operation: &'static str, result: CacheResult<T>) -> CacheResult<T> {
+        let result = result.map_err(|e| self.contextual(operation, e));
+        if let Err(e) = &result {
+            self.emit(|| CacheEvent::error(&self.name, self.id, operation, e));
+        }
+        result
+    }
+
+    /// Attaches the operation, path and object name to an I/O error
+    fn contextual(&self, operation: &'static str, error: impl Into<CacheError>) -> CacheError {
+        error.into().with_context(operation, &self.path, Some(&self.name))
     }
 
     /// Returns the cache object name
@@ -70,6 +426,17 @@ impl CacheObject {
         &self.path
     }
 
+    /// Returns the filesystem path for reading the file directly
+    ///
+    /// Unlike `path()` this counts as a read for `was_read()`.
+    ///
+    /// # Returns
+    /// `&Path` - Path to cache file
+    pub fn path_for_read(&self) -> &Path {
+        self.mark_read();
+        &self.path
+    }
+
     /// Returns the creation time of the cache object
     /// 
     /// # Returns
@@ -86,17 +453,36 @@ impl CacheObject {
         self.id
     }
 
+    /// Returns the cache generation the object was created in
+    ///
+    /// # Returns
+    /// `u64` - Generation, see `Cache::bump_generation()`
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Opens the cache file for reading/writing
+    ///
+    /// Writes through the raw handle bypass the cache bookkeeping (metadata
+    /// checksum, dependents, `Written` events); prefer `open_file()`.
     /// 
     /// # Returns
     /// `CacheResult<std::fs::File>` - File handle or error
     pub fn get_file(&self) -> CacheResult<std::fs::File> {
-        std::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&self.path)
-            .map_err(|e| CacheError::Io(e))
+        self.check_sealed()?;
+        self.unshare()?;
+        self.copy_up()?;
+        let file = self.storage().open(&self.path).map_err(|e| self.contextual("open", e))?;
+        self.mark_read();
+        Ok(file)
+    }
+
+    /// Opens the cache file as a seekable handle that reports its writes
+    ///
+    /// # Returns
+    /// `CacheResult<CacheFile<'_>>` - File handle, `Unsupported` for backends without files
+    pub fn open_file(&self) -> CacheResult<CacheFile<'_>> {
+        Ok(CacheFile::new(self, self.get_file()?))
     }
 
     /// Reads and returns the entire cache content as string
@@ -104,8 +490,9 @@ impl CacheObject {
     /// # Returns
     /// `CacheResult<String>` - Cache content or error
     pub fn get_string(&self) -> CacheResult<String> {
-        std::fs::read_to_string(&self.path)
-            .map_err(|e| CacheError::Io(e))
+        String::from_utf8(self.get_bytes()?).map_err(|e| {
+            CacheError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })
     }
 
     /// Writes string content to the cache file
@@ -116,8 +503,7 @@ impl CacheObject {
     /// # Returns
     /// `CacheResult<()>` - Success or error
     pub fn write_string(&self, content: &str) -> CacheResult<()> {
-        std::fs::write(&self.path, content)
-            .map_err(|e| CacheError::Io(e))
+        self.write_file(content.as_bytes())
     }
 
     /// Writes binary content to the cache file
@@ -128,8 +514,373 @@ impl CacheObject {
     /// # Returns
     /// `CacheResult<()>` - Success or error
     pub fn write_bytes(&self, content: &[u8]) -> CacheResult<()> {
-        std::fs::write(&self.path, content)
-            .map_err(|e| CacheError::Io(e))
+        self.write_file(content)
+    }
+
+    /// Writes binary content even if it exceeds `limits.max_entry_bytes`
+    ///
+    /// For deliberate exceptions to the limit, e.g. a known large asset.
+    ///
+    /// # Parameters
+    /// - `content: &[u8]` - Binary content to write
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn force_write_bytes(&self, content: &[u8]) -> CacheResult<()> {
+        self.store(content, None)
+    }
+
+    /// Fails with `SizeLimitExceeded` if the entry would grow past `limits.max_entry_bytes`
+    pub(crate) fn check_entry_size(&self, size: u64) -> CacheResult<()> {
+        let limit = self
+            .context
+            .as_ref()
+            .map_or(0, |context| context.max_entry_bytes.load(Ordering::Relaxed));
+        if limit == 0 || size <= limit {
+            return Ok(());
+        }
+        self.report(
+            "write",
+            Err(CacheError::SizeLimitExceeded(format!(
+                "Cache object '{}' would be {} bytes, above limits.max_entry_bytes ({} bytes)",
+                self.name, size, limit
+            ))),
+        )
+    }
+
+    /// Breaks content sharing with a parent cache before a write
+    pub(crate) fn unshare(&self) -> CacheResult<()> {
+        self.storage().unshare(&self.path).map_err(|e| self.contextual("unshare", e))
+    }
+
+    /// Fails with `DiskFull` if writing `size` bytes would go below `limits.min_free_bytes`
+    pub(crate) fn check_free_space(&self, size: u64) -> CacheResult<()> {
+        match &self.context {
+            Some(context) => self.report("write", context.space.ensure(self.storage(), &self.path, size)),
+            None => Ok(()),
+        }
+    }
+
+    fn write_file(&self, content: &[u8]) -> CacheResult<()> {
+        self.check_entry_size(content.len() as u64)?;
+        self.store(content, None)
+    }
+
+    /// Writes the whole content without checking the entry size limit
+    ///
+    /// `content_type` replaces the recorded MIME type, `None` clears it.
+    fn store(&self, content: &[u8], content_type: Option<&str>) -> CacheResult<()> {
+        self.check_free_space(content.len() as u64)?;
+        self.check_sealed()?;
+        self.unshare()?;
+        let mut span = OpSpan::start("write", &self.name);
+        span.bytes(content.len() as u64);
+        let op = Operation::Write { name: &self.name, size: content.len() as u64 };
+        let result = self.run(op, || {
+            self.storage().write(&self.path, content).map_err(|e| {
+                log_warn!(
+                    "failed to write cache object '{}' to {}: {}",
+                    self.name,
+                    self.path.display(),
+                    e
+                );
+                CacheError::Io(e)
+            })
+        });
+        self.report("write", span.record(result))?;
+        self.finish_write(content.len() as u64, content_type, || Ok(sha256_hex(content)))
+    }
+
+    /// Writes binary content and records its MIME type in the entry metadata
+    ///
+    /// # Parameters
+    /// - `content: &[u8]` - Binary content to write
+    /// - `content_type: &str` - MIME type, e.g. `"image/png"`
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, or `InvalidConfig` if the cache does not store metadata
+    pub fn write_bytes_with_type(&self, content: &[u8], content_type: &str) -> CacheResult<()> {
+        if self.metadata_mode() == MetadataMode::None {
+            return Err(CacheError::InvalidConfig(
+                "content types are stored in metadata, set the `metadata` config option".to_string(),
+            ));
+        }
+        self.check_entry_size(content.len() as u64)?;
+        self.store(content, Some(content_type))
+    }
+
+    /// Writes content assembled from several buffers to the cache file
+    ///
+    /// The buffers are handed to the file with vectored writes, so a header
+    /// and a body need not be concatenated first. Backends that cannot
+    /// stream (see `Storage::stream()`) receive them joined.
+    ///
+    /// # Parameters
+    /// - `bufs: &[IoSlice<'_>]` - Buffers written back to back
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn write_bytes_vectored(&self, bufs: &[IoSlice<'_>]) -> CacheResult<()> {
+        let size: u64 = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.check_entry_size(size)?;
+        self.check_free_space(size)?;
+        self.check_sealed()?;
+        self.unshare()?;
+        let mut span = OpSpan::start("write", &self.name);
+        span.bytes(size);
+        let op = Operation::Write { name: &self.name, size };
+        let result = self.run(op, || {
+            let written = match self.storage().stream(&self.path) {
+                Ok(mut stream) => write_all_vectored(&mut stream, bufs).and_then(|_| stream.flush()),
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    let joined: Vec<&[u8]> = bufs.iter().map(|buf| &**buf).collect();
+                    self.storage().write(&self.path, &joined.concat())
+                }
+                Err(e) => Err(e),
+            };
+            written.map_err(CacheError::Io)
+        });
+        self.report("write", span.record(result))?;
+        self.finish_write(size, None, || {
+            let mut hasher = Sha256::new();
+            bufs.iter().for_each(|buf| hasher.update(&**buf));
+            Ok(hex(&hasher.finalize()))
+        })
+    }
+
+    /// Updates metadata, metrics, dependents and observers after a partial write
+    ///
+    /// With `MetadataMode::Sidecar` the content is streamed back to checksum it,
+    /// writers holding the whole content use `finish_write` instead.
+    pub(crate) fn written(&self, size: u64) -> CacheResult<()> {
+        self.finish_write(size, None, || self.stream_digest())
+    }
+
+    /// Updates metadata, metrics, dependents and observers after a successful write
+    ///
+    /// `content_type` is the MIME type set by the write, any earlier one is cleared.
+    /// `digest` returns the SHA-256 of the new content, it is only called with
+    /// `MetadataMode::Sidecar`; inline headers are updated by `InlineMetaStorage` itself.
+    pub(crate) fn finish_write(
+        &self,
+        size: u64,
+        content_type: Option<&str>,
+        digest: impl FnOnce() -> CacheResult<String>,
+    ) -> CacheResult<()> {
+        match self.metadata_mode() {
+            MetadataMode::Sidecar => {
+                let sha256 = digest()?;
+                let written_by = self.app();
+                self.store_metadata(|meta| {
+                    meta.sha256 = Some(sha256);
+                    meta.written_by = written_by.or(meta.written_by.take());
+                    meta.content_type = content_type.map(str::to_string);
+                })?;
+            }
+            MetadataMode::Inline => {
+                // The header keeps its previous fields, only rewrite it when the type changes
+                let stored = self.report("metadata", self.load_metadata())?;
+                if stored.and_then(|meta| meta.content_type).as_deref() != content_type {
+                    self.store_metadata(|meta| meta.content_type = content_type.map(str::to_string))?;
+                }
+            }
+            MetadataMode::None => {}
+        }
+        #[cfg(feature = "notify")]
+        if let Some(context) = &self.context {
+            context.own_changes.note(&self.path);
+        }
+        metric_counter!("cache_lite_entry_write_bytes", size);
+        if let Some(context) = &self.context {
+            context.graph.written(&self.name);
+            context.quarantine.release(&self.name);
+            context.space.restored(&self.path);
+        }
+        self.emit(|| CacheEvent::Written {
+            name: self.name.clone(),
+            id: self.id,
+            size,
+        });
+        Ok(())
+    }
+
+    /// Computes the SHA-256 of the stored content without loading it whole
+    ///
+    /// Backends that cannot open a native file handle fall back to `read()`.
+    fn stream_digest(&self) -> CacheResult<String> {
+        let file = match self.storage().open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                let content = self.storage().read(&self.path).map_err(|e| self.contextual("write", e))?;
+                return Ok(sha256_hex(&content));
+            }
+            Err(e) => return Err(self.contextual("write", e)),
+        };
+        let mut reader = BufReader::with_capacity(self.buffer_sizes().0, file);
+        let mut hasher = Sha256::new();
+        loop {
+            let chunk = reader.fill_buf().map_err(|e| self.contextual("write", e))?;
+            if chunk.is_empty() {
+                break;
+            }
+            hasher.update(chunk);
+            let len = chunk.len();
+            reader.consume(len);
+        }
+        Ok(hex(&hasher.finalize()))
+    }
+
+    /// Returns the `metadata` mode of the owning cache
+    fn metadata_mode(&self) -> MetadataMode {
+        self.context.as_ref().map_or(MetadataMode::None, |context| context.metadata())
+    }
+
+    /// Returns the `app` configured for the owning cache
+    fn app(&self) -> Option<AppInfo> {
+        let context = self.context.as_ref()?;
+        context.app.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the metadata of a new entry, holding its creation time and writer
+    fn fresh_metadata(&self) -> EntryMeta {
+        EntryMeta {
+            created_at: self.created_at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            written_by: self.app(),
+            ..EntryMeta::default()
+        }
+    }
+
+    /// Reads the stored metadata, `None` if there is none
+    fn load_metadata(&self) -> CacheResult<Option<EntryMeta>> {
+        match (self.metadata_mode(), self.context.as_ref().and_then(|c| c.inline.as_ref())) {
+            (MetadataMode::Inline, Some(inline)) => inline.read_meta(&self.path),
+            (MetadataMode::Sidecar, _) => EntryMeta::load(self.storage(), &self.path),
+            _ => Ok(None),
+        }
+    }
+
+    /// Stores metadata in the sidecar or header of the entry
+    fn save_metadata(&self, meta: &EntryMeta) -> CacheResult<()> {
+        match (self.metadata_mode(), self.context.as_ref().and_then(|c| c.inline.as_ref())) {
+            (MetadataMode::Inline, Some(inline)) => inline.write_meta(&self.path, meta),
+            (MetadataMode::Sidecar, _) => meta.save(self.storage(), &self.path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes fresh metadata for a newly created entry, if metadata is stored
+    pub(crate) fn reset_metadata(&self) -> CacheResult<()> {
+        let result = self.save_metadata(&self.fresh_metadata());
+        self.report("metadata", result)
+    }
+
+    /// Loads the stored metadata, applies `update` and stores it again
+    fn store_metadata(&self, update: impl FnOnce(&mut EntryMeta)) -> CacheResult<()> {
+        let result = self.load_metadata().and_then(|meta| {
+            let mut meta = meta.unwrap_or_else(|| self.fresh_metadata());
+            update(&mut meta);
+            self.save_metadata(&meta)
+        });
+        self.report("metadata", result)
+    }
+
+    /// Returns the metadata of the entry
+    ///
+    /// Without stored metadata (`MetadataMode::None` or an entry written
+    /// by another tool) only `created_at` is set.
+    ///
+    /// # Returns
+    /// `CacheResult<EntryMeta>` - Metadata or error reading it
+    pub fn metadata(&self) -> CacheResult<EntryMeta> {
+        let meta = self.report("metadata", self.load_metadata())?;
+        Ok(meta.unwrap_or_else(|| self.fresh_metadata()))
+    }
+
+    /// Changes the stored metadata of the entry
+    ///
+    /// `sha256` is overwritten on the next write.
+    ///
+    /// # Parameters
+    /// - `update: impl FnOnce(&mut EntryMeta)` - Edits the current metadata
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, or `InvalidConfig` if the cache does not store metadata
+    pub fn update_metadata(&self, update: impl FnOnce(&mut EntryMeta)) -> CacheResult<()> {
+        if self.metadata_mode() == MetadataMode::None {
+            return Err(CacheError::InvalidConfig(
+                "metadata is not stored, set the `metadata` config option".to_string(),
+            ));
+        }
+        self.store_metadata(update)
+    }
+
+    /// Returns a buffered writer streaming new content to the cache file
+    ///
+    /// The content is streamed to a `.partial` file next to the entry,
+    /// without building it in memory first (backends that cannot stream
+    /// receive it on `finish()`). Call `CacheWriter::finish()` to replace
+    /// the entry and report the write; dropping the writer instead leaves
+    /// the previous content in place.
+    ///
+    /// # Parameters
+    /// - `capacity: usize` - Buffer size in bytes
+    ///
+    /// # Returns
+    /// `CacheResult<CacheWriter<'_>>` - Writer implementing `std::io::Write`
+    pub fn buffered_writer(&self, capacity: usize) -> CacheResult<CacheWriter<'_>> {
+        CacheWriter::new(self, capacity)
+    }
+
+    /// Serializes a value with the cache's codec and writes it
+    ///
+    /// # Parameters
+    /// - `value: &T` - Value to store
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn write_value<T: serde::Serialize + ?Sized>(&self, value: &T) -> CacheResult<()> {
+        match &self.context {
+            Some(context) => self.write_value_with(&*context.codec(), value),
+            None => self.write_value_with(&JsonCodec, value),
+        }
+    }
+
+    /// Serializes a value with the given codec and writes it
+    ///
+    /// # Parameters
+    /// - `codec: &dyn Codec` - Codec to use for this call
+    /// - `value: &T` - Value to store
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn write_value_with<T: serde::Serialize + ?Sized>(
+        &self,
+        codec: &dyn Codec,
+        value: &T,
+    ) -> CacheResult<()> {
+        self.write_file(&codec::encode(codec, value)?)
+    }
+
+    /// Reads the cache content and deserializes it with the cache's codec
+    ///
+    /// # Returns
+    /// `CacheResult<T>` - Decoded value or error
+    pub fn get_value<T: serde::de::DeserializeOwned>(&self) -> CacheResult<T> {
+        match &self.context {
+            Some(context) => self.get_value_with(&*context.codec()),
+            None => self.get_value_with(&JsonCodec),
+        }
+    }
+
+    /// Reads the cache content and deserializes it with the given codec
+    ///
+    /// # Parameters
+    /// - `codec: &dyn Codec` - Codec to use for this call
+    ///
+    /// # Returns
+    /// `CacheResult<T>` - Decoded value or error
+    pub fn get_value_with<T: serde::de::DeserializeOwned>(&self, codec: &dyn Codec) -> CacheResult<T> {
+        codec::decode(codec, &self.get_bytes()?)
     }
 
     /// Reads and returns the entire cache content as bytes
@@ -137,28 +888,515 @@ impl CacheObject {
     /// # Returns
     /// `CacheResult<Vec<u8>>` - Cache content or error
     pub fn get_bytes(&self) -> CacheResult<Vec<u8>> {
-        std::fs::read(&self.path)
-            .map_err(|e| CacheError::Io(e))
+        self.check_quarantine()?;
+        let span = OpSpan::start("read", &self.name);
+        let start = Instant::now();
+        let result = self.run(Operation::Read { name: &self.name }, || {
+            match self.base_fallback() {
+                Some(base) => std::fs::read(base).map_err(CacheError::Io),
+                None => self.storage().read(&self.path).map_err(CacheError::Io),
+            }
+        });
+        self.finish_read(span, start, result)
+    }
+
+    /// Records a finished read in metrics, events and the quarantine
+    fn finish_read(&self, mut span: OpSpan, start: Instant, result: CacheResult<Vec<u8>>) -> CacheResult<Vec<u8>> {
+        metric_histogram!("cache_lite_get_latency_seconds", start.elapsed().as_secs_f64(), "op" => "read");
+        if let Ok(content) = &result {
+            self.mark_read();
+            span.bytes(content.len() as u64);
+            self.emit(|| CacheEvent::Read {
+                name: self.name.clone(),
+                id: self.id,
+                size: content.len() as u64,
+            });
+        }
+        let result = self.report("read", span.record(result));
+        if let Some(context) = &self.context {
+            match &result {
+                Ok(_) => context.quarantine.record_success(&self.name),
+                Err(e) if context.quarantine.record_failure(&self.name, e) => {
+                    log_warn!("quarantined cache object '{}' after repeated read failures: {}", self.name, e);
+                }
+                Err(_) => {}
+            }
+        }
+        result
+    }
+
+    /// Fails with `EntryNotFound` if repeated read failures quarantined the object
+    fn check_quarantine(&self) -> CacheResult<()> {
+        match &self.context {
+            Some(context) if context.quarantine.contains(&self.name) => {
+                Err(CacheError::EntryNotFound { name: self.name.clone() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the cache content together with its content type
+    ///
+    /// # Returns
+    /// `CacheResult<(Vec<u8>, Option<String>)>` - Content and the MIME type
+    /// recorded by `write_bytes_with_type()`, if any
+    pub fn get_bytes_with_type(&self) -> CacheResult<(Vec<u8>, Option<String>)> {
+        let content = self.get_bytes()?;
+        Ok((content, self.content_type()?))
+    }
+
+    /// Reads the cache content into a reference-counted `Bytes` buffer
+    ///
+    /// The buffer takes over the read allocation without copying it, and
+    /// clones share it, so a large payload can be handed to several tasks
+    /// or channels cheaply.
+    ///
+    /// # Returns
+    /// `CacheResult<bytes::Bytes>` - Cache content or error
+    #[cfg(feature = "bytes")]
+    pub fn get_bytes_shared(&self) -> CacheResult<bytes::Bytes> {
+        self.get_bytes().map(bytes::Bytes::from)
+    }
+
+    /// Starts a staged write of content pushed chunk by chunk
+    ///
+    /// Meant for multi-gigabyte entries: the content goes to a temporary
+    /// file that replaces the entry on `ChunkedWrite::commit()`, so readers
+    /// never see a partially written entry.
+    ///
+    /// # Returns
+    /// `CacheResult<ChunkedWrite<'_>>` - The staged write
+    pub fn begin_write(&self) -> CacheResult<ChunkedWrite<'_>> {
+        ChunkedWrite::begin(self)
+    }
+
+    /// Reads the cache content without blocking the async runtime
+    ///
+    /// The read runs on Tokio's blocking thread pool, like `tokio::fs`, so
+    /// it works with every storage backend. Must be called within a Tokio
+    /// runtime. With the `io-uring` feature on Linux, objects of a plain
+    /// `FsStorage` cache without middleware or retries are read through a
+    /// shared io_uring instead; kernels refusing io_uring fall back to the
+    /// blocking pool.
+    ///
+    /// # Returns
+    /// `CacheResult<Vec<u8>>` - Cache content or error
+    #[cfg(feature = "async")]
+    pub async fn get_bytes_async(self: &Arc<Self>) -> CacheResult<Vec<u8>> {
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if let Some(driver) = self.uring_driver() {
+            self.check_quarantine()?;
+            let span = OpSpan::start("read", &self.name);
+            let start = Instant::now();
+            let path = self.base_fallback().unwrap_or(&self.path);
+            let result = driver.read(path).await.map_err(CacheError::Io);
+            return self.finish_read(span, start, result);
+        }
+        let object = Arc::clone(self);
+        tokio::task::spawn_blocking(move || object.get_bytes())
+            .await
+            .map_err(|e| CacheError::Task { operation: "read", source: Box::new(e) })?
+    }
+
+    /// Returns the io_uring driver if this object's I/O may bypass the storage backend
+    ///
+    /// Only plain file backends without middleware or retries qualify,
+    /// everything else keeps going through the blocking pool.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    fn uring_driver(&self) -> Option<&'static crate::uring::Driver> {
+        let context = self.context.as_ref()?;
+        if !context.storage.is_plain_file() || !context.middleware.is_passthrough() {
+            return None;
+        }
+        crate::uring::driver()
+    }
+
+    /// Writes binary content without blocking the async runtime
+    ///
+    /// See `get_bytes_async()`.
+    ///
+    /// # Parameters
+    /// - `content: impl Into<Vec<u8>>` - Binary content to write
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    #[cfg(feature = "async")]
+    pub async fn write_bytes_async(self: &Arc<Self>, content: impl Into<Vec<u8>>) -> CacheResult<()> {
+        let content = content.into();
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if let Some(driver) = self.uring_driver() {
+            let size = content.len() as u64;
+            self.check_entry_size(size)?;
+            self.check_free_space(size)?;
+            self.check_sealed()?;
+            self.unshare()?;
+            let mut span = OpSpan::start("write", &self.name);
+            span.bytes(size);
+            let digest = (self.metadata_mode() == MetadataMode::Sidecar).then(|| sha256_hex(&content));
+            let result = driver.write(&self.path, content).await.map_err(CacheError::Io);
+            self.report("write", span.record(result))?;
+            return self.finish_write(size, None, || digest.map_or_else(|| self.stream_digest(), Ok));
+        }
+        let object = Arc::clone(self);
+        tokio::task::spawn_blocking(move || object.write_bytes(&content))
+            .await
+            .map_err(|e| CacheError::Task { operation: "write", source: Box::new(e) })?
+    }
+
+    /// Streams the content of `reader` into the cache file
+    ///
+    /// Uses the `io.write_buffer` size of the cache config, see
+    /// `copy_from_reader_with()`.
+    ///
+    /// # Parameters
+    /// - `reader: &mut impl Read` - Source of the new content
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Number of bytes copied
+    pub fn copy_from_reader(&self, reader: &mut impl Read) -> CacheResult<u64> {
+        self.copy_from_reader_with(reader, self.buffer_sizes().1)
+    }
+
+    /// Streams the content of `reader` into the cache file with a given buffer size
+    ///
+    /// Nothing is held in memory beyond the buffer, e.g. when downloading
+    /// straight into an entry.
+    ///
+    /// # Parameters
+    /// - `reader: &mut impl Read` - Source of the new content
+    /// - `buffer_size: usize` - Copy buffer size in bytes
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Number of bytes copied
+    pub fn copy_from_reader_with(&self, reader: &mut impl Read, buffer_size: usize) -> CacheResult<u64> {
+        let mut writer = self.buffered_writer(buffer_size)?;
+        let copied = std::io::copy(reader, &mut writer);
+        self.report("write", copied.map_err(CacheError::Io))?;
+        writer.finish().map(|summary| summary.size)
+    }
+
+    /// Streams the cache content into `writer`
+    ///
+    /// Uses the `io.read_buffer` size of the cache config, see
+    /// `copy_to_writer_with()`.
+    ///
+    /// # Parameters
+    /// - `writer: &mut impl Write` - Destination of the content
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Number of bytes copied
+    pub fn copy_to_writer(&self, writer: &mut impl Write) -> CacheResult<u64> {
+        self.copy_to_writer_with(writer, self.buffer_sizes().0)
+    }
+
+    /// Streams the cache content into `writer` with a given buffer size
+    ///
+    /// Backends that cannot open entries as files are read in one piece.
+    ///
+    /// # Parameters
+    /// - `writer: &mut impl Write` - Destination of the content
+    /// - `buffer_size: usize` - Copy buffer size in bytes
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Number of bytes copied
+    pub fn copy_to_writer_with(&self, writer: &mut impl Write, buffer_size: usize) -> CacheResult<u64> {
+        self.check_quarantine()?;
+        let mut span = OpSpan::start("read", &self.name);
+        // Only opening the entry is retried, bytes already handed to `writer` cannot be taken back
+        let source = self.run(Operation::Read { name: &self.name }, || {
+            let file = match self.base_fallback() {
+                Some(base) => std::fs::File::open(base),
+                None if !self.storage().exists(&self.path) => {
+                    return Err(CacheError::Io(std::io::ErrorKind::NotFound.into()));
+                }
+                None => self.storage().open(&self.path),
+            };
+            match file {
+                Ok(file) => Ok(Ok(file)),
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    Ok(Err(self.storage().read(&self.path)?))
+                }
+                Err(e) => Err(CacheError::Io(e)),
+            }
+        });
+        let result = source.and_then(|source| {
+            let copied = match source {
+                Ok(file) => std::io::copy(&mut BufReader::with_capacity(buffer_size, file), writer),
+                Err(content) => writer.write_all(&content).map(|_| content.len() as u64),
+            };
+            copied.map_err(CacheError::Io)
+        });
+        if let Ok(size) = result {
+            self.mark_read();
+            span.bytes(size);
+            self.emit(|| CacheEvent::Read {
+                name: self.name.clone(),
+                id: self.id,
+                size,
+            });
+        }
+        self.report("read", span.record(result))
+    }
+
+    /// Maps the cache file into memory for in-place updates
+    ///
+    /// The file is resized to `len` bytes (zero-filled when it grows), so
+    /// fixed-size records such as ring buffers or bitmaps can be changed
+    /// without rewriting the whole file. Only storage backends handing out
+    /// files (see `Storage::open()`) support it.
+    ///
+    /// # Safety
+    /// The file must not be truncated or resized by anyone else (another
+    /// process, `write_bytes()`, `delete()`, ...) while the mapping is
+    /// alive; accessing pages beyond the new end is undefined behavior.
+    ///
+    /// # Parameters
+    /// - `len: usize` - Size of the file and the mapping in bytes
+    ///
+    /// # Returns
+    /// `CacheResult<CacheMmapMut<'_>>` - Writable mapping
+    #[cfg(feature = "mmap")]
+    pub unsafe fn mmap_mut(&self, len: usize) -> CacheResult<CacheMmapMut<'_>> {
+        self.check_entry_size(len as u64)?;
+        self.check_free_space(len as u64)?;
+        self.check_sealed()?;
+        self.unshare()?;
+        self.copy_up()?;
+        let file = self.storage().open(&self.path).map_err(|e| self.contextual("mmap", e))?;
+        file.set_len(len as u64).map_err(|e| self.contextual("mmap", e))?;
+        // SAFETY: the caller guarantees the file is not resized while mapped
+        let map = unsafe { memmap2::MmapMut::map_mut(&file) }.map_err(|e| self.contextual("mmap", e))?;
+        self.mark_read();
+        Ok(CacheMmapMut::new(self, map))
+    }
+
+    /// Writes `content` at `offset`, leaving the rest of the file untouched
+    ///
+    /// Writing past the end extends the file; the skipped range reads as
+    /// zeros and, on filesystems supporting sparse files (ext4, XFS, APFS,
+    /// ...), takes no disk space until written. This lets resumable or
+    /// out-of-order downloads fill an entry piece by piece.
+    ///
+    /// # Parameters
+    /// - `offset: u64` - Position of the first byte
+    /// - `content: &[u8]` - Bytes to write
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn write_at(&self, offset: u64, content: &[u8]) -> CacheResult<()> {
+        let end = offset.checked_add(content.len() as u64).ok_or_else(|| {
+            CacheError::SizeLimitExceeded(format!(
+                "Writing {} bytes at offset {} overflows the size of cache object '{}'",
+                content.len(),
+                offset,
+                self.name
+            ))
+        })?;
+        self.check_entry_size(end)?;
+        self.check_free_space(content.len() as u64)?;
+        self.check_sealed()?;
+        self.unshare()?;
+        self.copy_up()?;
+        let mut span = OpSpan::start("write", &self.name);
+        span.bytes(content.len() as u64);
+        let op = Operation::Write { name: &self.name, size: content.len() as u64 };
+        let result = self.run(op, || {
+            let written = match self.storage().open(&self.path) {
+                Ok(mut file) => file
+                    .seek(SeekFrom::Start(offset))
+                    .and_then(|_| file.write_all(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    // Only a missing entry starts out empty, other errors would lose its content
+                    let mut data = match self.storage().read(&self.path) {
+                        Ok(data) => data,
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                        Err(e) => return Err(CacheError::Io(e)),
+                    };
+                    let start = usize::try_from(offset).map_err(|_| CacheError::SizeLimitExceeded(
+                        format!("Offset {} does not fit in memory", offset),
+                    ))?;
+                    let end = start.checked_add(content.len()).ok_or_else(|| CacheError::SizeLimitExceeded(
+                        format!("Offset {} does not fit in memory", offset),
+                    ))?;
+                    if data.len() < end {
+                        data.resize(end, 0);
+                    }
+                    data[start..end].copy_from_slice(content);
+                    self.storage().write(&self.path, &data)
+                }
+                Err(e) => Err(e),
+            };
+            written.map_err(CacheError::Io)
+        });
+        self.report("write", span.record(result))?;
+        self.written(content.len() as u64)
+    }
+
+    /// Returns the disk space actually used by the cache file
+    ///
+    /// Smaller than `size()` for sparse files with unwritten ranges, larger
+    /// when space was reserved with `reserve()` or rounded up to whole
+    /// blocks. Backends without files report the logical size.
+    ///
+    /// # Returns
+    /// `CacheResult<u64>` - Allocated size in bytes or error
+    pub fn allocated_size(&self) -> CacheResult<u64> {
+        let file = match self.base_fallback() {
+            Some(base) => std::fs::File::open(base),
+            None => self.storage().open(&self.path),
+        };
+        match file {
+            Ok(file) => fs4::FileExt::allocated_size(&file).map_err(|e| self.contextual("size", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => self.size(),
+            Err(e) => Err(self.contextual("size", e)),
+        }
+    }
+
+    /// Reserves disk space for `len` bytes of content upfront
+    ///
+    /// Uses `fallocate` on Linux and `SetFileInformationByHandle` on
+    /// Windows, so a large download fails before it starts when the disk
+    /// cannot hold it. The file is extended to at least `len` bytes; fill
+    /// it in place (e.g. with `mmap_mut()`) rather than rewriting it, which
+    /// would release the reservation. Backends without files ignore it.
+    ///
+    /// # Parameters
+    /// - `len: u64` - Number of bytes to reserve
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, or `DiskFull` if the disk cannot hold `len` bytes
+    pub fn reserve(&self, len: u64) -> CacheResult<()> {
+        self.check_entry_size(len)?;
+        self.check_free_space(len)?;
+        self.check_sealed()?;
+        self.unshare()?;
+        self.copy_up()?;
+        let file = match self.storage().open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(()),
+            Err(e) => return self.report("reserve", Err(CacheError::Io(e))),
+        };
+        let result = fs4::FileExt::allocate(&file, len).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::StorageFull {
+                let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                let available = fs4::available_space(dir).unwrap_or(0);
+                CacheError::DiskFull { available, required: len }
+            } else {
+                CacheError::Io(e)
+            }
+        });
+        self.report("reserve", result)?;
+        #[cfg(feature = "notify")]
+        if let Some(context) = &self.context {
+            context.own_changes.note(&self.path);
+        }
+        Ok(())
     }
 
     /// Deletes the cache object and its file
+    ///
+    /// While other clones of this object exist the file is only marked
+    /// for removal; it is unlinked when the last clone is dropped, so
+    /// readers holding a clone keep working. `Arc`s of one object count
+    /// as a single clone.
     /// 
     /// # Returns
     /// `CacheResult<()>` - Success or error
     pub fn delete(&self) -> CacheResult<()> {
-        if self.path.exists() {
-            std::fs::remove_file(&self.path)
-                .map_err(|e| CacheError::Io(e))?;
+        self.delete_held(false)
+    }
+
+    /// Deletes the object like `delete()`, leaving the file to the last
+    /// handle if `held`, e.g. when other `Arc`s of it exist
+    pub(crate) fn delete_held(&self, held: bool) -> CacheResult<()> {
+        self.check_writable()?;
+        self.remove_file(held)?;
+        self.emit(|| CacheEvent::Removed {
+            name: self.name.clone(),
+            id: self.id,
+        });
+        Ok(())
+    }
+
+    /// Checks whether other clones of the object exist, see `delete()`
+    pub(crate) fn has_clones(&self) -> bool {
+        Arc::strong_count(&self.handle) > 1
+    }
+
+    /// Removes the file and sidecar of the object
+    ///
+    /// With `in_use`, or while other clones exist, the removal is left to
+    /// the last clone dropped.
+    pub(crate) fn remove_file(&self, in_use: bool) -> CacheResult<()> {
+        if in_use || self.has_clones() {
+            // Another clone still uses the file, the last one dropped removes it
+            if let Some(context) = &self.context {
+                context.defer_delete(&self.path);
+            }
+            *self.handle.pending_delete.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some((self.context.clone(), self.path.clone()));
+        } else if self.storage().exists(&self.path) {
+            // Base layer files are read-only and stay in place
+            let result = self.run(Operation::Remove { name: &self.name }, || {
+                self.storage().remove(&self.path).map_err(CacheError::Io)
+            });
+            self.report("delete", result)?;
+            let sidecar = sidecar_path(&self.path);
+            if self.storage().exists(&sidecar) {
+                self.report("delete", self.storage().remove(&sidecar).map_err(CacheError::Io))?;
+            }
+            #[cfg(feature = "notify")]
+            if let Some(context) = &self.context {
+                context.own_changes.note(&self.path);
+            }
         }
         Ok(())
     }
 
+    /// Returns the MIME type recorded for the content, if any
+    ///
+    /// # Returns
+    /// `CacheResult<Option<String>>` - Content type or error reading the metadata
+    pub fn content_type(&self) -> CacheResult<Option<String>> {
+        Ok(self.metadata()?.content_type)
+    }
+
+    /// Stores a structured value in the user metadata of the entry
+    ///
+    /// # Parameters
+    /// - `key: &str` - Metadata key, replacing any previous value
+    /// - `value: &T` - Value serialized as JSON
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success, or `InvalidConfig` if the cache does not store metadata
+    pub fn set_meta<T: serde::Serialize + ?Sized>(&self, key: &str, value: &T) -> CacheResult<()> {
+        let value = serde_json::to_value(value).map_err(|e| CacheError::Serialization(e.to_string()))?;
+        self.update_metadata(|meta| {
+            meta.user.insert(key.to_string(), value);
+        })
+    }
+
+    /// Reads a structured value from the user metadata of the entry
+    ///
+    /// # Parameters
+    /// - `key: &str` - Metadata key
+    ///
+    /// # Returns
+    /// `CacheResult<Option<T>>` - Value, `None` if the key is not set
+    pub fn get_meta<T: serde::de::DeserializeOwned>(&self, key: &str) -> CacheResult<Option<T>> {
+        self.metadata()?
+            .user
+            .remove(key)
+            .map(|value| serde_json::from_value(value).map_err(|e| CacheError::Serialization(e.to_string())))
+            .transpose()
+    }
+
     /// Checks if the cache file exists
     /// 
     /// # Returns
     /// `bool` - True if the cache file exists
     pub fn exists(&self) -> bool {
-        self.path.exists()
+        self.storage().exists(&self.path) || self.base.as_deref().is_some_and(Path::is_file)
     }
 
     /// Gets the file size in bytes
@@ -166,9 +1404,77 @@ impl CacheObject {
     /// # Returns
     /// `CacheResult<u64>` - File size in bytes or error
     pub fn size(&self) -> CacheResult<u64> {
-        std::fs::metadata(&self.path)
-            .map(|metadata| metadata.len())
-            .map_err(|e| CacheError::Io(e))
+        match self.base_fallback() {
+            Some(base) => std::fs::metadata(base)
+                .map(|meta| meta.len())
+                .map_err(|e| CacheError::from(e).with_context("size", base, Some(&self.name))),
+            None => self.storage().size(&self.path).map_err(|e| self.contextual("size", e)),
+        }
+    }
+
+    /// Records a source file this object was derived from
+    ///
+    /// The path is stored with the file's modification time and SHA-256
+    /// checksum, so `is_stale()` can tell when the source changed. Calling
+    /// it again for the same path records the current state.
+    ///
+    /// # Parameters
+    /// - `path: impl AsRef<Path>` - Source file, which must exist
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error reading the source
+    pub fn depends_on(&self, path: impl AsRef<Path>) -> CacheResult<()> {
+        let dependency = SourceDependency::record(path.as_ref())?;
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources.retain(|source| source.path != dependency.path);
+        sources.push(dependency);
+        Ok(())
+    }
+
+    /// Checks whether any source recorded with `depends_on()` changed
+    ///
+    /// Sources with an unchanged modification time are not re-read; a
+    /// missing source counts as changed. Objects whose source object was
+    /// rewritten or removed since (see `Cache::add_dependency()`) are
+    /// stale as well.
+    ///
+    /// # Returns
+    /// `bool` - True if the cached content is out of date
+    pub fn is_stale(&self) -> bool {
+        if let Some(context) = &self.context
+            && context.graph.is_invalidated(&self.name)
+        {
+            return true;
+        }
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(SourceDependency::has_changed)
+    }
+
+    /// Returns the source files recorded with `depends_on()`
+    ///
+    /// # Returns
+    /// `Vec<PathBuf>` - Source paths in recording order
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|source| source.path.clone())
+            .collect()
+    }
+
+    /// Returns the recorded source dependencies
+    pub(crate) fn sources(&self) -> Vec<SourceDependency> {
+        self.sources.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Restores source dependencies of an imported object
+    pub(crate) fn with_sources(self, sources: Vec<SourceDependency>) -> Self {
+        *self.sources.lock().unwrap_or_else(|e| e.into_inner()) = sources;
+        self
     }
 
     /// Checks if the cache has expired based on its lifecycle policy
@@ -187,7 +1493,49 @@ impl Clone for CacheObject {
             name: self.name.clone(),
             path: self.path.clone(),
             id: self.id,
-            created_at: self.created_at
+            created_at: self.created_at,
+            context: self.context.clone(),
+            base: self.base.clone(),
+            sources: Arc::clone(&self.sources),
+            generation: self.generation,
+            detached: Arc::clone(&self.detached),
+            moved_to: Arc::clone(&self.moved_to),
+            read: Arc::clone(&self.read),
+            reads_tracked: self.reads_tracked,
+            sealed: Arc::clone(&self.sealed),
+            read_only: self.read_only,
+            shared: self.shared,
+            handle: Arc::clone(&self.handle)
         }
     }
-}
\ No newline at end of file
+}
+
+/// Lets objects be passed wherever a path is expected, e.g. `std::fs::metadata(&*obj)`
+impl AsRef<Path> for CacheObject {
+    /// Does not count as a read, use `path_for_read()` when consuming the file
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Lets objects be used as lookup keys, e.g. `cache.get(&*obj)`
+impl Borrow<str> for CacheObject {
+    fn borrow(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Writes every buffer, retrying on short vectored writes
+fn write_all_vectored(writer: &mut impl Write, bufs: &[IoSlice<'_>]) -> std::io::Result<()> {
+    let mut slices: Vec<IoSlice<'_>> = bufs.iter().copied().filter(|buf| !buf.is_empty()).collect();
+    let mut slices = slices.as_mut_slice();
+    while !slices.is_empty() {
+        match writer.write_vectored(slices) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(n) => IoSlice::advance_slices(&mut slices, n),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
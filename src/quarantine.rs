@@ -0,0 +1,117 @@
+/*
+ * @filename: quarantine.rs
+ * @description: Skip-list of entries whose reads keep failing
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::CacheError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+/// Read failure counts of the objects of one cache
+///
+/// An object whose reads fail `quarantine_after` times in a row is
+/// quarantined: lookups report it as not found until it is written again
+/// or released with `Cache::release_quarantine()`.
+#[derive(Debug, Default)]
+pub(crate) struct Quarantine {
+    /// `quarantine_after` of the current config, 0 disables quarantining
+    threshold: AtomicU32,
+    /// Consecutive read failures per object name
+    failures: Mutex<HashMap<String, u32>>,
+}
+
+impl Quarantine {
+    pub(crate) fn new(threshold: u32) -> Self {
+        Quarantine {
+            threshold: AtomicU32::new(threshold),
+            failures: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn set_threshold(&self, threshold: u32) {
+        self.threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Checks whether reads of `name` are currently refused
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        threshold > 0 && self.lock().get(name).is_some_and(|failures| *failures >= threshold)
+    }
+
+    /// Counts a failed read, returning true if it quarantined `name`
+    ///
+    /// Only errors pointing at a broken file count: I/O errors other than
+    /// a missing file or a transient condition, and corrupted content.
+    pub(crate) fn record_failure(&self, name: &str, error: &CacheError) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        let counted = match error {
+            CacheError::Io(err) => err.kind() != std::io::ErrorKind::NotFound && !error.is_retryable(),
+            CacheError::Corrupted(_) => true,
+            _ => false,
+        };
+        if threshold == 0 || !counted {
+            return false;
+        }
+        let mut failures = self.lock();
+        let count = failures.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        *count == threshold
+    }
+
+    /// Resets the failure count of `name` after a successful read
+    pub(crate) fn record_success(&self, name: &str) {
+        if self.threshold.load(Ordering::Relaxed) > 0 {
+            self.lock().remove(name);
+        }
+    }
+
+    /// Forgets the failures of `name`, returning true if it was quarantined
+    pub(crate) fn release(&self, name: &str) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        self.lock()
+            .remove(name)
+            .is_some_and(|failures| threshold > 0 && failures >= threshold)
+    }
+
+    /// Returns the names of all quarantined objects, sorted
+    pub(crate) fn names(&self) -> Vec<String> {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        if threshold == 0 {
+            return Vec::new();
+        }
+        let mut names: Vec<String> = self
+            .lock()
+            .iter()
+            .filter(|(_, failures)| **failures >= threshold)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, u32>> {
+        self.failures.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * @filename: registry.rs
+ * @description: Object registry of a cache indexed by name and by file path
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::object::CacheObject;
+use crate::space::RegisteredFiles;
+use crate::utils::path_key;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Registered objects by name, with an index of the files they use
+///
+/// Reads go through `Deref` to the name map; every change goes through
+/// the methods below so the path index stays in sync.
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+    objects: BTreeMap<String, Arc<CacheObject>>,
+    /// Names of the objects using each file, by `path_key()`
+    paths: HashMap<PathBuf, BTreeSet<String>>,
+    /// Registered files shared with the `SpaceGuard` of the cache
+    files: Arc<RegisteredFiles>,
+}
+
+impl Deref for Registry {
+    type Target = BTreeMap<String, Arc<CacheObject>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.objects
+    }
+}
+
+impl Registry {
+    /// Creates an empty registry reporting its files to `files`
+    pub(crate) fn tracking(files: Arc<RegisteredFiles>) -> Self {
+        Registry { files, ..Registry::default() }
+    }
+
+    fn index(&mut self, name: &str, obj: &Arc<CacheObject>) {
+        self.paths.entry(path_key(obj.path())).or_default().insert(name.to_string());
+        self.files.add(obj);
+    }
+
+    fn unindex(&mut self, name: &str, obj: &Arc<CacheObject>) {
+        self.files.remove(obj);
+        let key = path_key(obj.path());
+        if let Some(names) = self.paths.get_mut(&key) {
+            names.remove(name);
+            if names.is_empty() {
+                self.paths.remove(&key);
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, name: String, obj: Arc<CacheObject>) -> Option<Arc<CacheObject>> {
+        let old = self.objects.insert(name.clone(), Arc::clone(&obj));
+        if let Some(old) = &old {
+            self.unindex(&name, old);
+        }
+        self.index(&name, &obj);
+        old
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Arc<CacheObject>> {
+        let old = self.objects.remove(name)?;
+        self.unindex(name, &old);
+        Some(old)
+    }
+
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&String, &mut Arc<CacheObject>) -> bool) {
+        let mut removed = Vec::new();
+        self.objects.retain(|name, obj| {
+            let kept = keep(name, obj);
+            if !kept {
+                removed.push((name.clone(), Arc::clone(obj)));
+            }
+            kept
+        });
+        for (name, obj) in removed {
+            self.unindex(&name, &obj);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.objects.clear();
+        self.paths.clear();
+        self.files.clear();
+    }
+
+    /// Returns the name of an object using `path`, other than `except`
+    pub(crate) fn path_owner(&self, path: &Path, except: Option<&str>) -> Option<&str> {
+        self.paths
+            .get(&path_key(path))?
+            .iter()
+            .map(String::as_str)
+            .find(|name| except != Some(*name))
+    }
+}
+
+/// Objects added through a shared `&Cache`, awaiting registration
+///
+/// The list only grows until it is taken with `&mut`, so lookups and
+/// iteration can hand out references while other threads add objects.
+#[derive(Debug, Default)]
+pub(crate) struct PendingObjects {
+    head: OnceLock<Box<PendingNode>>,
+    /// Serializes additions, so a name is only added once
+    adding: Mutex<()>,
+}
+
+#[derive(Debug)]
+struct PendingNode {
+    obj: Arc<CacheObject>,
+    next: OnceLock<Box<PendingNode>>,
+}
+
+impl PendingObjects {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Arc<CacheObject>> {
+        std::iter::successors(self.head.get(), |node| node.next.get()).map(|node| &node.obj)
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Arc<CacheObject>> {
+        self.iter().find(|obj| obj.name() == name)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.head.get().is_none()
+    }
+
+    /// Returns the object pending under `name`, adding the one `create` makes if none is
+    pub(crate) fn get_or_add(
+        &self,
+        name: &str,
+        create: impl FnOnce() -> crate::CacheResult<Arc<CacheObject>>,
+    ) -> crate::CacheResult<Arc<CacheObject>> {
+        let _adding = self.adding.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(obj) = self.get(name) {
+            return Ok(Arc::clone(obj));
+        }
+        let obj = create()?;
+        let mut tail = &self.head;
+        while let Some(node) = tail.get() {
+            tail = &node.next;
+        }
+        let _ = tail.set(Box::new(PendingNode {
+            obj: Arc::clone(&obj),
+            next: OnceLock::new(),
+        }));
+        Ok(obj)
+    }
+
+    /// Removes and returns every pending object, in the order they were added
+    pub(crate) fn take(&mut self) -> Vec<Arc<CacheObject>> {
+        let mut taken = Vec::new();
+        let mut next = self.head.take();
+        while let Some(mut node) = next {
+            next = node.next.take();
+            taken.push(node.obj);
+        }
+        taken
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * @filename: remote.rs
+ * @description: Pushing and pulling caches to and from HTTP endpoints
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cache::Cache;
+use crate::config::MetadataMode;
+use crate::manifest::MANIFEST_FILE;
+use crate::{CacheError, CacheResult};
+
+/// Percent-encodes an object name for use as a URL path segment
+fn encode_segment(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Prefix keeping object URLs apart from `manifest.json`
+const OBJECTS_PREFIX: &str = "objects";
+
+fn manifest_url(base: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), MANIFEST_FILE)
+}
+
+fn entry_url(base: &str, name: &str) -> String {
+    format!("{}/{}/{}", base.trim_end_matches('/'), OBJECTS_PREFIX, encode_segment(name))
+}
+
+fn http_error(url: &str, err: ureq::Error) -> CacheError {
+    match err {
+        ureq::Error::Io(e) => CacheError::Io(e),
+        ureq::Error::StatusCode(404) => CacheError::NotFound(format!("{} returned 404", url)),
+        e => CacheError::Io(std::io::Error::other(format!("{}: {}", url, e))),
+    }
+}
+
+fn put(url: &str, body: &[u8], content_type: Option<&str>) -> CacheResult<()> {
+    let request = ureq::put(url);
+    let request = match content_type {
+        Some(content_type) => request.header("Content-Type", content_type),
+        None => request,
+    };
+    request.send(body).map_err(|e| http_error(url, e))?;
+    Ok(())
+}
+
+/// Fetches a URL, returning the body and its `Content-Type`
+fn get(url: &str) -> CacheResult<(Vec<u8>, Option<String>)> {
+    let mut response = ureq::get(url).call().map_err(|e| http_error(url, e))?;
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(u64::MAX)
+        .read_to_vec()
+        .map_err(|e| http_error(url, e))?;
+    Ok((body, content_type))
+}
+
+impl Cache {
+    /// Uploads every object to an HTTP endpoint
+    ///
+    /// Each object is sent with `PUT {url}/objects/{name}` (with its recorded
+    /// content type as `Content-Type`), followed by
+    /// `PUT {url}/manifest.json` holding `dump_manifest()`. Objects live
+    /// under their own prefix, so one named `manifest.json` cannot
+    /// overwrite the manifest.
+    /// Any server or object store accepting PUT and serving GET works.
+    ///
+    /// # Parameters
+    /// - `url: &str` - Base URL of the remote cache
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects uploaded
+    pub fn push(&self, url: &str) -> CacheResult<usize> {
+        let mut pushed = 0;
+        for obj in self.iter().filter(|obj| obj.exists()) {
+            let (content, content_type) = obj.get_bytes_with_type()?;
+            put(&entry_url(url, obj.name()), &content, content_type.as_deref())?;
+            pushed += 1;
+        }
+        put(&manifest_url(url), self.dump_manifest()?.as_bytes(), Some("application/json"))?;
+        Ok(pushed)
+    }
+
+    /// Downloads every object listed by an HTTP endpoint
+    ///
+    /// Reads `GET {url}/manifest.json`, then fetches each object with
+    /// `GET {url}/objects/{name}` and checks it against the recorded checksum.
+    /// Missing objects are created, existing ones are overwritten. If the
+    /// cache stores metadata, the `Content-Type` of each response is
+    /// recorded as the content type.
+    ///
+    /// # Parameters
+    /// - `url: &str` - Base URL of the remote cache
+    ///
+    /// # Returns
+    /// `CacheResult<usize>` - Number of objects downloaded
+    pub fn pull(&mut self, url: &str) -> CacheResult<usize> {
+        let (manifest, _) = get(&manifest_url(url))?;
+        let manifest = self.parse_manifest(&String::from_utf8_lossy(&manifest))?;
+
+        let stores_types = self.get_config().metadata != MetadataMode::None;
+        let mut pulled = 0;
+        for entry in manifest.entries.iter().filter(|entry| entry.sha256.is_some()) {
+            let (content, content_type) = get(&entry_url(url, &entry.name))?;
+            entry.verify(&content)?;
+            let obj = match self.get(&entry.name) {
+                Ok(obj) => obj,
+                Err(_) => self.create(&entry.name, None)?,
+            };
+            match content_type {
+                Some(content_type) if stores_types => {
+                    obj.write_bytes_with_type(&content, &content_type)?
+                }
+                _ => obj.write_bytes(&content)?,
+            }
+            pulled += 1;
+        }
+        Ok(pulled)
+    }
+}
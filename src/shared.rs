@@ -0,0 +1,178 @@
+/*
+ * @filename: shared.rs
+ * @description: Thread-safe cache handle with a sharded object registry
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cache::{traced_get, Cache, CacheContext};
+use crate::object::CacheObject;
+use crate::CacheResult;
+use dashmap::DashMap;
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Cache handle meant to be shared between threads (e.g. in an `Arc`)
+///
+/// Lookups go through a sharded concurrent map, so `get()` calls from many
+/// threads neither wait for each other nor for a global lock. Operations
+/// that change the registry are serialized on the wrapped `Cache`.
+///
+/// ```rust
+/// use cache_lite::{Cache, SharedCache};
+/// use std::sync::Arc;
+///
+/// let shared = Arc::new(SharedCache::new(Cache::in_memory()));
+/// shared.create("config", None).unwrap().write_string("{}").unwrap();
+///
+/// let reader = Arc::clone(&shared);
+/// std::thread::spawn(move || reader.get("config").unwrap().get_string().unwrap())
+///     .join()
+///     .unwrap();
+/// ```
+pub struct SharedCache {
+    cache: Mutex<Cache>,
+    objects: DashMap<String, Arc<CacheObject>>,
+    context: Arc<CacheContext>,
+    lazy: bool,
+}
+
+impl SharedCache {
+    /// Wraps a cache for concurrent use
+    ///
+    /// # Parameters
+    /// - `cache: Cache` - Cache to take ownership of
+    ///
+    /// # Returns
+    /// New SharedCache instance
+    pub fn new(cache: Cache) -> Self {
+        let objects = cache
+            .live_objects()
+            .map(|obj| (obj.name().to_string(), Arc::clone(obj)))
+            .collect();
+        SharedCache {
+            context: Arc::clone(cache.context()),
+            lazy: cache.root().is_some(),
+            cache: Mutex::new(cache),
+            objects,
+        }
+    }
+
+    /// Retrieves a cache object by name without taking the cache lock
+    ///
+    /// Objects of an opened directory that are not indexed yet are still
+    /// resolved through the wrapped cache.
+    ///
+    /// # Parameters
+    /// - `name: &Q` - Name of cache object to retrieve, any `Borrow<str>`
+    ///
+    /// # Returns
+    /// `CacheResult<Arc<CacheObject>>` - Retrieved cache object or `EntryNotFound`
+    pub fn get<Q: Borrow<str> + ?Sized>(&self, name: &Q) -> CacheResult<Arc<CacheObject>> {
+        let name = name.borrow();
+        traced_get(&self.context, name, |name| {
+            self.objects
+                .get(name)
+                .map(|obj| Arc::clone(&obj))
+                .or_else(|| if self.lazy { self.lock().lookup(name) } else { None })
+        })
+    }
+
+    /// Creates a new cache object, see `Cache::create()`
+    ///
+    /// # Parameters
+    /// - `name: &str` - Name of the cache object
+    /// - `custom_config: Option<&str>` - Optional JSON configuration string
+    ///
+    /// # Returns
+    /// `CacheResult<Arc<CacheObject>>` - The new cache object
+    pub fn create(&self, name: &str, custom_config: Option<&str>) -> CacheResult<Arc<CacheObject>> {
+        let mut cache = self.lock();
+        let obj = cache.create(name, custom_config)?;
+        // An older generation may have held the name before
+        self.objects.insert(obj.name().to_string(), Arc::clone(&obj));
+        Ok(obj)
+    }
+
+    /// Removes a cache object and its dependents, see `Cache::remove()`
+    ///
+    /// # Parameters
+    /// - `name: &Q` - Name of the cache object to remove, any `Borrow<str>`
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn remove<Q: Borrow<str> + ?Sized>(&self, name: &Q) -> CacheResult<()> {
+        let name = name.borrow();
+        self.with_cache(|cache| cache.remove(name))
+    }
+
+    /// Runs `f` with exclusive access to the wrapped cache
+    ///
+    /// The concurrent registry is brought up to date afterwards, so any
+    /// `Cache` operation (`clear()`, `bump_generation()`, `index_all()`, ...)
+    /// can be used.
+    ///
+    /// # Parameters
+    /// - `f: impl FnOnce(&mut Cache) -> R` - Operation on the cache
+    ///
+    /// # Returns
+    /// `R` - Result of `f`
+    pub fn with_cache<R>(&self, f: impl FnOnce(&mut Cache) -> R) -> R {
+        let mut cache = self.lock();
+        let result = f(&mut cache);
+        let live: HashSet<&str> = cache.live_objects().map(|obj| obj.name()).collect();
+        self.objects.retain(|name, _| live.contains(name.as_str()));
+        for obj in cache.live_objects() {
+            self.objects.insert(obj.name().to_string(), Arc::clone(obj));
+        }
+        result
+    }
+
+    /// Returns the number of registered cache objects
+    ///
+    /// # Returns
+    /// `usize` - Number of live objects
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Checks whether no cache object is registered
+    ///
+    /// # Returns
+    /// `bool` - True if the cache holds no live objects
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns the wrapped cache
+    ///
+    /// # Returns
+    /// `Cache` - The cache with every change made through this handle
+    pub fn into_inner(self) -> Cache {
+        self.cache.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ * @filename: space.rs
+ * @description: Free space checks and emergency eviction before writes
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{CacheConfig, LowSpacePolicy};
+use crate::index::{INDEX_FILE, INDEX_LOCK_FILE};
+use crate::lock::CLAIM_FILE;
+use crate::manifest::MANIFEST_FILE;
+use crate::meta::sidecar_path;
+use crate::object::CacheObject;
+use crate::storage::Storage;
+use crate::{CacheError, CacheResult};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::SystemTime;
+
+/// Streamed writes check the free space again each time they grow by this much
+pub(crate) const SPACE_CHECK_INTERVAL: u64 = 1024 * 1024;
+
+/// Files the cache keeps next to its entries, never evicted
+const HOUSEKEEPING_FILES: [&str; 4] = [INDEX_FILE, INDEX_LOCK_FILE, CLAIM_FILE, MANIFEST_FILE];
+
+/// `limits.min_free_bytes` settings of the current config
+#[derive(Debug, Clone, Default)]
+struct SpaceSettings {
+    min_free_bytes: Option<u64>,
+    on_low_space: LowSpacePolicy,
+}
+
+/// Files of the registered objects, kept up to date by `Registry`
+///
+/// Holds weak references, so an object only the registry holds can be
+/// told apart from one a caller still uses.
+#[derive(Debug, Default)]
+pub(crate) struct RegisteredFiles {
+    files: Mutex<HashMap<PathBuf, Vec<Weak<CacheObject>>>>,
+}
+
+impl RegisteredFiles {
+    pub(crate) fn add(&self, obj: &Arc<CacheObject>) {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files.entry(obj.path().to_path_buf()).or_default().push(Arc::downgrade(obj));
+    }
+
+    pub(crate) fn remove(&self, obj: &Arc<CacheObject>) {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(users) = files.get_mut(obj.path()) {
+            users.retain(|user| !std::ptr::eq(user.as_ptr(), Arc::as_ptr(obj)));
+            if users.is_empty() {
+                files.remove(obj.path());
+            }
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Returns the files emergency eviction may remove
+    ///
+    /// Those are the files of a single registered object that is not
+    /// sealed and has no handle outside the registry.
+    fn evictable(&self) -> Vec<PathBuf> {
+        let files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files
+            .iter()
+            .filter(|(path, users)| {
+                let housekeeping = path
+                    .file_name()
+                    .is_some_and(|file_name| HOUSEKEEPING_FILES.iter().any(|file| file_name == *file));
+                let [user] = users.as_slice() else {
+                    return false;
+                };
+                // Only the registry holds the object, upgrading adds the one checked for
+                !housekeeping
+                    && user.strong_count() == 1
+                    && user.upgrade().is_some_and(|obj| !obj.is_sealed() && !obj.has_clones())
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+/// Free space guard shared by a cache and its objects
+///
+/// Files removed by emergency eviction are remembered so lookups report
+/// their objects as not found until they are written again; `Cache::gc()`
+/// unregisters them.
+#[derive(Debug, Default)]
+pub(crate) struct SpaceGuard {
+    settings: RwLock<SpaceSettings>,
+    evicted: Mutex<HashSet<PathBuf>>,
+    registered: Arc<RegisteredFiles>,
+}
+
+impl SpaceGuard {
+    pub(crate) fn new(config: &CacheConfig) -> Self {
+        let guard = SpaceGuard::default();
+        guard.configure(config);
+        guard
+    }
+
+    /// Applies the `limits` of a new config
+    pub(crate) fn configure(&self, config: &CacheConfig) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = SpaceSettings {
+            min_free_bytes: config.limits.min_free_bytes,
+            on_low_space: config.limits.on_low_space,
+        };
+    }
+
+    /// Returns the registered files, shared with the `Registry` of the cache
+    pub(crate) fn registered(&self) -> Arc<RegisteredFiles> {
+        Arc::clone(&self.registered)
+    }
+
+    /// Checks whether emergency eviction removed the file at `path`
+    pub(crate) fn is_evicted(&self, path: &Path) -> bool {
+        let evicted = self.evicted.lock().unwrap_or_else(|e| e.into_inner());
+        !evicted.is_empty() && evicted.contains(path)
+    }
+
+    /// Forgets an eviction once the file was written again
+    pub(crate) fn restored(&self, path: &Path) {
+        let mut evicted = self.evicted.lock().unwrap_or_else(|e| e.into_inner());
+        if !evicted.is_empty() {
+            evicted.remove(path);
+        }
+    }
+
+    /// Makes sure writing `size` bytes to `path` leaves `min_free_bytes` free
+    ///
+    /// Below the threshold, `LowSpacePolicy::Evict` removes the least
+    /// recently modified files of registered objects until enough space
+    /// is free, skipping sealed objects and objects a caller holds. Fails with `DiskFull` if there is still not enough. Volumes
+    /// whose free space cannot be determined (e.g. in-memory caches) pass.
+    pub(crate) fn ensure(&self, storage: &dyn Storage, path: &Path, size: u64) -> CacheResult<()> {
+        let settings = self.settings.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(min_free_bytes) = settings.min_free_bytes else {
+            return Ok(());
+        };
+        let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+            return Ok(());
+        };
+        let Ok(mut available) = fs4::available_space(dir) else {
+            return Ok(());
+        };
+        let required = min_free_bytes.saturating_add(size);
+        if available < required && settings.on_low_space == LowSpacePolicy::Evict {
+            available = self.evict(storage, dir, path, required).unwrap_or(available);
+        }
+        if available < required {
+            return Err(CacheError::DiskFull { available, required });
+        }
+        Ok(())
+    }
+
+    /// Removes the oldest evictable files until `required` bytes are free in `dir`
+    ///
+    /// Returns the free space afterwards.
+    fn evict(&self, storage: &dyn Storage, dir: &Path, keep: &Path, required: u64) -> std::io::Result<u64> {
+        let mut candidates: Vec<(SystemTime, PathBuf)> = Vec::new();
+        for path in self.registered.evictable() {
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.is_file() && path != keep && !self.is_evicted(&path) {
+                candidates.push((metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), path));
+            }
+        }
+        candidates.sort();
+
+        let mut available = fs4::available_space(dir)?;
+        for (_, path) in candidates {
+            if available >= required {
+                break;
+            }
+            if storage.remove(&path).is_err() {
+                continue;
+            }
+            let sidecar = sidecar_path(&path);
+            if storage.exists(&sidecar) {
+                let _ = storage.remove(&sidecar);
+            }
+            log_warn!("evicted {} to keep free disk space", path.display());
+            metric_counter!("cache_lite_eviction_total", 1);
+            self.evicted.lock().unwrap_or_else(|e| e.into_inner()).insert(path);
+            available = fs4::available_space(dir)?;
+        }
+        Ok(available)
+    }
+}
@@ -0,0 +1,627 @@
+/*
+ * @filename: storage.rs
+ * @description: Storage backends for cache-lite library
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{CacheIoConfig, IoAdvice};
+use crate::throttle::ThrottleState;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Backend performing the actual I/O for cache objects
+///
+/// Every read, write and delete of a cache object goes through the
+/// storage of its cache, so alternative backends (in-memory, browser
+/// storage, ...) can be plugged in with `Cache::with_storage()` while
+/// the rest of the API stays unchanged. Paths are the ones produced by
+/// the cache configuration and may be treated as plain keys.
+pub trait Storage: Send + Sync + fmt::Debug {
+    /// Reads the whole content stored at `path`
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Replaces the content stored at `path`
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()>;
+
+    /// Removes the content stored at `path`
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Checks whether content is stored at `path`
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Returns the size in bytes of the content stored at `path`
+    fn size(&self, path: &Path) -> io::Result<u64>;
+
+    /// Prepares a directory so entries can be stored below it
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates an empty entry when a cache object is created
+    fn create_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates an empty entry, failing with `AlreadyExists` if one is there
+    ///
+    /// Used instead of `create_file()` when `protect_existing_files` is
+    /// set. The default checks `exists()` first, backends should
+    /// override it with an atomic variant.
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        if self.exists(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        self.create_file(path)
+    }
+
+    /// Opens the entry up to the other users of the machine
+    ///
+    /// Called for directories and entries of a system-scoped cache.
+    /// Backends without a notion of ownership keep the default no-op.
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        let _ = path;
+        Ok(())
+    }
+
+    /// Gives the entry content of its own before it is modified
+    ///
+    /// Entries promoted from a parent cache may be hard links to the
+    /// parent's file (see `Cache::set_parent()`); they are replaced by a
+    /// copy so writes do not reach the parent. Backends without links
+    /// keep the default no-op.
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        let _ = path;
+        Ok(())
+    }
+
+    /// Makes the entry read-only, see `CacheObject::seal()`
+    ///
+    /// Backends without permissions keep the default no-op; the object
+    /// still refuses writes after it was sealed.
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        let _ = path;
+        Ok(())
+    }
+
+    /// Checks whether the entry was made read-only by `seal()`
+    fn is_sealed(&self, path: &Path) -> bool {
+        let _ = path;
+        false
+    }
+
+    /// Opens the entry as a native file handle
+    ///
+    /// Backends not based on the local filesystem return `Unsupported`.
+    fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot be opened as a file by this storage backend", path.display()),
+        ))
+    }
+
+    /// Truncates the entry and returns a sink streaming its new content
+    ///
+    /// Backends returning `Unsupported` (the default) receive the whole
+    /// content through `write()` once the writer is finished.
+    fn stream(&self, path: &Path) -> io::Result<Box<dyn io::Write + Send>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot be streamed to by this storage backend", path.display()),
+        ))
+    }
+
+    /// Persists writes the backend has buffered
+    ///
+    /// Backends writing through (the default) have nothing to do.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Reports the write rate limit applied by the backend, if any
+    ///
+    /// Wrapping backends forward the state of the backend they wrap.
+    fn throttle(&self) -> Option<ThrottleState> {
+        None
+    }
+
+    /// Moves the entry at `from` to `to`, replacing it
+    ///
+    /// Used to publish staged writes. The default copies the content and
+    /// removes `from`; backends with an atomic rename should override it.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let content = self.read(from)?;
+        self.write(to, &content)?;
+        self.remove(from)
+    }
+
+    /// Checks whether `read()` and `write()` are plain reads and writes of the file at `path`
+    ///
+    /// With the `io-uring` feature such backends let async reads and
+    /// writes go through io_uring instead of the backend. Backends that
+    /// transform, buffer or relocate content keep the default `false`.
+    fn is_plain_file(&self) -> bool {
+        false
+    }
+}
+
+/// Storage backend using the local filesystem through `std::fs`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsStorage;
+
+impl Storage for FsStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        std::fs::write(path, content)
+    }
+
+    fn is_plain_file(&self) -> bool {
+        true
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        // Windows refuses to delete read-only files, e.g. sealed entries
+        #[cfg(windows)]
+        if self.is_sealed(path) {
+            let mut permissions = std::fs::metadata(path)?.permissions();
+            permissions.set_readonly(false);
+            std::fs::set_permissions(path, permissions)?;
+        }
+        std::fs::remove_file(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        std::fs::metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let perms = std::fs::Permissions::from_mode(0o600); // rw-------
+            if let Ok(file) = std::fs::File::create(path) {
+                file.set_permissions(perms)?;
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        crate::utils::break_hard_link(path)
+    }
+
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        let mut permissions = std::fs::metadata(path)?.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(path, permissions)
+    }
+
+    fn is_sealed(&self, path: &Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly())
+    }
+
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // rwxrwsr-x for directories so new entries inherit the group, rw-rw-r-- for files
+            let mode = if std::fs::metadata(path)?.is_dir() { 0o2775 } else { 0o664 };
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600); // rw-------
+        options.open(path).map(|_| ())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+
+    fn stream(&self, path: &Path) -> io::Result<Box<dyn io::Write + Send>> {
+        Ok(Box::new(std::fs::File::create(path)?))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+}
+
+/// Alignment of buffers, file offsets and transfer sizes for direct I/O (4 KiB)
+pub const DIRECT_IO_ALIGN: usize = 4096;
+
+/// Byte buffer whose start is aligned to `DIRECT_IO_ALIGN`
+///
+/// Direct I/O transfers whole aligned blocks from aligned memory. The
+/// buffer dereferences to its logical content while `padded()` exposes
+/// the content rounded up to the next block, zero-filled.
+#[derive(Debug, Clone)]
+pub struct AlignedBuffer {
+    bytes: Vec<u8>,
+    offset: usize,
+    len: usize,
+}
+
+impl AlignedBuffer {
+    /// Creates a zero-filled buffer of `len` bytes
+    ///
+    /// # Parameters
+    /// - `len: usize` - Logical length in bytes
+    ///
+    /// # Returns
+    /// New AlignedBuffer instance
+    pub fn zeroed(len: usize) -> Self {
+        let padded = len.next_multiple_of(DIRECT_IO_ALIGN);
+        let bytes = vec![0; padded + DIRECT_IO_ALIGN];
+        let offset = bytes.as_ptr().align_offset(DIRECT_IO_ALIGN);
+        AlignedBuffer { bytes, offset, len }
+    }
+
+    /// Creates a buffer holding a copy of `content`
+    ///
+    /// # Parameters
+    /// - `content: &[u8]` - Content to copy
+    ///
+    /// # Returns
+    /// New AlignedBuffer instance
+    pub fn from_slice(content: &[u8]) -> Self {
+        let mut buffer = Self::zeroed(content.len());
+        buffer.copy_from_slice(content);
+        buffer
+    }
+
+    /// Changes the logical length, keeping it within the padded capacity
+    ///
+    /// # Parameters
+    /// - `len: usize` - New logical length in bytes
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Returns the content padded to a whole number of aligned blocks
+    ///
+    /// # Returns
+    /// `&[u8]` - Aligned slice of at least `len()` bytes
+    pub fn padded(&self) -> &[u8] {
+        let padded = self.len.next_multiple_of(DIRECT_IO_ALIGN);
+        &self.bytes[self.offset..self.offset + padded]
+    }
+
+    /// Mutable variant of `padded()`
+    ///
+    /// # Returns
+    /// `&mut [u8]` - Aligned slice of at least `len()` bytes
+    pub fn padded_mut(&mut self) -> &mut [u8] {
+        let padded = self.len.next_multiple_of(DIRECT_IO_ALIGN);
+        &mut self.bytes[self.offset..self.offset + padded]
+    }
+}
+
+impl std::ops::Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[self.offset..self.offset + self.len]
+    }
+}
+
+impl std::ops::DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[self.offset..self.offset + self.len]
+    }
+}
+
+/// Filesystem backend applying the `io` section of the cache config
+///
+/// Used by `Cache::new()` instead of `FsStorage` when `io` differs from
+/// the defaults:
+///
+/// - `direct` transfers whole entries through `AlignedBuffer`s with
+///   direct I/O; filesystems without support (e.g. tmpfs) and other
+///   platforms transparently use buffered I/O. Streaming and partial
+///   writes always use buffered I/O.
+/// - `advise` passes `posix_fadvise` hints on Linux and is ignored
+///   elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct TunedFsStorage {
+    io: CacheIoConfig,
+}
+
+impl TunedFsStorage {
+    /// Creates a backend with the given I/O settings
+    ///
+    /// # Parameters
+    /// - `io: CacheIoConfig` - I/O settings
+    ///
+    /// # Returns
+    /// New TunedFsStorage instance
+    pub fn new(io: CacheIoConfig) -> Self {
+        TunedFsStorage { io }
+    }
+
+    /// Opens `path`, bypassing the page cache if enabled and supported
+    ///
+    /// # Returns
+    /// The file and whether it was opened for direct I/O
+    fn open_file(&self, options: &std::fs::OpenOptions, path: &Path) -> io::Result<(std::fs::File, bool)> {
+        if self.io.direct
+            && let Some(file) = open_direct(options.clone(), path)?
+        {
+            return Ok((file, true));
+        }
+        options.open(path).map(|file| (file, false))
+    }
+}
+
+/// Opens `path` with direct I/O, `Ok(None)` where unsupported
+#[allow(unused_mut)]
+fn open_direct(mut options: std::fs::OpenOptions, path: &Path) -> io::Result<Option<std::fs::File>> {
+    #[cfg(target_os = "linux")]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_DIRECT);
+    #[cfg(windows)]
+    std::os::windows::fs::OpenOptionsExt::custom_flags(&mut options, 0x2000_0000); // FILE_FLAG_NO_BUFFERING
+    #[cfg(not(any(target_os = "linux", windows)))]
+    return Ok(None);
+
+    #[cfg(any(target_os = "linux", windows))]
+    match options.open(path) {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Passes an access pattern hint for the whole file to the kernel
+///
+/// Hints never fail an operation, errors are only logged.
+fn advise(file: &std::fs::File, advice: IoAdvice) {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        let flag = match advice {
+            IoAdvice::Normal => libc::POSIX_FADV_NORMAL,
+            IoAdvice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            IoAdvice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        };
+        // SAFETY: the descriptor is owned by `file` and open for the call
+        let ret = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, flag) };
+        if ret != 0 {
+            log_debug!("posix_fadvise failed: {}", io::Error::from_raw_os_error(ret));
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (file, advice);
+}
+
+/// Writes below this size are not worth flushing for `IoAdvice::DontNeed` (1 MiB)
+const DONTNEED_MIN_WRITE: usize = 1024 * 1024;
+
+impl Storage for TunedFsStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let (mut file, direct) = self.open_file(std::fs::OpenOptions::new().read(true), path)?;
+        if self.io.advise == IoAdvice::Sequential {
+            advise(&file, IoAdvice::Sequential);
+        }
+        let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+        let content = if direct {
+            let mut buffer = AlignedBuffer::zeroed(len);
+            let mut filled = 0;
+            // Reads stop short at the end of the file, which is not block aligned
+            while filled < len {
+                match io::Read::read(&mut file, &mut buffer.padded_mut()[filled..]) {
+                    Ok(0) => break,
+                    Ok(n) => filled += n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            buffer.truncate(filled);
+            buffer.to_vec()
+        } else {
+            let mut content = Vec::with_capacity(len);
+            io::Read::read_to_end(&mut file, &mut content)?;
+            content
+        };
+        if self.io.advise == IoAdvice::DontNeed {
+            advise(&file, IoAdvice::DontNeed);
+        }
+        Ok(content)
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        let (mut file, direct) = self.open_file(&options, path)?;
+        if direct {
+            io::Write::write_all(&mut file, AlignedBuffer::from_slice(content).padded())?;
+            // Drop the zero padding of the last block
+            file.set_len(content.len() as u64)?;
+        } else {
+            io::Write::write_all(&mut file, content)?;
+        }
+        if self.io.advise == IoAdvice::DontNeed && content.len() >= DONTNEED_MIN_WRITE {
+            // Dirty pages cannot be dropped, write them back first
+            file.sync_data()?;
+            advise(&file, IoAdvice::DontNeed);
+        }
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        FsStorage.remove(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        FsStorage.exists(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        FsStorage.size(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        FsStorage.create_dir_all(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        FsStorage.create_file(path)
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        FsStorage.create_new(path)
+    }
+
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        FsStorage.set_shared(path)
+    }
+
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        FsStorage.unshare(path)
+    }
+
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        FsStorage.seal(path)
+    }
+
+    fn is_sealed(&self, path: &Path) -> bool {
+        FsStorage.is_sealed(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+        FsStorage.open(path)
+    }
+
+    fn stream(&self, path: &Path) -> io::Result<Box<dyn io::Write + Send>> {
+        FsStorage.stream(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        FsStorage.rename(from, to)
+    }
+}
+
+/// Storage backend keeping every entry in a byte map
+///
+/// Nothing touches the filesystem; paths are only used as keys.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty in-memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not stored in memory", path.display()),
+        )
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.entries()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        self.entries().insert(path.to_path_buf(), content.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.entries()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.entries().contains_key(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.entries()
+            .get(path)
+            .map(|content| content.len() as u64)
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.entries().insert(path.to_path_buf(), Vec::new());
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        match self.entries().entry(path.to_path_buf()) {
+            std::collections::hash_map::Entry::Occupied(_) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            )),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(Vec::new());
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/*
+ * @filename: telemetry.rs
+ * @description: OpenTelemetry span helpers for cache-lite library
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::CacheError;
+
+#[cfg(feature = "otel")]
+use opentelemetry::trace::{Span, Status, Tracer};
+#[cfg(feature = "otel")]
+use opentelemetry::{KeyValue, global};
+
+/// Instrumentation scope name reported to OpenTelemetry
+#[cfg(feature = "otel")]
+const TRACER_NAME: &str = "cache-lite";
+
+/// Span covering a single cache operation
+///
+/// Started as a child of the current OpenTelemetry context and ended on
+/// drop. Without the `otel` feature this is a zero-sized no-op.
+pub(crate) struct OpSpan {
+    #[cfg(feature = "otel")]
+    span: global::BoxedSpan,
+}
+
+impl OpSpan {
+    /// Starts a span named `cache.<operation>` for the given cache object
+    pub(crate) fn start(operation: &'static str, name: &str) -> Self {
+        #[cfg(feature = "otel")]
+        {
+            let mut span = global::tracer(TRACER_NAME).start(format!("cache.{}", operation));
+            span.set_attribute(KeyValue::new("cache.operation", operation));
+            span.set_attribute(KeyValue::new("cache.name", name.to_string()));
+            OpSpan { span }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = (operation, name);
+            OpSpan {}
+        }
+    }
+
+    /// Records whether the lookup found the object
+    pub(crate) fn hit(&mut self, hit: bool) {
+        #[cfg(feature = "otel")]
+        self.span.set_attribute(KeyValue::new("cache.hit", hit));
+        #[cfg(not(feature = "otel"))]
+        let _ = hit;
+    }
+
+    /// Records the number of bytes read or written
+    pub(crate) fn bytes(&mut self, bytes: u64) {
+        #[cfg(feature = "otel")]
+        self.span.set_attribute(KeyValue::new("cache.bytes", bytes as i64));
+        #[cfg(not(feature = "otel"))]
+        let _ = bytes;
+    }
+
+    /// Marks the span as failed with the given error
+    pub(crate) fn error(&mut self, err: &CacheError) {
+        #[cfg(feature = "otel")]
+        {
+            self.span.set_attribute(KeyValue::new("error.type", err.kind()));
+            self.span.set_status(Status::error(err.to_string()));
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = err;
+    }
+
+    /// Records the outcome of a fallible operation and passes it through
+    pub(crate) fn record<T>(&mut self, result: crate::CacheResult<T>) -> crate::CacheResult<T> {
+        if let Err(e) = &result {
+            self.error(e);
+        }
+        result
+    }
+}
+
+#[cfg(feature = "otel")]
+impl Drop for OpSpan {
+    fn drop(&mut self) {
+        self.span.end();
+    }
+}
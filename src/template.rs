@@ -0,0 +1,270 @@
+/*
+ * @filename: template.rs
+ * @description: File name templates compiled once per configuration
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+#[cfg(feature = "time")]
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+#[cfg(feature = "time")]
+use chrono::{DateTime, Local};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Inserts `token` between the file stem and the extension of `path`
+pub(crate) fn suffixed(path: &Path, token: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}{}.{}", stem, token, ext.to_string_lossy()),
+        None => format!("{}{}", stem, token),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Backtracking match of `rest` against `segments`
+fn match_segments(segments: &[Segment], rest: &str, name: &mut Option<String>) -> bool {
+    let Some((segment, tail)) = segments.split_first() else {
+        return rest.is_empty();
+    };
+    match segment {
+        Segment::Literal(text) => rest
+            .strip_prefix(text.as_str())
+            .is_some_and(|rest| match_segments(tail, rest, name)),
+        Segment::Name if name.is_some() => {
+            let known = name.clone().unwrap_or_default();
+            rest.strip_prefix(known.as_str())
+                .is_some_and(|rest| match_segments(tail, rest, name))
+        }
+        wildcard => {
+            let ends = rest.char_indices().map(|(i, _)| i).skip(1).chain([rest.len()]);
+            for end in ends.collect::<Vec<_>>().into_iter().rev() {
+                let (value, rest) = rest.split_at(end);
+                if matches!(wildcard, Segment::Id) && !value.bytes().all(|b| b.is_ascii_digit()) {
+                    continue;
+                }
+                if matches!(wildcard, Segment::Name) {
+                    *name = Some(value.to_string());
+                }
+                if match_segments(tail, rest, name) {
+                    return true;
+                }
+                if matches!(wildcard, Segment::Name) {
+                    *name = None;
+                }
+            }
+            false
+        }
+    }
+}
+
+/// Piece of a file name format
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Name,
+    Id,
+    Time,
+}
+
+/// `format.filename` split into segments, with `format.time` parsed
+///
+/// Built when the configuration is set, so creating an object only
+/// concatenates segments. The formatted `{time}` is reused for every
+/// object created within the same second unless the time format has
+/// sub-second fields.
+#[derive(Debug)]
+pub(crate) struct FilenameTemplate {
+    segments: Vec<Segment>,
+    /// Length of the literal segments, used to presize file names
+    literal_len: usize,
+    time: TimeFormat,
+    /// Last formatted `{time}` and the second it was formatted for
+    last_time: Option<Mutex<(u64, String)>>,
+}
+
+impl FilenameTemplate {
+    /// Compiles a file name format and its time format
+    pub(crate) fn new(format: &str, time_format: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = format;
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            let (segment, tail) = if let Some(tail) = tail.strip_prefix("{name}") {
+                (Segment::Name, tail)
+            } else if let Some(tail) = tail.strip_prefix("{id}") {
+                (Segment::Id, tail)
+            } else if let Some(tail) = tail.strip_prefix("{time}") {
+                (Segment::Time, tail)
+            } else {
+                literal.push('{');
+                rest = &tail[1..];
+                continue;
+            };
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(segment);
+            rest = tail;
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        let literal_len = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.len(),
+                _ => 0,
+            })
+            .sum();
+        let time = TimeFormat::new(time_format);
+        let uses_time = segments.iter().any(|segment| matches!(segment, Segment::Time));
+        let subsecond = time.is_subsecond();
+        FilenameTemplate {
+            segments,
+            literal_len,
+            time,
+            last_time: (uses_time && !subsecond).then(|| Mutex::new((u64::MAX, String::new()))),
+        }
+    }
+
+    /// Matches a file name against the format, returning the object name
+    ///
+    /// `{time}` and `{id}` match anything they could have produced. With
+    /// `name`, `{name}` must expand to it; otherwise the longest possible
+    /// name is taken, `None` inside the result if the format has no
+    /// `{name}`.
+    pub(crate) fn parse(&self, file_name: &str, name: Option<&str>) -> Option<Option<String>> {
+        let mut captured = name.map(str::to_string);
+        match_segments(&self.segments, file_name, &mut captured).then_some(captured)
+    }
+
+    /// Checks whether formatted names contain `{id}`, making them unique
+    pub(crate) fn has_id(&self) -> bool {
+        self.segments.iter().any(|segment| matches!(segment, Segment::Id))
+    }
+
+    /// Expands `{name}`, `{id}` and `{time}` for one object
+    pub(crate) fn format(&self, name: &str, id: u32) -> String {
+        let mut filename = String::with_capacity(self.literal_len + name.len() + 32);
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => filename.push_str(text),
+                Segment::Name => filename.push_str(name),
+                Segment::Id => {
+                    let _ = write!(filename, "{}", id);
+                }
+                Segment::Time => self.push_time(&mut filename),
+            }
+        }
+        filename
+    }
+
+    fn push_time(&self, filename: &mut String) {
+        let now = SystemTime::now();
+        let Some(last_time) = &self.last_time else {
+            self.time.write(filename, now);
+            return;
+        };
+        let second = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let mut last_time = last_time.lock().unwrap_or_else(|e| e.into_inner());
+        if last_time.0 != second {
+            last_time.1.clear();
+            self.time.write(&mut last_time.1, now);
+            last_time.0 = second;
+        }
+        filename.push_str(&last_time.1);
+    }
+}
+
+/// Parsed `format.time`, or the raw format if chrono rejects it
+#[cfg(feature = "time")]
+#[derive(Debug)]
+struct TimeFormat(Result<Vec<Item<'static>>, String>);
+
+#[cfg(feature = "time")]
+impl TimeFormat {
+    fn new(time_format: &str) -> Self {
+        TimeFormat(
+            StrftimeItems::new(time_format)
+                .parse_to_owned()
+                .map_err(|_| time_format.to_string()),
+        )
+    }
+
+    /// Checks whether the formatted time changes within a second
+    fn is_subsecond(&self) -> bool {
+        self.0.as_ref().map_or(true, |items| items.iter().any(is_subsecond))
+    }
+
+    fn write(&self, out: &mut String, now: SystemTime) {
+        let datetime: DateTime<Local> = now.into();
+        let _ = match &self.0 {
+            Ok(items) => write!(out, "{}", datetime.format_with_items(items.iter())),
+            Err(raw) => write!(out, "{}", datetime.format(raw)),
+        };
+    }
+}
+
+/// Without the `time` feature `{time}` is the Unix time in seconds
+#[cfg(not(feature = "time"))]
+#[derive(Debug)]
+struct TimeFormat;
+
+#[cfg(not(feature = "time"))]
+impl TimeFormat {
+    fn new(_time_format: &str) -> Self {
+        TimeFormat
+    }
+
+    fn is_subsecond(&self) -> bool {
+        false
+    }
+
+    fn write(&self, out: &mut String, now: SystemTime) {
+        let _ = write!(out, "{}", now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    }
+}
+
+/// Checks whether a time format item changes within a second
+#[cfg(feature = "time")]
+fn is_subsecond(item: &Item<'_>) -> bool {
+    match item {
+        Item::Numeric(Numeric::Nanosecond, _) => true,
+        Item::Fixed(fixed) => matches!(
+            fixed,
+            Fixed::Nanosecond
+                | Fixed::Nanosecond3
+                | Fixed::Nanosecond6
+                | Fixed::Nanosecond9
+                | Fixed::Internal(_)
+        ),
+        _ => false,
+    }
+}
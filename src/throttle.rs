@@ -0,0 +1,240 @@
+/*
+ * @filename: throttle.rs
+ * @description: Token-bucket limit on the bytes written to disk
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::storage::Storage;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Current state of a write rate limit, reported in `CacheStats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThrottleState {
+    /// Configured limit in bytes per second
+    pub bytes_per_sec: u64,
+    /// Bytes that can be written right now without waiting
+    pub available_bytes: u64,
+    /// Writers currently waiting for the limiter
+    pub waiting: usize,
+    /// Number of writes that had to wait so far
+    pub throttled_writes: u64,
+    /// Total time writers spent waiting
+    pub throttled_for: Duration,
+}
+
+/// Token bucket refilled at `bytes_per_sec`, holding one second of writes
+#[derive(Debug)]
+struct Bucket {
+    /// Negative while writers are paying off a burst
+    tokens: f64,
+    refilled_at: Instant,
+    waiting: usize,
+    throttled_writes: u64,
+    throttled_for: Duration,
+}
+
+/// Storage backend limiting the bytes written per second
+///
+/// Writes wait until the token bucket covers them, so background cache
+/// population cannot starve the application's own disk I/O. Bursts of up
+/// to one second worth of bytes pass immediately. Content written
+/// through `write()` and `stream()` is throttled; file handles from
+/// `open()` (`write_at()`, `mmap_mut()`) are not.
+///
+/// Used by `Cache::new()` when `io.max_write_bytes_per_sec` is set.
+#[derive(Debug)]
+pub struct ThrottledStorage {
+    inner: Arc<dyn Storage>,
+    limiter: Arc<Limiter>,
+}
+
+#[derive(Debug)]
+struct Limiter {
+    bytes_per_sec: u64,
+    bucket: Mutex<Bucket>,
+}
+
+impl ThrottledStorage {
+    /// Wraps a backend, allowing `bytes_per_sec` bytes written per second
+    ///
+    /// # Parameters
+    /// - `inner: Arc<dyn Storage>` - Backend performing the writes
+    /// - `bytes_per_sec: u64` - Sustained write rate, at least 1
+    ///
+    /// # Returns
+    /// New ThrottledStorage instance
+    pub fn new(inner: Arc<dyn Storage>, bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        ThrottledStorage {
+            inner,
+            limiter: Arc::new(Limiter {
+                bytes_per_sec,
+                bucket: Mutex::new(Bucket {
+                    tokens: bytes_per_sec as f64,
+                    refilled_at: Instant::now(),
+                    waiting: 0,
+                    throttled_writes: 0,
+                    throttled_for: Duration::ZERO,
+                }),
+            }),
+        }
+    }
+}
+
+impl Limiter {
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        let capacity = self.bytes_per_sec as f64;
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.refilled_at = now;
+    }
+
+    /// Takes `bytes` from the bucket, sleeping off any deficit
+    fn acquire(&self, bytes: usize) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            self.refill(&mut bucket);
+            bucket.tokens -= bytes as f64;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            let wait = Duration::from_secs_f64(-bucket.tokens / self.bytes_per_sec as f64);
+            bucket.waiting += 1;
+            bucket.throttled_writes += 1;
+            bucket.throttled_for += wait;
+            wait
+        };
+        log_debug!("write throttled for {:?}", wait);
+        std::thread::sleep(wait);
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner()).waiting -= 1;
+    }
+
+    fn state(&self) -> ThrottleState {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+        ThrottleState {
+            bytes_per_sec: self.bytes_per_sec,
+            available_bytes: bucket.tokens.max(0.0) as u64,
+            waiting: bucket.waiting,
+            throttled_writes: bucket.throttled_writes,
+            throttled_for: bucket.throttled_for,
+        }
+    }
+}
+
+/// Stream paying for every buffer before passing it on
+struct ThrottledWrite {
+    inner: Box<dyn io::Write + Send>,
+    limiter: Arc<Limiter>,
+}
+
+impl io::Write for ThrottledWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.limiter.acquire(buf.len());
+        self.inner.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Storage for ThrottledStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        self.limiter.acquire(content.len());
+        self.inner.write(path, content)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.inner.size(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_file(path)
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_new(path)
+    }
+
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        self.inner.set_shared(path)
+    }
+
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        self.inner.unshare(path)
+    }
+
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        self.inner.seal(path)
+    }
+
+    fn is_sealed(&self, path: &Path) -> bool {
+        self.inner.is_sealed(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+        self.inner.open(path)
+    }
+
+    fn stream(&self, path: &Path) -> io::Result<Box<dyn io::Write + Send>> {
+        Ok(Box::new(ThrottledWrite {
+            inner: self.inner.stream(path)?,
+            limiter: Arc::clone(&self.limiter),
+        }))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn throttle(&self) -> Option<ThrottleState> {
+        Some(self.limiter.state())
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * @filename: uring.rs
+ * @description: io_uring driver for async object reads and writes on Linux
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use io_uring::{opcode, squeue, types, IoUring};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::sync::OnceLock;
+use tokio::sync::oneshot;
+
+/// Submission queue size; further requests wait in the channel
+const RING_ENTRIES: u32 = 256;
+/// `user_data` of the eventfd read waking the driver thread
+const WAKE: u64 = u64::MAX;
+/// Bytes added to the buffer when a read fills it
+const READ_CHUNK: usize = 64 * 1024;
+/// Largest transfer of a single request
+const MAX_TRANSFER: usize = 1 << 30;
+
+enum Kind {
+    /// Fills the spare capacity of the buffer
+    Read,
+    /// Writes the buffer from `offset` on
+    Write,
+}
+
+/// Outcome of a request, handing the file and the buffer back
+type Completion = io::Result<(usize, File, Vec<u8>)>;
+
+/// One read or write in flight
+///
+/// The request owns the file and the buffer until the kernel completes
+/// it, so dropping the awaiting future never frees memory the kernel
+/// still writes to.
+struct Request {
+    kind: Kind,
+    file: File,
+    buf: Vec<u8>,
+    /// Position in the file
+    offset: u64,
+    reply: oneshot::Sender<Completion>,
+}
+
+impl Request {
+    fn entry(&mut self, id: u64) -> squeue::Entry {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let entry = match self.kind {
+            Kind::Read => {
+                let spare = self.buf.spare_capacity_mut();
+                let len = spare.len().min(MAX_TRANSFER) as u32;
+                opcode::Read::new(fd, spare.as_mut_ptr().cast(), len).offset(self.offset).build()
+            }
+            Kind::Write => {
+                let rest = &self.buf[self.offset as usize..];
+                let len = rest.len().min(MAX_TRANSFER) as u32;
+                opcode::Write::new(fd, rest.as_ptr(), len).offset(self.offset).build()
+            }
+        };
+        entry.user_data(id)
+    }
+}
+
+/// Handle to the ring shared by every cache of the process
+pub(crate) struct Driver {
+    sender: Sender<Request>,
+    /// Eventfd signalled after each request so the driver thread picks it up
+    wake: File,
+}
+
+static DRIVER: OnceLock<Option<Driver>> = OnceLock::new();
+
+/// Returns the shared ring, starting it on first use
+///
+/// `None` if the kernel does not offer io_uring or refuses it (old
+/// kernels, seccomp filters of containers), callers then fall back to
+/// the blocking pool.
+pub(crate) fn driver() -> Option<&'static Driver> {
+    DRIVER
+        .get_or_init(|| match Driver::start() {
+            Ok(driver) => Some(driver),
+            Err(e) => {
+                log_debug!("io_uring is unavailable, async I/O uses the blocking pool: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+impl Driver {
+    fn start() -> io::Result<Self> {
+        let ring = IoUring::new(RING_ENTRIES)?;
+        // SAFETY: eventfd has no preconditions, the result is checked below
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a new descriptor owned by nothing else
+        let wake = unsafe { File::from_raw_fd(fd) };
+        let wake_reader = wake.try_clone()?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("cache-lite-uring".to_string())
+            .spawn(move || drive(ring, receiver, wake_reader))?;
+        Ok(Driver { sender, wake })
+    }
+
+    async fn submit(&self, kind: Kind, file: File, buf: Vec<u8>, offset: u64) -> Completion {
+        let (reply, completion) = oneshot::channel();
+        self.sender
+            .send(Request { kind, file, buf, offset, reply })
+            .map_err(|_| io::Error::other("io_uring driver thread stopped"))?;
+        (&self.wake).write_all(&1u64.to_ne_bytes())?;
+        completion
+            .await
+            .map_err(|_| io::Error::other("io_uring driver thread stopped"))?
+    }
+
+    /// Reads the whole file at `path`
+    pub(crate) async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut buf = Vec::with_capacity(usize::try_from(size).unwrap_or(0));
+        loop {
+            if buf.len() == buf.capacity() {
+                buf.reserve(READ_CHUNK);
+            }
+            let offset = buf.len() as u64;
+            let (read, returned_file, mut returned_buf) = self.submit(Kind::Read, file, buf, offset).await?;
+            if read == 0 {
+                return Ok(returned_buf);
+            }
+            // SAFETY: the kernel initialized `read` bytes of the spare capacity
+            unsafe { returned_buf.set_len(returned_buf.len() + read) };
+            (file, buf) = (returned_file, returned_buf);
+        }
+    }
+
+    /// Replaces the content of the file at `path`
+    pub(crate) async fn write(&self, path: &Path, content: Vec<u8>) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        let mut buf = content;
+        let mut offset = 0;
+        while offset < buf.len() {
+            let (written, returned_file, returned_buf) = self.submit(Kind::Write, file, buf, offset as u64).await?;
+            if written == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            offset += written;
+            (file, buf) = (returned_file, returned_buf);
+        }
+        Ok(())
+    }
+}
+
+/// Queues an entry, submitting the full queue to the kernel if needed
+fn push(ring: &mut IoUring, entry: &squeue::Entry) {
+    // SAFETY: the buffer and descriptor of every entry stay owned by the
+    // driver thread (`inflight` or `wake_buf`) until its completion
+    while unsafe { ring.submission().push(entry) }.is_err() {
+        let _ = ring.submit();
+    }
+}
+
+/// Runs the ring: queues new requests and answers completed ones
+fn drive(mut ring: IoUring, receiver: mpsc::Receiver<Request>, wake: File) {
+    let mut wake_buf = Box::new([0u8; 8]);
+    let mut inflight: HashMap<u64, Request> = HashMap::new();
+    let mut next_id = 0u64;
+    let mut armed = false;
+    loop {
+        if !armed {
+            let entry = opcode::Read::new(types::Fd(wake.as_raw_fd()), wake_buf.as_mut_ptr(), 8)
+                .build()
+                .user_data(WAKE);
+            push(&mut ring, &entry);
+            armed = true;
+        }
+        if let Err(e) = ring.submit_and_wait(1) {
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            log_warn!("io_uring driver stopped: {}", e);
+            for (_, request) in inflight.drain() {
+                let _ = request.reply.send(Err(io::Error::new(e.kind(), e.to_string())));
+            }
+            return;
+        }
+
+        let completed: Vec<(u64, i32)> = ring.completion().map(|cqe| (cqe.user_data(), cqe.result())).collect();
+        for (id, result) in completed {
+            if id == WAKE {
+                armed = false;
+                loop {
+                    match receiver.try_recv() {
+                        Ok(mut request) => {
+                            let entry = request.entry(next_id);
+                            inflight.insert(next_id, request);
+                            next_id = next_id.wrapping_add(1) % WAKE;
+                            push(&mut ring, &entry);
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        // Every driver handle is gone, nothing can be submitted anymore
+                        Err(TryRecvError::Disconnected) if inflight.is_empty() => return,
+                        Err(TryRecvError::Disconnected) => break,
+                    }
+                }
+            } else if let Some(request) = inflight.remove(&id) {
+                let completion = match usize::try_from(result) {
+                    Ok(transferred) => Ok((transferred, request.file, request.buf)),
+                    Err(_) => Err(io::Error::from_raw_os_error(-result)),
+                };
+                let _ = request.reply.send(completion);
+            }
+        }
+    }
+}
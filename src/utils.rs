@@ -1,53 +1,322 @@
 // utils.rs
+use crate::config::{CachePathConfig, CacheConfig, CacheScope, UndefinedVarPolicy};
 use crate::{CacheResult, CacheError};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-/// Expands Windows environment variables
-fn expand_windows_env_vars(path: &str) -> String {
-    use std::env;
-    let mut result = path.to_string();
+/// Parses a glob pattern matched against object names
+///
+/// # Parameters
+/// - `pattern: &str` - Pattern with `*`, `?` and `[...]` wildcards
+///
+/// # Returns
+/// `CacheResult<glob::Pattern>` - Compiled pattern or `InvalidConfig`
+#[cfg(feature = "glob")]
+pub fn parse_name_pattern(pattern: &str) -> CacheResult<glob::Pattern> {
+    glob::Pattern::new(pattern)
+        .map_err(|e| CacheError::InvalidConfig(format!("invalid name pattern '{}': {}", pattern, e)))
+}
+
+/// Checks whether `name` is a plausible environment variable name
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '(' || c == ')')
+}
+
+
+/// Expands `${VAR}` and `%VAR%` environment variables on every platform
+///
+/// # Parameters
+/// - `path: &str` - Path containing variable references
+/// - `policy: UndefinedVarPolicy` - What to do with undefined variables
+///
+/// # Returns
+/// `CacheResult<String>` - Expanded path, or `InvalidPath` for an undefined
+/// variable under `UndefinedVarPolicy::Error`
+pub fn expand_env_vars(path: &str, policy: UndefinedVarPolicy) -> CacheResult<String> {
+    expand_vars_with(path, policy, |name| std::env::var(name).ok())
+}
+
+/// Expands variables like `expand_env_vars()`, reading them through `lookup`
+///
+/// Variables are looked up as written, then upper-cased (`%temp%` -> `TEMP`).
+pub(crate) fn expand_vars_with(
+    path: &str,
+    policy: UndefinedVarPolicy,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> CacheResult<String> {
+    let mut result = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(pos) = rest.find(['$', '%']) {
+        result.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
 
-    // Simple environment variable expansion
-    if let Ok(temp) = env::var("TEMP") {
-        result = result.replace("%temp%", &temp);
+        let reference = if let Some(inner) = tail.strip_prefix("${") {
+            inner.find('}').map(|end| (&inner[..end], end + 3))
+        } else if let Some(inner) = tail.strip_prefix('%') {
+            inner.find('%').map(|end| (&inner[..end], end + 2))
+        } else {
+            None
+        };
+
+        // Not a variable reference, keep the sigil literally
+        let Some((name, len)) = reference.filter(|(name, _)| is_var_name(name)) else {
+            result.push_str(&tail[..1]);
+            rest = &tail[1..];
+            continue;
+        };
+
+        match lookup(name).or_else(|| lookup(&name.to_uppercase())) {
+            Some(value) => result.push_str(&value),
+            None if policy == UndefinedVarPolicy::Passthrough => result.push_str(&tail[..len]),
+            None => {
+                return Err(CacheError::InvalidPath(format!(
+                    "Environment variable '{}' used in '{}' is not defined",
+                    name, path
+                )));
+            }
+        }
+        rest = &tail[len..];
     }
-    if let Ok(tmp) = env::var("TMP") {
-        result = result.replace("%tmp%", &tmp);
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Cache directory supplied by the host application at runtime
+static PLATFORM_CACHE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the platform cache directory used for the `{cache_dir}` placeholder
+///
+/// Mobile platforms don't expose their cache directory through
+/// environment variables: on Android pass the result of
+/// `Context.getCacheDir()`, on iOS it defaults to `Library/Caches`.
+///
+/// # Parameters
+/// - `path: impl Into<PathBuf>` - Cache directory of the application
+pub fn set_platform_cache_dir(path: impl Into<PathBuf>) {
+    replace_platform_cache_dir(Some(path.into()));
+}
+
+/// Replaces the directory set with `set_platform_cache_dir()`, returning the previous one
+pub(crate) fn replace_platform_cache_dir(path: Option<PathBuf>) -> Option<PathBuf> {
+    std::mem::replace(&mut *PLATFORM_CACHE_DIR.write().unwrap_or_else(|e| e.into_inner()), path)
+}
+
+/// Returns the directory the `{cache_dir}` placeholder expands to
+///
+/// On Linux this follows the XDG Base Directory specification:
+/// `$XDG_CACHE_HOME`, falling back to `~/.cache`. Without the `dirs`
+/// feature only a directory set with `set_platform_cache_dir()` is known.
+///
+/// # Returns
+/// `Option<PathBuf>` - Injected directory, or the platform default if known
+pub fn platform_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = PLATFORM_CACHE_DIR.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Some(dir);
     }
-    if let Ok(appdata) = env::var("APPDATA") {
-        result = result.replace("%appdata%", &appdata);
+    #[cfg(feature = "dirs")]
+    {
+        if cfg!(target_os = "ios") {
+            return dirs::home_dir().map(|home| home.join("Library").join("Caches"));
+        }
+        dirs::cache_dir()
     }
-    if let Ok(localappdata) = env::var("LOCALAPPDATA") {
-        result = result.replace("%localappdata%", &localappdata);
+    #[cfg(not(feature = "dirs"))]
+    None
+}
+
+/// Returns the home directory `~` expands to
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    #[cfg(feature = "dirs")]
+    {
+        dirs::home_dir()
     }
-    if let Ok(userprofile) = env::var("USERPROFILE") {
-        result = result.replace("%userprofile%", &userprofile);
+    #[cfg(not(feature = "dirs"))]
+    {
+        std::env::home_dir()
     }
+}
 
-    result
+/// Returns the machine-wide cache directory shared by all users
+///
+/// # Returns
+/// `Option<PathBuf>` - `%ProgramData%` on Windows, `/Library/Caches` on
+/// macOS, `/var/cache` on other Unix systems, `None` on mobile platforms
+pub fn system_cache_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let data = std::env::var_os("ProgramData").unwrap_or_else(|| r"C:\ProgramData".into());
+        Some(PathBuf::from(data))
+    } else if cfg!(any(target_os = "android", target_os = "ios")) {
+        None
+    } else if cfg!(target_os = "macos") {
+        Some(PathBuf::from("/Library/Caches"))
+    } else {
+        Some(PathBuf::from("/var/cache"))
+    }
 }
 
 /// Expands environment variables in path
+///
+/// Undefined variables are left untouched; use `expand_path_with()` to
+/// reject them.
 pub fn expand_path(path: &str) -> String {
+    expand_path_with(path, UndefinedVarPolicy::Passthrough).unwrap_or_else(|_| path.to_string())
+}
+
+/// Expands placeholders, environment variables and `~` in path
+///
+/// # Parameters
+/// - `path: &str` - Path template
+/// - `policy: UndefinedVarPolicy` - What to do with undefined variables
+///
+/// # Returns
+/// `CacheResult<String>` - Expanded path or `InvalidPath` error
+pub fn expand_path_with(path: &str, policy: UndefinedVarPolicy) -> CacheResult<String> {
     let mut expanded = path.to_string();
 
-    // Expand Windows environment variables
-    if cfg!(windows) && path.contains("%") {
-        expanded = expand_windows_env_vars(path);
+    if expanded.contains("{cache_dir}")
+        && let Some(dir) = platform_cache_dir()
+    {
+        expanded = expanded.replace("{cache_dir}", &dir.to_string_lossy());
+    }
+
+    // Expand ${VAR} and %VAR% environment variables
+    if expanded.contains(['$', '%']) {
+        expanded = expand_env_vars(&expanded, policy)?;
     }
 
     // Expand tilde for home directory (Unix-like systems)
-    if expanded.starts_with('~') {
-        if let Some(home) = dirs::home_dir() {
-            expanded = home.to_string_lossy().to_string() + &expanded[1..];
+    if expanded.starts_with('~')
+        && let Some(home) = home_dir()
+    {
+        expanded = home.to_string_lossy().to_string() + &expanded[1..];
+    }
+
+    if cfg!(windows) && is_unc_path(&expanded) {
+        // Separator conversion would leave doubled separators in UNC roots
+        expanded = normalize_unc_path(&expanded);
+    } else {
+        #[cfg(windows)]
+        {
+            expanded = expanded.replace('/', "\\");
         }
     }
 
-    #[cfg(windows)]
-    {
-        expanded = expanded.replace('/', "\\");
+    if expanded != path {
+        log_debug!("expanded path '{}' to '{}'", path, expanded);
+    }
+
+    Ok(expanded)
+}
+
+/// Checks whether a path is a UNC network path (`\\server\share`)
+///
+/// Device and verbatim paths (`\\?\`, `\\.\`) are not treated as UNC.
+pub fn is_unc_path(path: &str) -> bool {
+    let is_sep = |c: char| c == '\\' || c == '/';
+    let mut chars = path.chars();
+    matches!((chars.next(), chars.next()), (Some(a), Some(b)) if is_sep(a) && is_sep(b))
+        && !path.trim_start_matches(is_sep).starts_with(['?', '.'])
+}
+
+/// Normalizes a UNC path to `\\server\share\rest`
+///
+/// Any mix of forward and back slashes, including the doubled separators
+/// produced by escaping backslashes in JSON, collapses to single backslashes.
+pub fn normalize_unc_path(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .split(['\\', '/'])
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    format!("\\\\{}", segments.join("\\"))
+}
+
+/// Returns the `\\server\share` root of a UNC path
+///
+/// # Returns
+/// `Option<String>` - Share root, or None if `path` is not a UNC path
+pub fn unc_share_root(path: &str) -> Option<String> {
+    if !is_unc_path(path) {
+        return None;
+    }
+    let normalized = normalize_unc_path(path);
+    let mut segments = normalized.trim_start_matches('\\').split('\\');
+    match (segments.next(), segments.next()) {
+        (Some(server), Some(share)) => Some(format!("\\\\{}\\{}", server, share)),
+        _ => None,
+    }
+}
+
+/// Checks that the network share holding `path` can be reached
+///
+/// Only UNC paths on Windows are checked, anything else succeeds.
+///
+/// # Returns
+/// `CacheResult<()>` - Success, or `InvalidPath` if the share is offline
+pub fn check_share_reachable(path: &str) -> CacheResult<()> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+    let Some(root) = unc_share_root(path) else {
+        return Ok(());
+    };
+    std::fs::metadata(&root).map(|_| ()).map_err(|e| {
+        log_warn!("network share {} is unreachable: {}", root, e);
+        CacheError::InvalidPath(format!("Network share {} is unreachable: {}", root, e))
+    })
+}
+
+/// Resolves the cache directory for the current platform
+///
+/// # Parameters
+/// - `config: &CacheConfig` - Configuration holding the platform paths
+///
+/// # Returns
+/// `CacheResult<PathBuf>` - Expanded cache directory or `InvalidPath` error
+pub fn resolve_cache_dir(config: &CacheConfig) -> CacheResult<PathBuf> {
+    let policy = config.path.undefined_vars;
+    let path = config.path.resolve();
+    match config.path.scope {
+        CacheScope::User => {
+            if cfg!(target_os = "linux") && config.path.legacy_tmp_dir {
+                return expand_path_with(&path.replace("{cache_dir}", "/tmp"), policy).map(PathBuf::from);
+            }
+            expand_path_with(path, policy).map(PathBuf::from)
+        }
+        CacheScope::System => {
+            let Some(dir) = system_cache_dir() else {
+                return Err(CacheError::InvalidPath(
+                    "No machine-wide cache directory exists on this platform".to_string(),
+                ));
+            };
+            // The built-in Windows default lives in the per-user %temp%
+            let path = if path == CachePathConfig::default().resolve() && !path.contains("{cache_dir}") {
+                "{cache_dir}/Rust/Cache"
+            } else {
+                path
+            };
+            expand_path_with(&path.replace("{cache_dir}", &dir.to_string_lossy()), policy)
+                .map(PathBuf::from)
+        }
+        CacheScope::Custom => {
+            if path.contains("{cache_dir}") {
+                return Err(CacheError::InvalidPath(format!(
+                    "'{}' uses {{cache_dir}}, which is not allowed with scope 'custom'",
+                    path
+                )));
+            }
+            expand_path_with(path, policy).map(PathBuf::from)
+        }
     }
+}
 
-    expanded
+/// Returns the closest ancestor of `path` (including itself) that exists
+pub fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
 }
 
 /// Validates if a cache name is valid
@@ -98,22 +367,11 @@ pub fn validate_name(name: &str) -> CacheResult<()> {
     }
 
     #[cfg(windows)]
-    {
-        let reserved_names = [
-            "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7",
-            "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
-        ];
-
-        let uppercase_name = name.to_uppercase();
-        for reserved in &reserved_names {
-            if uppercase_name == *reserved || uppercase_name.starts_with(&format!("{}.", reserved))
-            {
-                return Err(CacheError::InvalidName(format!(
-                    "Cache name '{}' is a reserved system name",
-                    name
-                )));
-            }
-        }
+    if is_reserved_name(name) {
+        return Err(CacheError::InvalidName(format!(
+            "Cache name '{}' is a reserved system name",
+            name
+        )));
     }
 
     if name.chars().any(|c| c.is_control()) {
@@ -124,3 +382,152 @@ pub fn validate_name(name: &str) -> CacheResult<()> {
 
     Ok(())
 }
+
+/// Returns a key equal for paths naming the same file on this platform's file system
+///
+/// File names are compared case-insensitively on Windows and macOS.
+pub(crate) fn path_key(path: &Path) -> PathBuf {
+    if cfg!(any(windows, target_os = "macos")) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Checks whether a file name is a device name on Windows (`CON`, `COM1.txt`, ...)
+pub(crate) fn is_reserved_name(name: &str) -> bool {
+    const RESERVED_NAMES: [&str; 22] = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7",
+        "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    let uppercase_name = name.to_uppercase();
+    RESERVED_NAMES.iter().any(|reserved| {
+        uppercase_name == *reserved || uppercase_name.starts_with(&format!("{}.", reserved))
+    })
+}
+
+/// Recovers an object name from a file name
+///
+/// Only possible when `format` contains `{name}` once and neither `{time}`
+/// nor `{id}`.
+pub(crate) fn name_from_file(format: &str, file_name: &std::ffi::OsStr) -> Option<String> {
+    if format.contains("{time}") || format.contains("{id}") || format.matches("{name}").count() != 1 {
+        return None;
+    }
+    let (prefix, suffix) = format.split_once("{name}")?;
+    let name = file_name.to_str()?.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Builds the file name of an object from a `{name}`-only format
+///
+/// Returns `None` when `format` also uses `{time}` or `{id}`, whose values
+/// cannot be known in advance.
+pub(crate) fn file_from_name(format: &str, name: &str) -> Option<String> {
+    if format.contains("{time}") || format.contains("{id}") || !format.contains("{name}") {
+        return None;
+    }
+    Some(format.replace("{name}", name))
+}
+
+/// Makes `to` share the content of `from` without copying it
+///
+/// Tries a reflink first, whose blocks the filesystem copies on write by
+/// itself, then a hard link, which `Storage::unshare()` breaks before
+/// the file is modified.
+///
+/// # Returns
+/// `std::io::Result<()>` - Success, or the error of the hard link if the
+/// files cannot share content
+pub(crate) fn share_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if reflink(from, to).is_ok() {
+        return Ok(());
+    }
+    if cfg!(unix) {
+        std::fs::hard_link(from, to)
+    } else {
+        // Without a link count the link could not be broken on write
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Clones `from` into a new file `to` on filesystems supporting it
+#[cfg(target_os = "linux")]
+fn reflink(from: &Path, to: &Path) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+    let source = std::fs::File::open(from)?;
+    let target = std::fs::OpenOptions::new().write(true).create_new(true).open(to)?;
+    // SAFETY: both descriptors are owned by the open files for the call
+    let ret = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if ret == 0 {
+        return Ok(());
+    }
+    let e = std::io::Error::last_os_error();
+    drop(target);
+    let _ = std::fs::remove_file(to);
+    Err(e)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_from: &Path, _to: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Replaces a file that has other hard links by a copy of its own
+#[cfg(unix)]
+pub(crate) fn break_hard_link(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.nlink() > 1 => {}
+        _ => return Ok(()),
+    }
+    let copy = crate::template::suffixed(path, ".unshare");
+    std::fs::copy(path, &copy)?;
+    std::fs::rename(&copy, path)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn break_hard_link(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Runs `f` over `items` on at most `parallelism` scoped threads
+///
+/// Results are returned in the order of `items`. Small inputs and a
+/// parallelism of 1 run on the calling thread.
+pub(crate) fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    parallelism: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let threads = parallelism.max(1).min(items.len() / PARALLEL_MIN_ITEMS);
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        let Some(item) = items.get(i) else { break };
+                        done.push((i, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Items each extra thread of `parallel_map` must have to be worth spawning
+const PARALLEL_MIN_ITEMS: usize = 32;
@@ -0,0 +1,238 @@
+/*
+ * @filename: watch.rs
+ * @description: Watching the cache directory for changes made by other processes
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cache::CacheContext;
+use crate::event::CacheEvent;
+use crate::{CacheError, CacheResult};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+/// Modification time of a file, `None` if it does not exist
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Changes made by this process, which the watcher must not report
+#[derive(Debug, Default)]
+pub(crate) struct OwnChanges {
+    enabled: AtomicBool,
+    entries: Mutex<HashMap<OsString, Option<SystemTime>>>,
+}
+
+impl OwnChanges {
+    /// Records the current state of a file this process just changed
+    pub(crate) fn note(&self, path: &Path) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(file_name) = path.file_name() {
+            self.entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(file_name.to_os_string(), modified(path));
+        }
+    }
+
+    /// Checks whether the file is still in the state this process left it in
+    fn is_own(&self, file_name: &OsStr, state: Option<SystemTime>) -> bool {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(file_name)
+            .is_some_and(|own| *own == state)
+    }
+}
+
+/// Callback invoked when another process changes a matching object
+pub(crate) type InvalidationCallback = Arc<dyn Fn(&CacheEvent) + Send + Sync>;
+
+/// Callbacks registered with `Cache::on_invalidated()`
+#[derive(Default)]
+pub(crate) struct Invalidations {
+    callbacks: RwLock<Vec<(glob::Pattern, InvalidationCallback)>>,
+}
+
+impl Invalidations {
+    pub(crate) fn add(&self, pattern: glob::Pattern, callback: InvalidationCallback) {
+        self.callbacks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((pattern, callback));
+    }
+
+    /// Calls the matching callbacks without holding the lock, so they may
+    /// register further callbacks
+    fn notify(&self, event: &CacheEvent) {
+        let matching: Vec<InvalidationCallback> = self
+            .callbacks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(event.name()))
+            .map(|(_, callback)| Arc::clone(callback))
+            .collect();
+        for callback in matching {
+            callback(event);
+        }
+    }
+}
+
+impl std::fmt::Debug for Invalidations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let callbacks = self.callbacks.read().unwrap_or_else(|e| e.into_inner());
+        f.debug_list()
+            .entries(callbacks.iter().map(|(pattern, _)| pattern.as_str()))
+            .finish()
+    }
+}
+
+/// State shared with the notification thread
+struct WatchState {
+    /// Tracked objects by file name
+    index: RwLock<HashMap<OsString, (String, u32)>>,
+    /// Paths changed since the last `Cache::refresh()`
+    pending: Mutex<Vec<PathBuf>>,
+    context: Arc<CacheContext>,
+}
+
+impl WatchState {
+    fn handle(&self, path: &Path) {
+        let Some(file_name) = path.file_name() else {
+            return;
+        };
+        if path.is_dir() {
+            return;
+        }
+        let state = modified(path);
+        if self.context.own_changes.is_own(file_name, state) {
+            return;
+        }
+
+        let known = self
+            .index
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(file_name)
+            .cloned();
+        if let Some((name, id)) = known {
+            let event = match state {
+                Some(_) => CacheEvent::Changed { name, id },
+                None => CacheEvent::Removed { name, id },
+            };
+            self.context.invalidations.notify(&event);
+            self.context.events.emit_with(|| event);
+        }
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(path.to_path_buf());
+    }
+}
+
+/// Watches a cache directory and queues external changes
+pub(crate) struct DirWatcher {
+    _watcher: RecommendedWatcher,
+    state: Arc<WatchState>,
+}
+
+impl DirWatcher {
+    /// Starts watching `dir`, which must exist
+    pub(crate) fn start(dir: &Path, context: Arc<CacheContext>) -> CacheResult<Self> {
+        context.own_changes.enabled.store(true, Ordering::Relaxed);
+        let state = Arc::new(WatchState {
+            index: RwLock::new(HashMap::new()),
+            pending: Mutex::new(Vec::new()),
+            context,
+        });
+
+        let handler_state = Arc::clone(&state);
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                for path in &event.paths {
+                    handler_state.handle(path);
+                }
+            }
+        })
+        .map_err(|e| CacheError::Io(std::io::Error::other(e)))?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| CacheError::Io(std::io::Error::other(e)))?;
+
+        Ok(DirWatcher {
+            _watcher: watcher,
+            state,
+        })
+    }
+
+    /// Starts reporting changes of an object's file
+    pub(crate) fn track(&self, path: &Path, name: &str, id: u32) {
+        if let Some(file_name) = path.file_name() {
+            self.state
+                .index
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(file_name.to_os_string(), (name.to_string(), id));
+        }
+    }
+
+    /// Stops reporting changes of an object's file
+    pub(crate) fn untrack(&self, path: &Path) {
+        if let Some(file_name) = path.file_name() {
+            self.state
+                .index
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(file_name);
+        }
+    }
+
+    /// Returns the tracked object stored in a file
+    pub(crate) fn lookup(&self, path: &Path) -> Option<(String, u32)> {
+        let file_name = path.file_name()?;
+        self.state
+            .index
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(file_name)
+            .cloned()
+    }
+
+    /// Takes the paths changed since the previous call
+    pub(crate) fn take_pending(&self) -> Vec<PathBuf> {
+        let mut paths = std::mem::take(&mut *self.state.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
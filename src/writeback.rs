@@ -0,0 +1,384 @@
+/*
+ * @filename: writeback.rs
+ * @description: Write-back storage flushing dirty entries in batches
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+
+use crate::config::{QueueFullPolicy, WriteBackConfig};
+use crate::storage::Storage;
+use crate::throttle::ThrottleState;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Payload of the `WouldBlock` error of a full queue with `on_full: "fail"`
+///
+/// Marks the error as deliberate, so retry policies do not wait for the
+/// queue to drain.
+#[derive(Debug)]
+pub(crate) struct QueueFull(String);
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for QueueFull {}
+
+/// Storage backend deferring writes to a background flusher
+///
+/// `write()` only records the new content; reads see it immediately.
+/// Dirty entries are coalesced (rewriting an entry before it is flushed
+/// costs one write) and written to the inner backend in batches every
+/// `interval_ms`, or as soon as `max_dirty_bytes` are pending. Removals
+/// and file-handle access are applied right away. Call `flush()` (or
+/// `Cache::flush()` / `Cache::flush_all()`) for explicit sync points;
+/// dropping the backend flushes what is left.
+///
+/// Used by `Cache::new()` when `io.write_back` is set.
+pub struct WriteBackStorage {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    inner: Arc<dyn Storage>,
+    config: WriteBackConfig,
+    state: Mutex<State>,
+    /// Wakes the flusher on threshold crossings and shutdown
+    wake: Condvar,
+    /// Wakes writers waiting for room in the queue after a flush
+    drained: Condvar,
+    /// Serializes flushes with the operations bypassing the buffer
+    io: Mutex<()>,
+}
+
+#[derive(Default)]
+struct State {
+    dirty: HashMap<PathBuf, Arc<[u8]>>,
+    /// Entries of the batch being written, still served to readers
+    in_flight: HashMap<PathBuf, Arc<[u8]>>,
+    dirty_bytes: usize,
+    in_flight_bytes: usize,
+    /// Set by writers that need a flush before the next tick
+    flush_requested: bool,
+    /// Number of completed flushes, lets blocked writers tell one happened
+    flushes: u64,
+    /// Error of the last flush, if it failed
+    flush_error: Option<(io::ErrorKind, String)>,
+    closed: bool,
+}
+
+impl WriteBackStorage {
+    /// Wraps a backend and starts its flusher thread
+    ///
+    /// # Parameters
+    /// - `inner: Arc<dyn Storage>` - Backend receiving the flushed entries
+    /// - `config: WriteBackConfig` - Flush interval and dirty threshold
+    ///
+    /// # Returns
+    /// New WriteBackStorage instance
+    pub fn new(inner: Arc<dyn Storage>, config: WriteBackConfig) -> Self {
+        let shared = Arc::new(Shared {
+            inner,
+            config,
+            state: Mutex::new(State::default()),
+            wake: Condvar::new(),
+            drained: Condvar::new(),
+            io: Mutex::new(()),
+        });
+        let flusher = Arc::clone(&shared);
+        std::thread::spawn(move || flusher.run());
+        WriteBackStorage { shared }
+    }
+
+    /// Returns the number of bytes written but not flushed yet
+    ///
+    /// # Returns
+    /// `usize` - Pending bytes
+    pub fn dirty_bytes(&self) -> usize {
+        self.shared.state().dirty_bytes
+    }
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn io(&self) -> MutexGuard<'_, ()> {
+        self.io.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the buffered content of `path`, if any
+    fn buffered(&self, path: &Path) -> Option<Arc<[u8]>> {
+        let state = self.state();
+        state.dirty.get(path).or_else(|| state.in_flight.get(path)).cloned()
+    }
+
+    /// Drops the buffered content of `path`, which is about to be replaced
+    fn discard(&self, path: &Path) -> bool {
+        let mut state = self.state();
+        match state.dirty.remove(path) {
+            Some(content) => {
+                state.dirty_bytes -= content.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Flusher thread: flushes on every tick and threshold crossing
+    fn run(&self) {
+        let interval = Duration::from_millis(self.config.interval_ms.max(1));
+        let mut state = self.state();
+        loop {
+            if state.closed {
+                return;
+            }
+            if !state.flush_requested {
+                state = self.wake.wait_timeout(state, interval).unwrap_or_else(|e| e.into_inner()).0;
+                if state.closed {
+                    return;
+                }
+            }
+            state.flush_requested = false;
+            if state.dirty.is_empty() {
+                continue;
+            }
+            drop(state);
+            if let Err(e) = self.flush() {
+                log_warn!("write-back flush failed: {}", e);
+            }
+            state = self.state();
+        }
+    }
+
+    /// Writes every dirty entry to the inner backend
+    ///
+    /// Entries failing to flush stay dirty unless rewritten meanwhile, the
+    /// first error is returned.
+    fn flush(&self) -> io::Result<()> {
+        let _io = self.io();
+        let batch = {
+            let mut state = self.state();
+            state.in_flight_bytes = std::mem::take(&mut state.dirty_bytes);
+            state.in_flight = std::mem::take(&mut state.dirty);
+            state.in_flight.clone()
+        };
+        if batch.is_empty() {
+            return Ok(());
+        }
+        log_debug!("write-back flushing {} entries", batch.len());
+
+        let mut result = Ok(());
+        let mut failed = Vec::new();
+        for (path, content) in batch {
+            if let Err(e) = self.inner.write(&path, &content) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+                failed.push((path, content));
+            }
+        }
+
+        let mut state = self.state();
+        state.in_flight.clear();
+        state.in_flight_bytes = 0;
+        for (path, content) in failed {
+            if !state.dirty.contains_key(&path) {
+                state.dirty_bytes += content.len();
+                state.dirty.insert(path, content);
+            }
+        }
+        state.flushes += 1;
+        state.flush_error = result.as_ref().err().map(|e| (e.kind(), e.to_string()));
+        self.drained.notify_all();
+        result
+    }
+}
+
+impl Storage for WriteBackStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.shared.buffered(path) {
+            Some(content) => Ok(content.to_vec()),
+            None => self.shared.inner.read(path),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        let config = &self.shared.config;
+        let mut state = self.shared.state();
+        if let Some(limit) = config.max_queue_bytes {
+            loop {
+                let replaced = state.dirty.get(path).map_or(0, |previous| previous.len());
+                let queued = state.dirty_bytes + state.in_flight_bytes - replaced;
+                // A single entry larger than the limit still passes an empty queue
+                if queued == 0 || queued + content.len() <= limit {
+                    break;
+                }
+                match config.on_full {
+                    QueueFullPolicy::Fail => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WouldBlock,
+                            QueueFull(format!("write-back queue is full ({} of {} bytes)", queued, limit)),
+                        ));
+                    }
+                    QueueFullPolicy::Block => {
+                        state.flush_requested = true;
+                        self.shared.wake.notify_one();
+                        let flushes = state.flushes;
+                        state = self.shared.drained.wait(state).unwrap_or_else(|e| e.into_inner());
+                        // A flush that failed would fail again, waiting could last forever
+                        if state.flushes != flushes
+                            && let Some((kind, message)) = &state.flush_error
+                        {
+                            return Err(io::Error::new(
+                                *kind,
+                                format!("write-back queue is full and flushing it failed: {}", message),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        state.dirty_bytes += content.len();
+        if let Some(previous) = state.dirty.insert(path.to_path_buf(), Arc::from(content)) {
+            state.dirty_bytes -= previous.len();
+        }
+        if state.dirty_bytes >= config.max_dirty_bytes {
+            state.flush_requested = true;
+            self.shared.wake.notify_one();
+        }
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let _io = self.shared.io();
+        let buffered = self.shared.discard(path);
+        match self.shared.inner.remove(path) {
+            // Never flushed, only the buffered content existed
+            Err(e) if buffered && e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.shared.buffered(path).is_some() || self.shared.inner.exists(path)
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        match self.shared.buffered(path) {
+            Some(content) => Ok(content.len() as u64),
+            None => self.shared.inner.size(path),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.shared.inner.create_dir_all(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        let _io = self.shared.io();
+        self.shared.discard(path);
+        self.shared.inner.create_file(path)
+    }
+
+    fn create_new(&self, path: &Path) -> io::Result<()> {
+        let _io = self.shared.io();
+        if self.shared.buffered(path).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        self.shared.inner.create_new(path)
+    }
+
+    fn set_shared(&self, path: &Path) -> io::Result<()> {
+        self.shared.inner.set_shared(path)
+    }
+
+    fn unshare(&self, path: &Path) -> io::Result<()> {
+        self.shared.inner.unshare(path)
+    }
+
+    fn seal(&self, path: &Path) -> io::Result<()> {
+        self.shared.inner.seal(path)
+    }
+
+    fn is_sealed(&self, path: &Path) -> bool {
+        self.shared.inner.is_sealed(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+        // The handle must see the latest content
+        self.shared.flush()?;
+        self.shared.inner.open(path)
+    }
+
+    fn stream(&self, path: &Path) -> io::Result<Box<dyn io::Write + Send>> {
+        let _io = self.shared.io();
+        self.shared.discard(path);
+        self.shared.inner.stream(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.shared.flush()?;
+        let _io = self.shared.io();
+        self.shared.discard(to);
+        self.shared.inner.rename(from, to)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.shared.flush()
+    }
+
+    fn throttle(&self) -> Option<ThrottleState> {
+        self.shared.inner.throttle()
+    }
+}
+
+impl Drop for WriteBackStorage {
+    fn drop(&mut self) {
+        if let Err(e) = self.shared.flush() {
+            log_warn!("write-back flush on drop failed: {}", e);
+        }
+        self.shared.state().closed = true;
+        self.shared.wake.notify_one();
+    }
+}
+
+impl fmt::Debug for WriteBackStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteBackStorage")
+            .field("inner", &self.shared.inner)
+            .field("config", &self.shared.config)
+            .field("dirty_bytes", &self.dirty_bytes())
+            .finish()
+    }
+}
@@ -0,0 +1,281 @@
+/*
+ * @filename: writer.rs
+ * @description: Buffered incremental writer for cache objects
+ * @author: TaimWay <taimway@gmail.com>
+ *
+ * Copyright (C) 2026 TaimWay
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::manifest::hex;
+use crate::middleware::Operation;
+use crate::object::CacheObject;
+use crate::space::SPACE_CHECK_INTERVAL;
+use crate::telemetry::OpSpan;
+use crate::{CacheError, CacheResult};
+use sha2::{Digest, Sha256};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Size and checksum of content written with a `CacheWriter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteSummary {
+    /// Number of bytes written
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the content, as in manifests
+    pub sha256: String,
+}
+
+/// Destination of the buffered content
+enum Sink {
+    /// Streamed directly to the storage backend
+    Stream(Box<dyn Write + Send>),
+    /// Collected for backends that cannot stream
+    Collect(Vec<u8>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Stream(stream) => stream.write(buf),
+            Sink::Collect(content) => content.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Stream(stream) => stream.flush(),
+            Sink::Collect(_) => Ok(()),
+        }
+    }
+}
+
+/// Buffered writer returned by `CacheObject::buffered_writer()`
+///
+/// Keeps a running size and SHA-256 of the content; both are returned by
+/// `finish()`, which also emits the `Written` event, counts the bytes in
+/// metrics and marks dependent objects stale like `write_bytes()` does.
+/// The content is staged in a `.partial` file like `ChunkedWrite`, so the
+/// entry keeps its previous content until `finish()` and is left untouched
+/// when the writer is dropped or a middleware rejects the write.
+///
+/// ```rust
+/// use std::io::Write;
+///
+/// let mut cache = cache_lite::Cache::in_memory();
+/// let obj = cache.create("log", None).unwrap();
+/// let mut writer = obj.buffered_writer(8 * 1024).unwrap();
+/// for line in 0..3 {
+///     writeln!(writer, "line {}", line).unwrap();
+/// }
+/// assert_eq!(writer.finish().unwrap().size, 21);
+/// ```
+pub struct CacheWriter<'a> {
+    staged: ChunkedWrite<'a>,
+}
+
+impl<'a> CacheWriter<'a> {
+    pub(crate) fn new(object: &'a CacheObject, capacity: usize) -> CacheResult<Self> {
+        Ok(CacheWriter {
+            staged: ChunkedWrite::with_capacity(object, capacity)?,
+        })
+    }
+
+    /// Flushes the remaining content and reports the write
+    ///
+    /// # Returns
+    /// `CacheResult<WriteSummary>` - Size and checksum of the written content
+    pub fn finish(self) -> CacheResult<WriteSummary> {
+        self.staged.commit()
+    }
+}
+
+impl Write for CacheWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.staged.push_chunk(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.staged.inner {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Staged write started with `CacheObject::begin_write()`
+///
+/// Chunks are written to a temporary `.partial` file next to the entry,
+/// which replaces the entry only on `commit()`. Until then readers keep
+/// seeing the previous content, and an interrupted or dropped write never
+/// leaves a truncated entry behind.
+///
+/// ```rust
+/// let mut cache = cache_lite::Cache::in_memory();
+/// let obj = cache.create("artifact", None).unwrap();
+/// let mut write = obj.begin_write().unwrap().on_progress(|written| println!("{} bytes", written));
+/// for chunk in [b"part one, ".as_slice(), b"part two"] {
+///     write.push_chunk(chunk).unwrap();
+/// }
+/// assert_eq!(write.commit().unwrap().size, 18);
+/// ```
+pub struct ChunkedWrite<'a> {
+    object: &'a CacheObject,
+    /// Temporary file, `None` for backends that cannot stream
+    staging: Option<PathBuf>,
+    inner: Option<BufWriter<Sink>>,
+    hasher: Sha256,
+    size: u64,
+    progress: Option<Box<dyn FnMut(u64) + Send + 'a>>,
+}
+
+impl<'a> ChunkedWrite<'a> {
+    pub(crate) fn begin(object: &'a CacheObject) -> CacheResult<Self> {
+        Self::with_capacity(object, object.buffer_sizes().1)
+    }
+
+    /// Starts a staged write buffering `capacity` bytes before each write to the staging file
+    fn with_capacity(object: &'a CacheObject, capacity: usize) -> CacheResult<Self> {
+        object.check_sealed()?;
+        let mut staging = object.path().as_os_str().to_owned();
+        staging.push(".partial");
+        let staging = PathBuf::from(staging);
+
+        let storage = object.storage();
+        let staged = storage.create_file(&staging).and_then(|_| storage.stream(&staging));
+        let (staging, sink) = match staged {
+            Ok(stream) => (Some(staging), Sink::Stream(stream)),
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+                let _ = storage.remove(&staging);
+                (None, Sink::Collect(Vec::new()))
+            }
+            Err(e) => return object.report("write", Err(CacheError::Io(e))),
+        };
+        Ok(ChunkedWrite {
+            object,
+            staging,
+            inner: Some(BufWriter::with_capacity(capacity, sink)),
+            hasher: Sha256::new(),
+            size: 0,
+            progress: None,
+        })
+    }
+
+    /// Calls `callback` with the total number of bytes staged after each chunk
+    ///
+    /// # Parameters
+    /// - `callback: impl FnMut(u64) + Send + 'a` - Progress callback
+    ///
+    /// # Returns
+    /// `Self` - The write with the callback attached
+    pub fn on_progress(mut self, callback: impl FnMut(u64) + Send + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Appends a chunk to the staged content
+    ///
+    /// # Parameters
+    /// - `chunk: &[u8]` - Next part of the content
+    ///
+    /// # Returns
+    /// `CacheResult<()>` - Success or error
+    pub fn push_chunk(&mut self, chunk: &[u8]) -> CacheResult<()> {
+        self.object.check_entry_size(self.size + chunk.len() as u64)?;
+        if (self.size + chunk.len() as u64) / SPACE_CHECK_INTERVAL > self.size / SPACE_CHECK_INTERVAL {
+            self.object.check_free_space(chunk.len().max(SPACE_CHECK_INTERVAL as usize) as u64)?;
+        }
+        if let Some(inner) = &mut self.inner {
+            inner.write_all(chunk).map_err(CacheError::Io)?;
+        }
+        self.hasher.update(chunk);
+        self.size += chunk.len() as u64;
+        if let Some(progress) = &mut self.progress {
+            progress(self.size);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes staged so far
+    ///
+    /// # Returns
+    /// `u64` - Staged size in bytes
+    pub fn written(&self) -> u64 {
+        self.size
+    }
+
+    /// Publishes the staged content as the new content of the entry
+    ///
+    /// # Returns
+    /// `CacheResult<WriteSummary>` - Size and checksum of the committed content
+    pub fn commit(mut self) -> CacheResult<WriteSummary> {
+        let object = self.object;
+        let mut span = OpSpan::start("write", object.name());
+        span.bytes(self.size);
+        let inner = self.inner.take().expect("inner writer is only taken by commit");
+        let mut sink = inner.into_inner().map_err(|e| CacheError::Io(e.into_error()));
+        let op = Operation::Write {
+            name: object.name(),
+            size: self.size,
+        };
+        let staging = self.staging.clone();
+        let result = object.run(op, || match (&mut sink, &staging) {
+            (Ok(Sink::Stream(stream)), Some(staging)) => stream
+                .flush()
+                .and_then(|_| object.storage().rename(staging, object.path()))
+                // The staging file was created with the default permissions
+                .and_then(|_| {
+                    if object.is_shared() {
+                        object.storage().set_shared(object.path())
+                    } else {
+                        Ok(())
+                    }
+                })
+                .map_err(CacheError::Io),
+            (Ok(Sink::Collect(content)), _) => object
+                .storage()
+                .write(object.path(), content)
+                .map_err(CacheError::Io),
+            (Ok(Sink::Stream(_)), None) => Err(CacheError::Generic("staging file is missing".to_string())),
+            (Err(e), _) => Err(CacheError::Generic(e.to_string())),
+        });
+        object.report("write", span.record(result))?;
+        self.staging = None;
+        let sha256 = hex(&std::mem::take(&mut self.hasher).finalize());
+        object.finish_write(self.size, None, || Ok(sha256.clone()))?;
+        Ok(WriteSummary { size: self.size, sha256 })
+    }
+
+    /// Discards the staged content, leaving the entry unchanged
+    ///
+    /// Dropping the write has the same effect.
+    pub fn abort(self) {}
+}
+
+impl Drop for ChunkedWrite<'_> {
+    fn drop(&mut self) {
+        // Close the staging file before removing it
+        self.inner.take();
+        if let Some(staging) = self.staging.take() {
+            let _ = self.object.storage().remove(&staging);
+        }
+    }
+}